@@ -5,6 +5,18 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("hex256", |b| {
         b.iter(|| murmur3_str(black_box("0c3ce0caf5997fb777d2e519dfad77c9ff24256bed90")))
     });
+    // Typical table and column names
+    let labels = (0..1000)
+        .map(|i| format!("SYS_GimmickLocation_{i}"))
+        .collect::<Vec<_>>();
+    c.bench_function("labels", |b| {
+        b.iter(|| {
+            black_box(&labels)
+                .iter()
+                .map(|l| murmur3_str(l))
+                .fold(0, u32::wrapping_add)
+        })
+    });
 }
 
 criterion_group!(murmur_bench, criterion_benchmark);
@@ -0,0 +1,128 @@
+//! Read and write benchmarks, over generated files that are large enough to stress the
+//! row and string table loops.
+
+use bdat::legacy::{LegacyColumnBuilder, LegacyFlag, LegacyRow, LegacyTable, LegacyTableBuilder};
+use bdat::modern::{ModernColumn, ModernRow, ModernTable, ModernTableBuilder};
+use bdat::{BdatFile, Cell, Label, LegacyVersion, SwitchEndian, Value, ValueType};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+const ROWS: u32 = 10_000;
+/// Row count for the builder benchmark, large enough to catch repeated rehashing
+const BUILD_ROWS: u32 = 100_000;
+/// Table count for the multi-table write benchmark. Compare the sequential and parallel paths
+/// by running it with and without the `parallel` feature.
+const MANY_TABLES: u32 = 64;
+
+fn modern_table() -> ModernTable<'static> {
+    modern_builder(ROWS).build()
+}
+
+fn modern_builder(row_count: u32) -> ModernTableBuilder<'static> {
+    let rows = (0..row_count).map(|i| {
+        ModernRow::new(vec![
+            Value::HashRef(bdat::hash::murmur3_str(&format!("row_{i}"))),
+            Value::UnsignedInt(i),
+            // Unique strings, so each one needs a string table lookup
+            Value::String(format!("Row {i}").into()),
+            Value::Float((i as f32 / 3.0).into()),
+            Value::MessageId(i as u16),
+            Value::DebugString("".into()),
+        ])
+    });
+    ModernTableBuilder::with_name(Label::Hash(0xCAFEBABE))
+        .set_columns(vec![
+            ModernColumn::new(ValueType::HashRef, Label::Hash(0xDEADBEEF)),
+            ModernColumn::new(ValueType::UnsignedInt, Label::Hash(1)),
+            ModernColumn::new(ValueType::String, Label::Hash(2)),
+            ModernColumn::new(ValueType::Float, Label::Hash(3)),
+            ModernColumn::new(ValueType::MessageId, Label::Hash(4)),
+            ModernColumn::new(ValueType::DebugString, Label::Hash(5)),
+        ])
+        .set_rows(rows.collect())
+}
+
+fn legacy_table() -> LegacyTable<'static> {
+    let rows = (0..ROWS).map(|i| {
+        LegacyRow::new(vec![
+            Cell::Single(Value::UnsignedInt(i)),
+            Cell::Single(Value::String(format!("Row {i}").into())),
+            Cell::Single(Value::Float((i as f32 / 3.0).into())),
+            Cell::List(vec![Value::UnsignedShort(i as u16); 4]),
+            Cell::Flags(vec![i & 1, (i >> 1) & 1]),
+        ])
+    });
+    LegacyTableBuilder::with_name("Bench")
+        .add_column(LegacyColumnBuilder::new(
+            ValueType::UnsignedInt,
+            "Id".into(),
+        ))
+        .add_column(LegacyColumnBuilder::new(ValueType::String, "Name".into()))
+        .add_column(LegacyColumnBuilder::new(ValueType::Float, "Ratio".into()))
+        .add_column(
+            LegacyColumnBuilder::new(ValueType::UnsignedShort, "Params".into())
+                .set_count(4)
+                .build(),
+        )
+        .add_column(
+            LegacyColumnBuilder::new(ValueType::UnsignedByte, "Flags".into())
+                .set_flags(vec![
+                    LegacyFlag::new_bit("Flag1", 0),
+                    LegacyFlag::new_bit("Flag2", 1),
+                ])
+                .build(),
+        )
+        .set_rows(rows.collect())
+        .build()
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let modern = [modern_table()];
+    let modern_bytes = bdat::modern::to_vec::<SwitchEndian>(&modern).unwrap();
+    let legacy = [legacy_table()];
+    let legacy_bytes =
+        bdat::legacy::to_vec::<SwitchEndian>(&legacy, LegacyVersion::Switch).unwrap();
+
+    c.bench_function("modern_read", |b| {
+        b.iter(|| {
+            bdat::modern::from_bytes::<SwitchEndian>(black_box(&modern_bytes))
+                .unwrap()
+                .get_tables()
+                .unwrap()
+        })
+    });
+    c.bench_function("modern_write", |b| {
+        b.iter(|| bdat::modern::to_vec::<SwitchEndian>(black_box(&modern)).unwrap())
+    });
+    let many = (0..MANY_TABLES)
+        .map(|_| modern_builder(ROWS / 10).build())
+        .collect::<Vec<_>>();
+    c.bench_function("modern_write_many", |b| {
+        b.iter(|| bdat::modern::to_vec::<SwitchEndian>(black_box(&many)).unwrap())
+    });
+    c.bench_function("modern_build", |b| {
+        b.iter_batched(
+            || modern_builder(BUILD_ROWS),
+            |builder| builder.build(),
+            BatchSize::LargeInput,
+        )
+    });
+    c.bench_function("legacy_read", |b| {
+        b.iter(|| {
+            bdat::legacy::from_bytes_copy::<SwitchEndian>(
+                black_box(&legacy_bytes),
+                LegacyVersion::Switch,
+            )
+            .unwrap()
+            .get_tables()
+            .unwrap()
+        })
+    });
+    c.bench_function("legacy_write", |b| {
+        b.iter(|| {
+            bdat::legacy::to_vec::<SwitchEndian>(black_box(&legacy), LegacyVersion::Switch).unwrap()
+        })
+    });
+}
+
+criterion_group!(read_write_bench, criterion_benchmark);
+criterion_main!(read_write_bench);
@@ -0,0 +1,75 @@
+use bdat::modern::{ModernColumn, ModernRow, ModernTable, ModernTableBuilder};
+use bdat::{Label, Value, ValueType};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const ROWS: u32 = 10_000;
+
+fn table(changed_every: Option<u32>) -> ModernTable<'static> {
+    let rows = (0..ROWS).map(|i| {
+        let changed = changed_every.is_some_and(|n| i % n == 0);
+        ModernRow::new(vec![
+            Value::HashRef(i),
+            Value::UnsignedInt(if changed { i + 1 } else { i }),
+            Value::String(format!("Row {i}").into()),
+            Value::Float(1.5.into()),
+            Value::MessageId(i as u16),
+        ])
+    });
+    ModernTableBuilder::with_name(Label::Hash(0))
+        .set_columns(vec![
+            ModernColumn::new(ValueType::HashRef, Label::Hash(0)),
+            ModernColumn::new(ValueType::UnsignedInt, Label::Hash(1)),
+            ModernColumn::new(ValueType::String, Label::Hash(2)),
+            ModernColumn::new(ValueType::Float, Label::Hash(3)),
+            ModernColumn::new(ValueType::MessageId, Label::Hash(4)),
+        ])
+        .set_rows(rows.collect())
+        .build()
+}
+
+/// Compares rows cell by cell, looking up columns by label like the diff tool does.
+fn changed_rows_cells(old: &ModernTable, new: &ModernTable) -> usize {
+    let labels = new.column_labels();
+    new.rows()
+        .filter(|new_row| {
+            let old_row = old.row(new_row.id());
+            labels
+                .iter()
+                .any(|label| old_row.get(label.as_ref()) != new_row.get(label.as_ref()))
+        })
+        .count()
+}
+
+/// Only compares cells for rows with different content hashes.
+fn changed_rows_hash(old: &ModernTable, new: &ModernTable) -> usize {
+    let labels = new.column_labels();
+    new.rows()
+        .filter(|new_row| {
+            let old_row = old.row(new_row.id());
+            old_row.content_hash() != new_row.content_hash()
+                && labels
+                    .iter()
+                    .any(|label| old_row.get(label.as_ref()) != new_row.get(label.as_ref()))
+        })
+        .count()
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    // 1% of rows changed
+    let old = table(None);
+    let new = table(Some(100));
+    assert_eq!(
+        changed_rows_cells(&old, &new),
+        changed_rows_hash(&old, &new)
+    );
+
+    c.bench_function("cells", |b| {
+        b.iter(|| changed_rows_cells(black_box(&old), black_box(&new)))
+    });
+    c.bench_function("content_hash", |b| {
+        b.iter(|| changed_rows_hash(black_box(&old), black_box(&new)))
+    });
+}
+
+criterion_group!(row_hash_bench, criterion_benchmark);
+criterion_main!(row_hash_bench);
@@ -0,0 +1,167 @@
+//! Locating BDAT files embedded in game archives
+//!
+//! Xenoblade games pack BDAT files inside larger archives (`.ard` data files, indexed by
+//! `.arh` files). This module does not parse the archive index. Instead, it scans the archive
+//! data for BDAT files, which are always stored at 4-byte aligned offsets.
+//!
+//! Files that are compressed inside the archive can't be found this way, and must be extracted
+//! externally.
+//!
+//! ```
+//! use bdat::{BdatFile, BdatResult};
+//!
+//! fn read_archive(archive: &[u8]) -> BdatResult<()> {
+//!     for file in bdat::archive::find_files(archive) {
+//!         let mut data = file.data().to_vec();
+//!         let tables = bdat::from_bytes(&mut data)?.get_tables()?;
+//!         println!("{} tables at offset {:#x}", tables.len(), file.offset());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! This module is only available when the `archive` feature is enabled.
+
+use std::io::Cursor;
+
+use byteorder::ByteOrder;
+
+use crate::error::Result;
+use crate::io::modern::FILE_VERSION;
+use crate::io::BDAT_MAGIC;
+use crate::legacy::TableHeader;
+use crate::{BdatFile, BdatVersion, Label, LegacyVersion, SwitchEndian, WiiEndian};
+
+/// Legacy files with more tables than this are assumed to be false positives
+const MAX_LEGACY_TABLES: usize = 0xFFFF;
+
+/// A BDAT file found inside an archive.
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedFile<'a> {
+    offset: usize,
+    version: BdatVersion,
+    data: &'a [u8],
+}
+
+impl<'a> EmbeddedFile<'a> {
+    /// Returns the offset of the file, relative to the start of the archive.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the BDAT version of the file.
+    pub fn version(&self) -> BdatVersion {
+        self.version
+    }
+
+    /// Returns the file's data, which can be passed to the slice-based readers.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Reads the names of all tables in the file.
+    pub fn table_names(&self) -> Result<Vec<Label<'a>>> {
+        match self.version {
+            BdatVersion::Modern => {
+                crate::modern::from_bytes::<SwitchEndian>(self.data)?.table_names()
+            }
+            BdatVersion::Legacy(v @ (LegacyVersion::Wii | LegacyVersion::X)) => {
+                crate::legacy::from_bytes_copy::<WiiEndian>(self.data, v)?.table_names()
+            }
+            BdatVersion::Legacy(v) => {
+                crate::legacy::from_bytes_copy::<SwitchEndian>(self.data, v)?.table_names()
+            }
+        }
+    }
+}
+
+/// Scans an archive for BDAT files.
+///
+/// Files are returned in the order they appear in the archive.
+pub fn find_files(archive: &[u8]) -> Vec<EmbeddedFile<'_>> {
+    let mut files = Vec::new();
+    let mut offset = 0;
+    while offset + 16 <= archive.len() {
+        match probe(&archive[offset..]) {
+            Some((version, len)) => {
+                files.push(EmbeddedFile {
+                    offset,
+                    version,
+                    data: &archive[offset..offset + len],
+                });
+                offset += len.next_multiple_of(4).max(4);
+            }
+            None => offset += 4,
+        }
+    }
+    files
+}
+
+/// Scans an archive for the BDAT file that contains a table with the given name.
+///
+/// For modern files, the name is hashed before comparing, if needed.
+///
+/// Files whose table names can't be read (e.g. false positives from the scan) are skipped.
+pub fn find_file_with_table<'a>(archive: &'a [u8], table_name: &Label) -> Option<EmbeddedFile<'a>> {
+    find_files(archive).into_iter().find(|file| {
+        let name = table_name.as_ref().into_hash(file.version);
+        file.table_names().is_ok_and(|names| names.contains(&name))
+    })
+}
+
+/// Checks whether the data starts with a BDAT file, returning its version and length.
+fn probe(data: &[u8]) -> Option<(BdatVersion, usize)> {
+    if data[..4] == BDAT_MAGIC {
+        if SwitchEndian::read_u32(&data[4..]) != FILE_VERSION {
+            return None;
+        }
+        let len = SwitchEndian::read_u32(&data[12..]) as usize;
+        return (len <= data.len()).then_some((BdatVersion::Modern, len));
+    }
+
+    // Legacy files start with the table count and the file size, followed by the table offsets.
+    // The first offset must point to a table.
+    let is_legacy = |count: usize, first_offset: usize| {
+        (1..=MAX_LEGACY_TABLES).contains(&count)
+            && first_offset >= 8 + count * 4
+            && data
+                .get(first_offset..first_offset + 4)
+                .is_some_and(|magic| {
+                    magic == BDAT_MAGIC || magic.iter().eq(BDAT_MAGIC.iter().rev())
+                })
+    };
+    let version = if is_legacy(
+        SwitchEndian::read_u32(data) as usize,
+        SwitchEndian::read_u32(&data[8..]) as usize,
+    ) || is_legacy(
+        WiiEndian::read_u32(data) as usize,
+        WiiEndian::read_u32(&data[8..]) as usize,
+    ) {
+        match crate::detect_bytes_version(data).ok()? {
+            BdatVersion::Legacy(v) => v,
+            BdatVersion::Modern => return None,
+        }
+    } else {
+        return None;
+    };
+
+    let len = match version {
+        LegacyVersion::Wii | LegacyVersion::X => legacy_len::<WiiEndian>(data, version),
+        LegacyVersion::Switch | LegacyVersion::New3ds => legacy_len::<SwitchEndian>(data, version),
+    }?;
+    Some((version.into(), len))
+}
+
+/// Calculates the length of a legacy file from the end of its last table, as the file size
+/// in the header is unreliable.
+fn legacy_len<E: ByteOrder>(data: &[u8], version: LegacyVersion) -> Option<usize> {
+    let count = E::read_u32(data) as usize;
+    let offsets = data.get(8..8 + count * 4)?;
+    let last_table = offsets
+        .chunks_exact(4)
+        .map(|o| E::read_u32(o) as usize)
+        .max()?;
+    let header = TableHeader::read::<E>(Cursor::new(data.get(last_table..)?), version).ok()?;
+    let len = last_table + header.get_table_len();
+    (len <= data.len()).then_some(len)
+}
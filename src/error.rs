@@ -1,5 +1,6 @@
+use crate::legacy::LegacyEncoding;
 use crate::table::convert::FormatConvertError;
-use crate::{BdatVersion, DetectError, Label, ValueType};
+use crate::{BdatVersion, DetectError, Label, RowId, ValueType};
 use std::num::TryFromIntError;
 use std::str::Utf8Error;
 use thiserror::Error;
@@ -16,15 +17,20 @@ pub enum BdatError {
     Io(#[from] std::io::Error),
     #[error("Malformed BDAT ({0:?})")]
     MalformedBdat(Scope),
+    #[error("Unexpected magic bytes {found:02x?}, expected \"BDAT\"")]
+    UnexpectedMagic { found: [u8; 4] },
     #[error(transparent)]
     InvalidLength(#[from] TryFromIntError),
     #[error("Unknown cell type: {0}")]
     UnknownCellType(u8),
     #[error("Unknown value type: {0}")]
     UnknownValueType(u8),
-    #[error("Unsupported type: BDAT version {1:?} does not support value type {0:?}")]
+    #[error(
+        "Unsupported type: {1} tables do not support {0} values (supported types: {})",
+        supported_types(*_1)
+    )]
     UnsupportedType(ValueType, BdatVersion),
-    #[error("Invalid flag type: value type {0:?} does not support flags")]
+    #[error("Invalid flag type: {0} columns can't have flags, only integer columns can")]
     InvalidFlagType(ValueType),
     #[error("Could not detect version: {0}")]
     VersionDetect(#[from] DetectError),
@@ -37,6 +43,52 @@ pub enum BdatError {
         _0.0, _0.1, _0.2, _0.3
     )]
     DuplicateKey(Box<(Label<'static>, Label<'static>, usize, usize)>),
+    #[error("Checksum mismatch in table {table}: expected {expected:#06x}, got {actual:#06x}")]
+    ChecksumMismatch {
+        table: String,
+        expected: u16,
+        actual: u16,
+    },
+    #[error(
+        "Endianness mismatch in table {}: the table was written with the opposite byte order",
+        table_index.map_or_else(|| "?".to_string(), |i| i.to_string())
+    )]
+    EndiannessMismatch { table_index: Option<usize> },
+    #[error(
+        "Invalid UTF-8 string at offset {offset:#x} in table {}",
+        table.as_deref().unwrap_or("?")
+    )]
+    InvalidString {
+        /// The name of the table, if it was read successfully
+        table: Option<String>,
+        /// The offset of the string, relative to the start of the table
+        offset: usize,
+        source: Utf8Error,
+    },
+    #[error("String is not valid in encoding {0:?}")]
+    InvalidEncoding(LegacyEncoding),
+    #[error("Row {row} has a size of {actual} bytes, but its columns require {expected} bytes")]
+    RowSizeMismatch {
+        row: RowId,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Flag {flag_label} does not belong to any column")]
+    OrphanFlag { flag_label: String },
+    #[error("Duplicate table name {0}")]
+    DuplicateTable(Label<'static>),
+    #[error("Table is already scrambled")]
+    AlreadyScrambled,
+}
+
+/// Lists the value types that can be written for the given version.
+fn supported_types(version: BdatVersion) -> String {
+    version
+        .supported_value_types()
+        .iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 #[derive(Debug)]
@@ -44,3 +96,24 @@ pub enum Scope {
     Table,
     File,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::BdatError;
+    use crate::{LegacyVersion, ValueType};
+
+    #[test]
+    fn unsupported_type_message() {
+        let err = BdatError::UnsupportedType(ValueType::HashRef, LegacyVersion::X.into());
+        assert_eq!(
+            "Unsupported type: legacy Wii U (XCX) tables do not support hash reference values \
+            (supported types: unsigned byte, unsigned short, unsigned int, signed byte, \
+            signed short, signed int, string, float)",
+            err.to_string()
+        );
+        assert_eq!(
+            "Invalid flag type: float columns can't have flags, only integer columns can",
+            BdatError::InvalidFlagType(ValueType::Float).to_string()
+        );
+    }
+}
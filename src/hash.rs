@@ -1,6 +1,7 @@
 //! Hash utilities (+ a murmur3 implementation) for XC3 BDATs
 
-const MURMUR3_SEED: u32 = 0;
+/// Seed used by the games when hashing with murmur3
+pub const DEFAULT_MURMUR_SEED: u32 = 0;
 
 #[cfg(feature = "hash-table")]
 pub use table::{IdentityHasher, PreHashedMap};
@@ -61,10 +62,12 @@ macro_rules! label_hash {
 
 // MIT-licensed const version of murmur3, adapted from
 // https://github.com/Reboare/const-murmur3
+/// Hashes the data with murmur3 (32-bit), using the games' seed ([`DEFAULT_MURMUR_SEED`]).
 pub const fn murmur3(data: &[u8]) -> u32 {
-    murmur3_with_seed(data, MURMUR3_SEED)
+    murmur3_with_seed(data, DEFAULT_MURMUR_SEED)
 }
 
+/// Hashes the data with murmur3 (32-bit), using a custom seed.
 pub const fn murmur3_with_seed(data: &[u8], seed: u32) -> u32 {
     let slice_size: usize = data.len();
     let mut hash = seed;
@@ -115,6 +118,7 @@ pub const fn murmur3_with_seed(data: &[u8], seed: u32) -> u32 {
     hash
 }
 
+/// Hashes a string with murmur3 (32-bit), using the games' seed ([`DEFAULT_MURMUR_SEED`]).
 #[inline]
 pub const fn murmur3_str(src: &str) -> u32 {
     murmur3(src.as_bytes())
@@ -134,7 +138,7 @@ const fn murmur3_scramble(data: [u8; 4]) -> u32 {
 
 #[cfg(test)]
 mod tests {
-    use super::murmur3_str;
+    use super::{murmur3, murmur3_str, murmur3_with_seed, DEFAULT_MURMUR_SEED};
 
     #[test]
     fn test_murmur3() {
@@ -142,4 +146,15 @@ mod tests {
         assert_eq!(murmur3_str("FLD_EnemyData"), 0x2521C473);
         assert_eq!(murmur3_str("EVT_listEv"), 0x23EE284B);
     }
+
+    #[test]
+    fn test_murmur3_seed() {
+        assert_eq!(
+            murmur3_with_seed(b"abc", DEFAULT_MURMUR_SEED),
+            murmur3(b"abc")
+        );
+        assert_eq!(murmur3_with_seed(b"", 1), 0x514E28B7);
+        assert_eq!(murmur3_with_seed(b"", 0xFFFFFFFF), 0x81F16F39);
+        assert_ne!(murmur3_with_seed(b"abc", 1), murmur3(b"abc"));
+    }
 }
@@ -8,7 +8,7 @@ use crate::io::read::{BdatFile, BdatReader, BdatSlice};
 use crate::io::BDAT_MAGIC;
 use crate::legacy::read::{LegacyBytes, LegacyReader};
 use crate::modern::FileReader;
-use crate::{BdatVersion, LegacyVersion, SwitchEndian, WiiEndian};
+use crate::{BdatVersion, Label, LegacyVersion, SwitchEndian, WiiEndian};
 
 /// Compatibility file reader returned by [`bdat::from_reader`](`crate::from_reader`)
 pub enum VersionReader<R: Read + Seek> {
@@ -17,7 +17,8 @@ pub enum VersionReader<R: Read + Seek> {
     Modern(FileReader<BdatReader<R, SwitchEndian>, SwitchEndian>),
 }
 
-/// Compatibility slice reader returned by [`bdat::from_bytes`](`crate::from_bytes`)
+/// Compatibility slice reader returned by [`bdat::from_bytes`](`crate::from_bytes`) and
+/// [`bdat::from_bytes_ref`](`crate::from_bytes_ref`)
 pub enum VersionSlice<'b> {
     LegacyWii(LegacyBytes<'b, WiiEndian>),
     LegacySwitch(LegacyBytes<'b, SwitchEndian>),
@@ -44,13 +45,27 @@ pub enum DetectError {
 /// version, use the specialized functions from [`bdat::legacy`] and [`bdat::modern`].  
 /// Notably, only the legacy implementation needs a mutable reference to the data (as it may
 /// need to unscramble text), yet this function is forced to carry that restriction, even when
-/// effectively dealing with modern tables.
+/// effectively dealing with modern tables. If the data can't be borrowed mutably, use
+/// [`from_bytes_ref`] instead.
 ///
 /// Tables read using this function are compatible with most operations.  
 /// If you know in advance that you are dealing with modern (XC3) or legacy (other games) tables,
 /// you should use the specialized functions instead. That way, you can benefit from ergonomic
 /// functions on the [`ModernTable`] and [`LegacyTable`] types.
 ///
+/// Tables borrow strings from the data instead of copying them, so the data can't be
+/// modified while the tables are in use:
+///
+/// ```compile_fail
+/// use bdat::BdatFile;
+///
+/// fn read(data: &mut [u8]) {
+///     let tables = bdat::from_bytes(data).unwrap().get_tables().unwrap();
+///     data[0] = 0;
+///     drop(tables);
+/// }
+/// ```
+///
 /// ## Examples
 ///
 /// ```
@@ -82,6 +97,40 @@ pub fn from_bytes(bytes: &mut [u8]) -> Result<VersionSlice<'_>> {
     }
 }
 
+/// Reads a BDAT file from a slice, without requiring mutable access to the data. The slice
+/// needs to have the **full** file data, though any unrelated bytes at the end will be ignored.
+///
+/// Modern files are always borrowed. Legacy files are also borrowed, unless some of their tables
+/// are scrambled: in that case, the data is copied so it can be unscrambled. If mutable access
+/// is available, [`from_bytes`] can unscramble the data in place.
+///
+/// This function will only read the file header. To parse tables, call [`BdatFile::get_tables`].
+/// See [`from_bytes`] for more details on version detection.
+///
+/// ```
+/// use bdat::{BdatFile, BdatResult};
+///
+/// fn read(data: &[u8]) -> BdatResult<()> {
+///     let tables = bdat::from_bytes_ref(data)?.get_tables()?;
+///     Ok(())
+/// }
+/// ```
+///
+/// [`BdatFile::get_tables`]: crate::BdatFile::get_tables
+pub fn from_bytes_ref(bytes: &[u8]) -> Result<VersionSlice<'_>> {
+    match detect_version(Cursor::new(bytes))? {
+        BdatVersion::Legacy(v @ LegacyVersion::Switch | v @ LegacyVersion::New3ds) => {
+            Ok(VersionSlice::LegacySwitch(LegacyBytes::new_copy(bytes, v)?))
+        }
+        BdatVersion::Legacy(v @ LegacyVersion::Wii | v @ LegacyVersion::X) => {
+            Ok(VersionSlice::LegacyWii(LegacyBytes::new_copy(bytes, v)?))
+        }
+        BdatVersion::Modern => Ok(VersionSlice::Modern(
+            FileReader::<_, SwitchEndian>::read_file(BdatSlice::<SwitchEndian>::new(bytes))?,
+        )),
+    }
+}
+
 /// Reads a BDAT file from a [`std::io::Read`] implementation. That type must also implement
 /// [`std::io::Seek`].
 ///
@@ -248,6 +297,28 @@ fn detect_version<R: Read + Seek>(mut reader: R) -> Result<BdatVersion> {
     })
 }
 
+impl<R: Read + Seek> VersionReader<R> {
+    /// Returns the detected version of the file.
+    pub fn version(&self) -> BdatVersion {
+        match self {
+            Self::LegacyWii(r) => r.version().into(),
+            Self::LegacySwitch(r) => r.version().into(),
+            Self::Modern(_) => BdatVersion::Modern,
+        }
+    }
+}
+
+impl<'b> VersionSlice<'b> {
+    /// Returns the detected version of the file.
+    pub fn version(&self) -> BdatVersion {
+        match self {
+            Self::LegacyWii(r) => r.version().into(),
+            Self::LegacySwitch(r) => r.version().into(),
+            Self::Modern(_) => BdatVersion::Modern,
+        }
+    }
+}
+
 impl<'b, R: Read + Seek> BdatFile<'b> for VersionReader<R> {
     type TableOut = CompatTable<'b>;
 
@@ -272,6 +343,22 @@ impl<'b, R: Read + Seek> BdatFile<'b> for VersionReader<R> {
             Self::Modern(r) => r.table_count(),
         }
     }
+
+    fn table_shapes(&mut self) -> crate::error::Result<Vec<(usize, usize)>> {
+        match self {
+            Self::LegacySwitch(r) => r.table_shapes(),
+            Self::LegacyWii(r) => r.table_shapes(),
+            Self::Modern(r) => r.table_shapes(),
+        }
+    }
+
+    fn table_names(&mut self) -> crate::error::Result<Vec<Label<'b>>> {
+        match self {
+            Self::LegacySwitch(r) => r.table_names(),
+            Self::LegacyWii(r) => r.table_names(),
+            Self::Modern(r) => r.table_names(),
+        }
+    }
 }
 
 impl<'b> BdatFile<'b> for VersionSlice<'b> {
@@ -298,4 +385,20 @@ impl<'b> BdatFile<'b> for VersionSlice<'b> {
             Self::Modern(r) => r.table_count(),
         }
     }
+
+    fn table_shapes(&mut self) -> crate::error::Result<Vec<(usize, usize)>> {
+        match self {
+            Self::LegacySwitch(r) => r.table_shapes(),
+            Self::LegacyWii(r) => r.table_shapes(),
+            Self::Modern(r) => r.table_shapes(),
+        }
+    }
+
+    fn table_names(&mut self) -> crate::error::Result<Vec<Label<'b>>> {
+        match self {
+            Self::LegacySwitch(r) => r.table_names(),
+            Self::LegacyWii(r) => r.table_names(),
+            Self::Modern(r) => r.table_names(),
+        }
+    }
 }
@@ -0,0 +1,45 @@
+use std::borrow::Cow;
+
+use crate::error::Result;
+
+/// Text encoding used for string cells in legacy tables.
+///
+/// Only the values of string cells are affected: table and column names are always
+/// read and written as UTF-8, as they are also used to build the table's hash table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LegacyEncoding {
+    /// UTF-8, used by most files.
+    #[default]
+    Utf8,
+    /// Shift-JIS, found in some Japanese-region files.
+    #[cfg(feature = "encoding")]
+    ShiftJis,
+}
+
+impl LegacyEncoding {
+    /// Decodes a string, without its nul terminator.
+    pub(crate) fn decode(self, bytes: &[u8]) -> Result<Cow<'_, str>> {
+        match self {
+            Self::Utf8 => Ok(Cow::Borrowed(std::str::from_utf8(bytes)?)),
+            #[cfg(feature = "encoding")]
+            Self::ShiftJis => encoding_rs::SHIFT_JIS
+                .decode_without_bom_handling_and_without_replacement(bytes)
+                .ok_or(crate::BdatError::InvalidEncoding(self)),
+        }
+    }
+
+    /// Encodes a string, without adding a nul terminator.
+    pub(crate) fn encode(self, text: &str) -> Result<Cow<'_, [u8]>> {
+        match self {
+            Self::Utf8 => Ok(Cow::Borrowed(text.as_bytes())),
+            #[cfg(feature = "encoding")]
+            Self::ShiftJis => {
+                let (bytes, _, had_errors) = encoding_rs::SHIFT_JIS.encode(text);
+                if had_errors {
+                    return Err(crate::BdatError::InvalidEncoding(self));
+                }
+                Ok(bytes)
+            }
+        }
+    }
+}
@@ -103,7 +103,8 @@ impl From<BdatReal> for f32 {
     }
 }
 
-#[cfg(test)]
+/// The value is stored as [`BdatReal::Unknown`], and will be converted to the proper
+/// representation when written.
 impl From<f32> for BdatReal {
     fn from(value: f32) -> Self {
         Self::Unknown(value)
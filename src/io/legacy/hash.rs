@@ -1,16 +1,40 @@
 use super::util::pad_8;
+use super::HASH_SLOT_SIZE;
 use crate::error::Result;
 use byteorder::{ByteOrder, WriteBytesExt};
 use std::io::{Seek, SeekFrom, Write};
 
 /// A simple hash table with separate chaining.
 /// When the table is written, chain nodes are linked together in column info tables.
+///
+/// This is the column name hash table used by legacy tables. It can be used to find out which
+/// slot a name is stored in, for a given number of slots (see
+/// [`LegacyWriteOptions::hash_slots`]).
+///
+/// The hash of a name is computed from its first 8 bytes: starting with the first character,
+/// each following byte is added to the previous result multiplied by 7. The result is then
+/// reduced modulo the number of slots. Empty names are always placed in slot 0.
+///
+/// ```
+/// use bdat::legacy::LegacyHashTable;
+///
+/// let table = LegacyHashTable::new(61);
+/// assert_eq!(9, table.slot_of("KizunaReward1"));
+/// // Only the first 8 bytes are hashed
+/// assert_eq!(table.slot_of("KizunaReward1"), table.slot_of("KizunaReward2"));
+/// ```
+///
+/// [`LegacyWriteOptions::hash_slots`]: crate::legacy::LegacyWriteOptions::hash_slots
 pub struct HashTable {
     slots: Vec<Vec<u16>>,
     hash_mod: u32,
 }
 
 impl HashTable {
+    /// Creates an empty hash table with the given number of slots.
+    ///
+    /// ## Panics
+    /// Panics when hashing names if the slot count is 0.
     pub fn new(hash_mod: u32) -> Self {
         let mut table = Self {
             slots: Vec::new(),
@@ -34,6 +58,12 @@ impl HashTable {
         self.slots = vec![Vec::new(); self.hash_mod as usize];
     }
 
+    /// Returns the slot index a name is stored in.
+    pub fn slot_of(&self, name: &str) -> usize {
+        self.hash(name) as usize
+    }
+
+    /// Returns the hash of a name, already reduced modulo the number of slots.
     pub fn hash(&self, text: &str) -> u32 {
         if text.is_empty() {
             return 0;
@@ -51,7 +81,7 @@ impl HashTable {
         for slot in &self.slots {
             writer.write_u16::<E>(slot.first().copied().unwrap_or(0))?;
         }
-        let len = self.slots.len() * 2;
+        let len = self.slots.len() * HASH_SLOT_SIZE;
         for _ in len..pad_8(len) {
             writer.write_u8(0)?;
         }
@@ -3,6 +3,7 @@
 pub mod float;
 pub mod scramble;
 
+mod encoding;
 mod hash;
 pub(crate) mod read;
 mod util;
@@ -18,29 +19,41 @@ use std::ops::Range;
 use crate::error::Result;
 use crate::legacy::read::{LegacyBytes, LegacyReader};
 use crate::table::legacy::LegacyTable;
-use crate::LegacyVersion;
+use crate::{BdatFile, LegacyVersion};
 use write::FileWriter;
 
 pub(super) const HEADER_SIZE: usize = 64;
 pub(super) const HEADER_SIZE_WII: usize = 32;
-const COLUMN_NODE_SIZE: usize = 6;
-const COLUMN_NODE_SIZE_WII: usize = 4;
+pub(super) const COLUMN_NODE_SIZE: usize = 6;
+pub(super) const COLUMN_NODE_SIZE_WII: usize = 4;
+/// Size of a slot in the first level of the name hash table
+pub(super) const HASH_SLOT_SIZE: usize = 2;
 
+pub use encoding::LegacyEncoding;
 pub use hash::HashTable as LegacyHashTable;
 
+/// Additional options for reading legacy BDAT tables.
+#[derive(Copy, Clone)]
+pub struct LegacyReadOptions {
+    pub(crate) verify_checksum: bool,
+    pub(crate) encoding: LegacyEncoding,
+    pub(crate) skip_orphan_flags: bool,
+}
+
 /// Additional options for writing legacy BDAT tables.
 #[derive(Copy, Clone)]
 pub struct LegacyWriteOptions {
     pub(crate) hash_slots: usize,
     pub(crate) scramble: bool,
     pub(crate) scramble_key: Option<u16>,
+    pub(crate) preserve_order: bool,
+    pub(crate) encoding: LegacyEncoding,
 }
 
 #[derive(Debug)]
 #[doc(hidden)]
 pub struct FileHeader {
     pub table_count: usize,
-    file_size: usize,
     table_offsets: Vec<usize>,
 }
 
@@ -116,7 +129,33 @@ pub fn from_reader<R: Read + Seek, E: ByteOrder>(
     reader: R,
     version: LegacyVersion,
 ) -> Result<LegacyReader<R, E>> {
-    LegacyReader::new(reader, version)
+    from_reader_options(reader, version, LegacyReadOptions::new())
+}
+
+/// Reads a legacy BDAT file from a [`std::io::Read`] implementation. That type must also implement
+/// [`std::io::Seek`].
+///
+/// This function also allows customization of a few read options, using
+/// [`LegacyReadOptions`]. See [`from_reader`] for more details.
+///
+/// ```
+/// use std::fs::File;
+/// use bdat::{BdatFile, BdatResult, LegacyVersion, SwitchEndian};
+/// use bdat::legacy::LegacyReadOptions;
+///
+/// fn read_file(name: &str) -> BdatResult<()> {
+///     let file = File::open(name)?;
+///     let tables = bdat::legacy::from_reader_options::<_, SwitchEndian>(file, LegacyVersion::Switch,
+///             LegacyReadOptions::new().verify_checksum(true))?.get_tables();
+///     Ok(())
+/// }
+/// ```
+pub fn from_reader_options<R: Read + Seek, E: ByteOrder>(
+    reader: R,
+    version: LegacyVersion,
+    opts: LegacyReadOptions,
+) -> Result<LegacyReader<R, E>> {
+    LegacyReader::new_with_options(reader, version, opts)
 }
 
 /// Reads a BDAT file from a slice. The slice needs to have the **full** file data, though any
@@ -149,7 +188,31 @@ pub fn from_bytes<E: ByteOrder>(
     bytes: &mut [u8],
     version: LegacyVersion,
 ) -> Result<LegacyBytes<'_, E>> {
-    LegacyBytes::new(bytes, version)
+    from_bytes_options(bytes, version, LegacyReadOptions::new())
+}
+
+/// Reads a BDAT file from a slice. The slice needs to have the **full** file data, though any
+/// unrelated bytes at the end will be ignored.
+///
+/// This function also allows customization of a few read options, using
+/// [`LegacyReadOptions`]. See [`from_bytes`] for more details.
+///
+/// ```
+/// use bdat::{BdatFile, BdatResult, LegacyVersion, SwitchEndian};
+/// use bdat::legacy::LegacyReadOptions;
+///
+/// fn read(data: &mut [u8]) -> BdatResult<()> {
+///     let tables = bdat::legacy::from_bytes_options::<SwitchEndian>(data, LegacyVersion::Switch,
+///             LegacyReadOptions::new().verify_checksum(true))?.get_tables();
+///     Ok(())
+/// }
+/// ```
+pub fn from_bytes_options<E: ByteOrder>(
+    bytes: &mut [u8],
+    version: LegacyVersion,
+    opts: LegacyReadOptions,
+) -> Result<LegacyBytes<'_, E>> {
+    LegacyBytes::new_with_options(bytes, version, opts)
 }
 
 /// Reads a BDAT file from a slice. The slice needs to have the **full** file data, though any
@@ -160,7 +223,8 @@ pub fn from_bytes<E: ByteOrder>(
 /// [`bdat::from_bytes`].
 ///
 /// Unlike [`from_bytes`], this doesn't require mutable access to the data, at the cost of
-/// potentially copying the data if there's a need to unscramble it.
+/// potentially copying the data if there's a need to unscramble it. If no table is scrambled,
+/// the data is borrowed instead, so tables can borrow strings from `bytes`.
 ///
 /// This function will only read the file header. To parse tables, call [`BdatFile::get_tables`].
 ///
@@ -181,7 +245,31 @@ pub fn from_bytes_copy<E: ByteOrder>(
     bytes: &[u8],
     version: LegacyVersion,
 ) -> Result<LegacyBytes<'_, E>> {
-    LegacyBytes::new_copy(bytes, version)
+    from_bytes_copy_options(bytes, version, LegacyReadOptions::new())
+}
+
+/// Reads a BDAT file from a slice, copying the data if needed. See [`from_bytes_copy`] for
+/// more details.
+///
+/// This function also allows customization of a few read options, using
+/// [`LegacyReadOptions`].
+///
+/// ```
+/// use bdat::{BdatFile, BdatResult, LegacyVersion, SwitchEndian};
+/// use bdat::legacy::LegacyReadOptions;
+///
+/// fn read(data: &[u8]) -> BdatResult<()> {
+///     let tables = bdat::legacy::from_bytes_copy_options::<SwitchEndian>(data, LegacyVersion::Switch,
+///             LegacyReadOptions::new().verify_checksum(true))?.get_tables();
+///     Ok(())
+/// }
+/// ```
+pub fn from_bytes_copy_options<E: ByteOrder>(
+    bytes: &[u8],
+    version: LegacyVersion,
+    opts: LegacyReadOptions,
+) -> Result<LegacyBytes<'_, E>> {
+    LegacyBytes::new_copy_with_options(bytes, version, opts)
 }
 
 /// Writes legacy BDAT tables to a [`std::io::Write`] implementation
@@ -281,12 +369,117 @@ pub fn to_vec_options<'t, E: ByteOrder + 'static>(
     Ok(vec)
 }
 
+/// Writes groups of legacy BDAT tables to separate files, returned as `Vec<u8>`s.
+///
+/// Each group is written as its own file, and is returned along with its name (e.g. the
+/// name of the file the tables were extracted from), in the same order.
+///
+/// ```
+/// use bdat::{BdatResult, legacy::LegacyTable, SwitchEndian, LegacyVersion};
+///
+/// fn write_files(a: &[LegacyTable], b: &[LegacyTable]) -> BdatResult<()> {
+///     let files = bdat::legacy::to_vec_grouped::<SwitchEndian, _>(
+///         [("a.bdat", a), ("b.bdat", b)],
+///         LegacyVersion::Switch,
+///     )?;
+///     for (name, bytes) in files {
+///         println!("{name}: {} bytes", bytes.len());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn to_vec_grouped<'t, E: ByteOrder + 'static, N>(
+    groups: impl IntoIterator<Item = (N, impl IntoIterator<Item = impl Borrow<LegacyTable<'t>>>)>,
+    version: LegacyVersion,
+) -> Result<Vec<(N, Vec<u8>)>> {
+    groups
+        .into_iter()
+        .map(|(name, tables)| Ok((name, to_vec::<E>(tables, version)?)))
+        .collect()
+}
+
+/// Reads a legacy BDAT file with byte order `EIn`, then writes its tables with byte order
+/// `EOut`, keeping the same format version.
+///
+/// Tables are written using the default [`LegacyWriteOptions`]. Like [`from_bytes`], this
+/// needs a mutable reference to the data, as tables may be unscrambled in place.
+///
+/// ```
+/// use bdat::{BdatResult, LegacyVersion, SwitchEndian, WiiEndian};
+///
+/// fn to_big_endian(data: &mut [u8]) -> BdatResult<Vec<u8>> {
+///     bdat::legacy::transcode_endianness::<SwitchEndian, WiiEndian>(data, LegacyVersion::Switch)
+/// }
+/// ```
+pub fn transcode_endianness<EIn: ByteOrder, EOut: ByteOrder + 'static>(
+    input: &mut [u8],
+    version: LegacyVersion,
+) -> Result<Vec<u8>> {
+    let tables = from_bytes::<EIn>(input, version)?.get_tables()?;
+    to_vec::<EOut>(&tables, version)
+}
+
+impl LegacyReadOptions {
+    pub const fn new() -> Self {
+        Self {
+            verify_checksum: false,
+            encoding: LegacyEncoding::Utf8,
+            skip_orphan_flags: false,
+        }
+    }
+
+    /// Sets whether the checksum of scrambled tables should be verified during read.
+    ///
+    /// When enabled, the checksum is recalculated after unscrambling each table, and
+    /// compared with the scramble key stored in the table header. If they don't match,
+    /// reading fails with [`BdatError::ChecksumMismatch`].
+    ///
+    /// Tables that are not scrambled don't store a checksum, so they are never verified.
+    ///
+    /// By default, checksums are not verified.
+    ///
+    /// [`BdatError::ChecksumMismatch`]: crate::BdatError::ChecksumMismatch
+    pub fn verify_checksum(mut self, verify: bool) -> Self {
+        self.verify_checksum = verify;
+        self
+    }
+
+    /// Sets the text encoding used to decode string cells.
+    ///
+    /// The default is [`LegacyEncoding::Utf8`].
+    pub fn encoding(mut self, encoding: LegacyEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets whether flags whose parent column can't be found should be dropped.
+    ///
+    /// Such flags can only be found in malformed files. When this is disabled, reading
+    /// fails with [`BdatError::OrphanFlag`] instead.
+    ///
+    /// By default, orphan flags are not dropped.
+    ///
+    /// [`BdatError::OrphanFlag`]: crate::BdatError::OrphanFlag
+    pub fn skip_orphan_flags(mut self, skip: bool) -> Self {
+        self.skip_orphan_flags = skip;
+        self
+    }
+}
+
+impl Default for LegacyReadOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LegacyWriteOptions {
     pub const fn new() -> Self {
         Self {
             hash_slots: 61, // used for all tables in 1/X/2/DE
             scramble: false,
             scramble_key: None, // calculated checksum by default
+            preserve_order: false,
+            encoding: LegacyEncoding::Utf8,
         }
     }
 
@@ -304,6 +497,10 @@ impl LegacyWriteOptions {
 
     /// Sets whether tables should be scrambled during write.
     ///
+    /// The scrambled sections are the name table and the string table. The padding at the
+    /// end of the table is considered part of the string table (its size includes it), so it
+    /// is scrambled too, and the table's checksum matches after the table is unscrambled.
+    ///
     /// By default, tables are not scrambled.
     pub fn scramble(mut self, scramble: bool) -> Self {
         self.scramble = scramble;
@@ -318,6 +515,29 @@ impl LegacyWriteOptions {
         self.scramble_key = Some(scramble_key);
         self
     }
+
+    /// Sets whether tables should be written in the order they are given, instead of
+    /// being sorted by name.
+    ///
+    /// Games look up tables by name using a binary search, so they expect tables to be
+    /// sorted. Official files always respect that order, which is why sorting is the default.
+    /// Disabling it is useful when the original table order must be reproduced exactly
+    /// (e.g. when repacking tables from a file that was not sorted to begin with), but the
+    /// caller is then responsible for providing tables in an order the game can handle.
+    ///
+    /// By default, tables are sorted by name.
+    pub fn preserve_order(mut self, preserve_order: bool) -> Self {
+        self.preserve_order = preserve_order;
+        self
+    }
+
+    /// Sets the text encoding used to encode string cells.
+    ///
+    /// The default is [`LegacyEncoding::Utf8`].
+    pub fn encoding(mut self, encoding: LegacyEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
 }
 
 impl Default for LegacyWriteOptions {
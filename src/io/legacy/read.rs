@@ -3,16 +3,17 @@ use std::collections::{HashSet, VecDeque};
 use std::ffi::CStr;
 use std::io::{Cursor, Read, Seek, SeekFrom};
 use std::marker::PhantomData;
+use std::ops::Range;
 
 use byteorder::{ByteOrder, NativeEndian, ReadBytesExt, WriteBytesExt};
 
 use super::float::BdatReal;
 use super::scramble::{calc_checksum, scramble, unscramble, ScrambleType};
-use super::{ColumnNodeInfo, COLUMN_NODE_SIZE};
+use super::{ColumnNodeInfo, LegacyEncoding, LegacyReadOptions, COLUMN_NODE_SIZE};
 use crate::error::{Result, Scope};
 use crate::io::BDAT_MAGIC;
 use crate::legacy::{LegacyColumn, LegacyFlag, LegacyRow, LegacyTable, LegacyTableBuilder};
-use crate::{BdatError, BdatFile, Cell, LegacyVersion, Utf, Value, ValueType};
+use crate::{BdatError, BdatFile, Cell, Label, LegacyVersion, Utf, Value, ValueType};
 
 use super::{FileHeader, TableHeader};
 
@@ -21,6 +22,7 @@ pub struct LegacyBytes<'t, E> {
     data: Cow<'t, [u8]>,
     header: FileHeader,
     version: LegacyVersion,
+    opts: LegacyReadOptions,
     table_headers: Vec<TableHeader>,
     _endianness: PhantomData<E>,
 }
@@ -29,14 +31,20 @@ pub struct LegacyBytes<'t, E> {
 pub struct LegacyReader<R, E> {
     reader: R,
     header: FileHeader,
+    stream_len: usize,
     version: LegacyVersion,
+    opts: LegacyReadOptions,
     _endianness: PhantomData<E>,
 }
 
 /// Reads tables in a file.
 struct TableReader<'t, E> {
     header: TableHeader,
+    /// The table's name, for error reporting
+    name: Option<String>,
     version: LegacyVersion,
+    encoding: LegacyEncoding,
+    skip_orphan_flags: bool,
     data: Cursor<Cow<'t, [u8]>>,
     _endianness: PhantomData<E>,
 }
@@ -98,11 +106,24 @@ struct TableColumns<'t> {
 struct Flags<'t>(Vec<ColumnData<'t>>);
 
 impl<R: Read + Seek, E: ByteOrder> LegacyReader<R, E> {
-    pub fn new(mut reader: R, version: LegacyVersion) -> Result<Self> {
+    pub fn new(reader: R, version: LegacyVersion) -> Result<Self> {
+        Self::new_with_options(reader, version, LegacyReadOptions::new())
+    }
+
+    pub fn new_with_options(
+        mut reader: R,
+        version: LegacyVersion,
+        opts: LegacyReadOptions,
+    ) -> Result<Self> {
+        let start = reader.stream_position()?;
+        let stream_len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
         let header = FileHeader::read::<_, E>(&mut reader)?;
         Ok(Self {
             header,
+            stream_len: stream_len.try_into()?,
             version,
+            opts,
             reader,
             _endianness: PhantomData,
         })
@@ -111,50 +132,185 @@ impl<R: Read + Seek, E: ByteOrder> LegacyReader<R, E> {
 
 impl<'t, E: ByteOrder> LegacyBytes<'t, E> {
     pub fn new(bytes: &'t mut [u8], version: LegacyVersion) -> Result<Self> {
+        Self::new_with_options(bytes, version, LegacyReadOptions::new())
+    }
+
+    pub fn new_with_options(
+        bytes: &'t mut [u8],
+        version: LegacyVersion,
+        opts: LegacyReadOptions,
+    ) -> Result<Self> {
         let header = FileHeader::read::<_, E>(Cursor::new(&bytes))?;
         let mut headers = vec![];
         header.for_each_table_mut(bytes, |table| {
-            let header = TableHeader::read::<E>(Cursor::new(&table), version)?;
+            let header = TableHeader::read::<E>(Cursor::new(&table), version)
+                .map_err(at_table(headers.len()))?;
+            header.check_bounds(table.len())?;
             header.unscramble_data(table);
+            if opts.verify_checksum {
+                header.verify_checksum(table)?;
+            }
             headers.push(header);
             Ok::<_, BdatError>(())
         })?;
         Ok(Self {
             header,
             version,
+            opts,
             data: Cow::Borrowed(bytes),
             table_headers: headers,
             _endianness: PhantomData,
         })
     }
 
-    pub fn new_copy(bytes: &[u8], version: LegacyVersion) -> Result<Self> {
+    /// Like [`LegacyBytes::new`], but without mutable access to the data.
+    ///
+    /// Despite the name, the data is only copied if some table needs to be unscrambled (or
+    /// can't be validated upfront). Otherwise, the data is borrowed, and tables read from it
+    /// borrow their strings from `bytes`. Earlier versions always copied the data.
+    pub fn new_copy(bytes: &'t [u8], version: LegacyVersion) -> Result<Self> {
+        Self::new_copy_with_options(bytes, version, LegacyReadOptions::new())
+    }
+
+    /// Like [`LegacyBytes::new_copy`], with additional read options.
+    pub fn new_copy_with_options(
+        bytes: &'t [u8],
+        version: LegacyVersion,
+        opts: LegacyReadOptions,
+    ) -> Result<Self> {
         let header = FileHeader::read::<_, E>(Cursor::new(&bytes))?;
+        // Invalid tables are also copied, errors are reported when reading tables
+        let headers = header
+            .table_offsets
+            .iter()
+            .map(|&offset| {
+                let table = bytes.get(offset..)?;
+                let table_header = TableHeader::read::<E>(Cursor::new(table), version).ok()?;
+                (table_header.scramble_type == ScrambleType::None
+                    && table_header.check_bounds(table.len()).is_ok())
+                .then_some(table_header)
+            })
+            .collect::<Option<Vec<_>>>();
+        let (data, table_headers) = match headers {
+            Some(headers) => (Cow::Borrowed(bytes), headers),
+            None => (Cow::Owned(bytes.to_vec()), Vec::new()),
+        };
         Ok(Self {
             header,
             version,
-            data: Cow::Owned(bytes.to_vec()),
-            table_headers: Vec::new(),
+            opts,
+            data,
+            table_headers,
             _endianness: PhantomData,
         })
     }
 }
 
+impl<R: Read + Seek, E: ByteOrder> LegacyReader<R, E> {
+    /// Reads the table at the given index, without reading any other table.
+    ///
+    /// Returns `None` if there is no such table. Table names can be read beforehand with
+    /// [`BdatFile::table_names`], to find the index of the table.
+    pub fn get_table<'b>(&mut self, index: usize) -> Result<Option<LegacyTable<'b>>> {
+        self.table_reader(index)?.map(TableReader::read).transpose()
+    }
+
+    fn table_reader<'b>(&mut self, index: usize) -> Result<Option<TableReader<'b, E>>> {
+        let Some(&offset) = self.header.table_offsets.get(index) else {
+            return Ok(None);
+        };
+        self.reader.seek(SeekFrom::Start(offset as u64))?;
+        TableReader::<E>::from_reader(&mut self.reader, self.version, self.opts)
+            .map(Some)
+            .map_err(at_table(index))
+    }
+}
+
+impl<R, E> LegacyReader<R, E> {
+    /// Returns the format version the file is read with.
+    pub fn version(&self) -> LegacyVersion {
+        self.version
+    }
+
+    /// Returns the byte range occupied by the table at the given index, relative to the
+    /// start of the file, or `None` if there is no such table.
+    ///
+    /// The range ends at the next table's offset, or at the end of the stream for the
+    /// last table. This can be used to patch a single table without rewriting the file.
+    pub fn table_byte_range(&self, index: usize) -> Option<Range<usize>> {
+        self.header.table_byte_range(index, self.stream_len)
+    }
+}
+
+impl<'t, E: ByteOrder> LegacyBytes<'t, E> {
+    /// Reads the table at the given index, without reading any other table.
+    ///
+    /// Returns `None` if there is no such table. Table names can be read beforehand with
+    /// [`BdatFile::table_names`], to find the index of the table.
+    pub fn get_table(&self, index: usize) -> Result<Option<LegacyTable<'t>>> {
+        self.table_reader(index)?.map(TableReader::read).transpose()
+    }
+
+    fn table_reader(&self, index: usize) -> Result<Option<TableReader<'t, E>>> {
+        let Some(&offset) = self.header.table_offsets.get(index) else {
+            return Ok(None);
+        };
+        match &self.data {
+            Cow::Owned(buf) => {
+                TableReader::<E>::from_reader(Cursor::new(&buf[offset..]), self.version, self.opts)
+            }
+            Cow::Borrowed(data) => TableReader::<E>::from_slice(
+                &data[offset..],
+                self.version,
+                self.opts,
+                self.table_headers.get(index).cloned(),
+            ),
+        }
+        .map(Some)
+        .map_err(at_table(index))
+    }
+}
+
+impl<'t, E> LegacyBytes<'t, E> {
+    /// Returns the format version the file is read with.
+    pub fn version(&self) -> LegacyVersion {
+        self.version
+    }
+
+    /// Returns the byte range occupied by the table at the given index, relative to the
+    /// start of the file, or `None` if there is no such table.
+    ///
+    /// The range ends at the next table's offset, or at the end of the data for the
+    /// last table. This can be used to patch a single table without rewriting the file.
+    pub fn table_byte_range(&self, index: usize) -> Option<Range<usize>> {
+        self.header.table_byte_range(index, self.data.len())
+    }
+}
+
 impl FileHeader {
     pub fn read<R: Read + Seek, E: ByteOrder>(mut reader: R) -> Result<Self> {
         let table_count = reader.read_u32::<E>()? as usize;
-        let file_size = reader.read_u32::<E>()? as usize;
+        reader.read_u32::<E>()?; // File size, unreliable (see for_each_table_mut)
         let mut offsets = Vec::with_capacity(table_count);
         for _ in 0..table_count {
             offsets.push(reader.read_u32::<E>()? as usize);
         }
         Ok(Self {
             table_count,
-            file_size,
             table_offsets: offsets,
         })
     }
 
+    fn table_byte_range(&self, index: usize, file_len: usize) -> Option<Range<usize>> {
+        let start = *self.table_offsets.get(index)?;
+        let end = self
+            .table_offsets
+            .get(index + 1)
+            .copied()
+            .unwrap_or(file_len);
+        (start <= end).then_some(start..end)
+    }
+
     pub fn for_each_table_mut<F, E>(&self, data: &mut [u8], mut f: F) -> std::result::Result<(), E>
     where
         F: FnMut(&mut [u8]) -> std::result::Result<(), E>,
@@ -162,10 +318,11 @@ impl FileHeader {
         // An iterator for this would require unsafe code because it's returning mutable
         // references
 
-        // Weird XC3D quirk where reported file size is higher than the actual size.
-        // Still appears to work well for all files, so the reported file probably
-        // accounts for padding that is not exported in dumps.
-        let file_size = self.file_size.min(data.len());
+        // The reported file size can't be trusted: in XC3D it is higher than the actual
+        // size (likely accounting for padding that is not exported in dumps), and some tools
+        // write sizes that don't account for the file header. The last table is instead
+        // assumed to extend to the end of the data, the table header knows its actual length.
+        let file_size = data.len();
 
         match self.table_offsets.len() {
             0 => return Ok(()),
@@ -203,7 +360,8 @@ impl TableHeader {
             }
         }
 
-        // Bit 0: seems to be 1 for Big Endian, 0 for Little Endian
+        // Bit 0: unknown. It is set in most big-endian files, but not all of them (e.g. XC1 Wii),
+        // so it can't be used to detect endianness. See the constant below instead.
         // Bit 1: whether the table is scrambled
         let flags = reader.read_u8()? as usize;
         reader.read_u8()?;
@@ -214,7 +372,13 @@ impl TableHeader {
         let offset_rows = reader.read_u16::<E>()? as usize;
         let row_count = reader.read_u16::<E>()? as usize;
         let base_id = reader.read_u16::<E>()?;
-        assert_eq!(2, reader.read_u16::<E>()?, "unknown constant is not 2");
+        match reader.read_u16::<E>()? {
+            2 => {}
+            // The constant is always 2, so this is the only reliable way to tell
+            // that the table was written with the other byte order.
+            0x200 => return Err(BdatError::EndiannessMismatch { table_index: None }),
+            _ => return Err(BdatError::MalformedBdat(Scope::Table)),
+        }
         let scramble_key = reader.read_u16::<E>()?;
         let offset_strings = reader.read_u32::<E>()? as usize;
         let strings_len = reader.read_u32::<E>()? as usize;
@@ -264,16 +428,49 @@ impl TableHeader {
 
     /// Scrambles the given byte slice, calculating the checksum automatically.
     /// The given slice must contain the full table.
-    pub fn scramble_data<E: ByteOrder>(&self, data: &mut [u8]) {
-        if self.scramble_type != ScrambleType::None {
-            return;
+    ///
+    /// The scrambled flag in the data is checked rather than this header, as the header
+    /// might be outdated if the data was unscrambled with [`unscramble_data`]. If the data is
+    /// already scrambled, [`BdatError::AlreadyScrambled`] is returned, as scrambling it again
+    /// would make it unrecoverable. If the slice is shorter than the table, this fails with
+    /// [`BdatError::MalformedBdat`].
+    ///
+    /// Earlier versions returned `()` and scrambled the data unconditionally.
+    ///
+    /// [`unscramble_data`]: Self::unscramble_data
+    pub fn scramble_data<E: ByteOrder>(&self, data: &mut [u8]) -> Result<()> {
+        if data.len() < self.get_table_len().max(self.hashes.offset).max(0x18) {
+            return Err(BdatError::MalformedBdat(Scope::Table));
+        }
+        if data[4] & 0b10 != 0 {
+            return Err(BdatError::AlreadyScrambled);
         }
-        let checksum = calc_checksum(data);
+        let checksum = calc_checksum(&data[..self.get_table_len()]);
         // Scramble column names and string table
         scramble(&mut data[self.offset_names..self.hashes.offset], checksum);
         scramble(&mut data[self.strings.range()], checksum);
         (&mut data[0x16..0x18]).write_u16::<E>(checksum).unwrap();
         data[4] |= 0b10; // set scrambled flag
+        Ok(())
+    }
+
+    /// Checks whether the stored scramble key matches the table's checksum.
+    /// The given slice must contain the full, unscrambled table.
+    ///
+    /// Tables that are not scrambled are always considered valid.
+    pub fn verify_checksum(&self, data: &[u8]) -> Result<()> {
+        let ScrambleType::Scrambled(expected) = self.scramble_type else {
+            return Ok(());
+        };
+        let actual = calc_checksum(&data[..self.get_table_len().min(data.len())]);
+        if actual != expected {
+            return Err(BdatError::ChecksumMismatch {
+                table: self.read_name(data).unwrap_or_default().to_string(),
+                expected,
+                actual,
+            });
+        }
+        Ok(())
     }
 
     /// Attempts to read the name of the table. The given slice must contain the full table.
@@ -282,7 +479,32 @@ impl TableHeader {
         TableReader::<NativeEndian>::read_str(data, self.offset_names)
     }
 
-    fn get_table_len(&self) -> usize {
+    /// Reads the name of the table, unscrambling it if needed. The given slice must contain
+    /// the table data up to the start of the hash table, and it is left untouched.
+    pub fn read_name_copy(&self, data: &[u8]) -> Result<String> {
+        let mut names = data
+            .get(self.offset_names..self.hashes.offset)
+            .ok_or(BdatError::MalformedBdat(Scope::Table))?
+            .to_vec();
+        if let ScrambleType::Scrambled(key) = self.scramble_type {
+            unscramble(&mut names, key);
+        }
+        TableReader::<NativeEndian>::read_str(&names, 0).map(ToString::to_string)
+    }
+
+    /// Checks whether the sections described by the header fit in a table of the given
+    /// length, so they can be safely accessed.
+    fn check_bounds(&self, table_len: usize) -> Result<()> {
+        if self.get_table_len() > table_len
+            || self.offset_names > self.hashes.offset
+            || self.hashes.offset > table_len
+        {
+            return Err(BdatError::MalformedBdat(Scope::Table));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn get_table_len(&self) -> usize {
         // All legacy games expect the table length to be determined by the last byte
         // of the string table. (see Bdat::calcCheckSum)
         self.strings.max_offset()
@@ -290,12 +512,21 @@ impl TableHeader {
 }
 
 impl<'t, E: ByteOrder> TableReader<'t, E> {
-    fn from_reader<R: Read + Seek>(mut reader: R, version: LegacyVersion) -> Result<Self> {
+    fn from_reader<R: Read + Seek>(
+        mut reader: R,
+        version: LegacyVersion,
+        opts: LegacyReadOptions,
+    ) -> Result<Self> {
         let original_pos = reader.stream_position()?;
         let header = TableHeader::read::<E>(&mut reader, version)?;
-        reader.seek(SeekFrom::Start(original_pos))?;
 
+        // Don't trust the header with the allocation size, the table can't possibly be larger
+        // than what's left in the reader
         let table_len = header.get_table_len();
+        let remaining = reader.seek(SeekFrom::End(0))?.saturating_sub(original_pos);
+        reader.seek(SeekFrom::Start(original_pos))?;
+        header.check_bounds(remaining.try_into().unwrap_or(usize::MAX))?;
+
         let mut table_data: Vec<u8> = Vec::with_capacity(table_len);
         let bytes_read = reader
             .take(table_len.try_into()?)
@@ -308,10 +539,16 @@ impl<'t, E: ByteOrder> TableReader<'t, E> {
             ScrambleType::Scrambled(_) => header.unscramble_data(&mut table_data),
             ScrambleType::None => {}
         };
+        if opts.verify_checksum {
+            header.verify_checksum(&table_data)?;
+        }
 
         Ok(Self {
             header,
+            name: None,
             version,
+            encoding: opts.encoding,
+            skip_orphan_flags: opts.skip_orphan_flags,
             data: Cursor::new(Cow::Owned(table_data)),
             _endianness: PhantomData,
         })
@@ -320,6 +557,7 @@ impl<'t, E: ByteOrder> TableReader<'t, E> {
     fn from_slice(
         bytes: &'t [u8],
         version: LegacyVersion,
+        opts: LegacyReadOptions,
         header: Option<TableHeader>,
     ) -> Result<TableReader<'t, E>> {
         let mut reader = Cursor::new(&bytes);
@@ -332,21 +570,28 @@ impl<'t, E: ByteOrder> TableReader<'t, E> {
 
         Ok(Self {
             header,
+            name: None,
             version,
+            encoding: opts.encoding,
+            skip_orphan_flags: opts.skip_orphan_flags,
             data: Cursor::new(Cow::Borrowed(bytes)),
             _endianness: PhantomData,
         })
     }
 
+    /// Returns the table's row and column counts, reading column definitions but no rows.
+    fn shape(self) -> Result<(usize, usize)> {
+        let columns = self.discover_columns()?;
+        Ok((self.header.row_count, columns.columns.len()))
+    }
+
     fn read(mut self) -> Result<LegacyTable<'t>> {
         let name = self.read_string(self.header.offset_names)?.to_string();
+        self.name = Some(name.clone());
         let TableColumns {
             columns: columns_src,
             flags,
-        } = match self.header.columns {
-            Some(info) => self.discover_columns_from_nodes(&info),
-            None => self.discover_columns_from_hash(),
-        }?;
+        } = self.discover_columns()?;
 
         // De-flag-ify
         let columns = columns_src
@@ -394,6 +639,32 @@ impl<'t, E: ByteOrder> TableReader<'t, E> {
             .build())
     }
 
+    fn discover_columns(&self) -> Result<TableColumns<'t>> {
+        let TableColumns { columns, flags } = match self.header.columns {
+            Some(info) => self.discover_columns_from_nodes(&info),
+            None => self.discover_columns_from_hash(),
+        }?;
+
+        // Flags whose parent is not a column would otherwise be dropped silently
+        let parents = columns
+            .iter()
+            .map(|c| c.info_offset)
+            .collect::<HashSet<_>>();
+        let (flags, orphans): (Vec<_>, Vec<_>) = flags
+            .0
+            .into_iter()
+            .partition(|f| parents.contains(&Flags::extract(f)));
+        if let Some(orphan) = orphans.first() {
+            if !self.skip_orphan_flags {
+                return Err(BdatError::OrphanFlag {
+                    flag_label: orphan.name.to_string(),
+                });
+            }
+        }
+        let flags = Flags::new(flags);
+        Ok(TableColumns { columns, flags })
+    }
+
     fn discover_columns_from_nodes(&self, info: &ColumnNodeInfo) -> Result<TableColumns<'t>> {
         let mut seek = info.offset_columns.try_into()?;
         let (flags, columns) = (0..info.column_count)
@@ -457,13 +728,54 @@ impl<'t, E: ByteOrder> TableReader<'t, E> {
             Cow::Owned(owned) => Ok(Self::read_str(owned, offset)?.to_string().into()),
             Cow::Borrowed(borrowed) => Self::read_str(borrowed, offset).map(Cow::Borrowed),
         };
-        res
+        res.map_err(|e| self.string_error(e, offset))
     }
 
-    fn read_str(bytes: &[u8], offset: usize) -> Result<&str> {
+    /// Reads the value of a string cell, using the configured encoding.
+    fn read_value_string(&self, offset: usize) -> Result<Utf<'t>> {
+        match self.data.get_ref() {
+            Cow::Owned(owned) => Ok(self
+                .encoding
+                .decode(Self::read_bytes(owned, offset)?)
+                .map_err(|e| self.string_error(e, offset))?
+                .into_owned()
+                .into()),
+            Cow::Borrowed(borrowed) => self
+                .encoding
+                .decode(Self::read_bytes(borrowed, offset)?)
+                .map_err(|e| self.string_error(e, offset)),
+        }
+    }
+
+    /// Adds the table name and string offset to UTF-8 errors.
+    fn string_error(&self, err: BdatError, offset: usize) -> BdatError {
+        match err {
+            BdatError::Utf8(source) | BdatError::InvalidString { source, .. } => {
+                BdatError::InvalidString {
+                    table: self.name.clone(),
+                    offset,
+                    source,
+                }
+            }
+            e => e,
+        }
+    }
+
+    fn read_bytes(bytes: &[u8], offset: usize) -> Result<&[u8]> {
         Ok(CStr::from_bytes_until_nul(&bytes[offset..])
             .map_err(eof)?
-            .to_str()?)
+            .to_bytes())
+    }
+
+    fn read_str(bytes: &[u8], offset: usize) -> Result<&str> {
+        CStr::from_bytes_until_nul(&bytes[offset..])
+            .map_err(eof)?
+            .to_str()
+            .map_err(|source| BdatError::InvalidString {
+                table: None,
+                offset,
+                source,
+            })
     }
 }
 
@@ -537,7 +849,11 @@ impl<'a, 't: 'a, E: ByteOrder + 'a> ColumnReader<'a, 't, E> {
         let flag_index = info_table.read_u8()?;
         let flag_mask = info_table.read_u32::<E>()?;
         let parent_offset = info_table.read_u16::<E>()? as usize;
-        let parent_info_offset = (&full_table[parent_offset..]).read_u16::<E>()? as usize;
+        let parent_info_offset = match full_table.get(parent_offset..) {
+            Some(mut parent) => parent.read_u16::<E>()? as usize,
+            // Dangling parent pointer, this can't match any column
+            None => usize::MAX,
+        };
         Ok(FlagData {
             index: flag_index as usize,
             mask: flag_mask,
@@ -635,7 +951,7 @@ impl<'a, 't, E: ByteOrder> RowReader<'a, 't, E> {
             ValueType::String => {
                 let offset = buf.read_u32::<E>()? as usize;
                 // explicit return to get rid of the `buf` mutable borrow early
-                return Ok(Value::String(self.table.read_string(offset)?));
+                return Ok(Value::String(self.table.read_value_string(offset)?));
             }
             ValueType::Float => Value::Float(BdatReal::from_bits(
                 buf.read_u32::<E>()?,
@@ -690,43 +1006,83 @@ impl<'b, R: Read + Seek, E: ByteOrder> BdatFile<'b> for LegacyReader<R, E> {
     type TableOut = LegacyTable<'b>;
 
     fn get_tables(&mut self) -> Result<Vec<LegacyTable<'b>>> {
-        let mut tables = Vec::with_capacity(self.header.table_count);
-        for offset in &self.header.table_offsets {
-            self.reader.seek(SeekFrom::Start(*offset as u64))?;
-            tables.push(TableReader::<E>::from_reader(&mut self.reader, self.version)?.read()?);
-        }
-        Ok(tables)
+        (0..self.header.table_count)
+            .map(|i| Ok(self.get_table(i)?.expect("table index out of bounds")))
+            .collect()
     }
 
     fn table_count(&self) -> usize {
         self.header.table_count
     }
+
+    fn table_shapes(&mut self) -> Result<Vec<(usize, usize)>> {
+        (0..self.header.table_count)
+            .map(|i| {
+                self.table_reader(i)?
+                    .expect("table index out of bounds")
+                    .shape()
+            })
+            .collect()
+    }
+
+    fn table_names(&mut self) -> Result<Vec<Label<'b>>> {
+        let mut names = Vec::with_capacity(self.header.table_count);
+        for (i, offset) in self.header.table_offsets.iter().enumerate() {
+            self.reader.seek(SeekFrom::Start(*offset as u64))?;
+            let header =
+                TableHeader::read::<E>(&mut self.reader, self.version).map_err(at_table(i))?;
+            self.reader.seek(SeekFrom::Start(*offset as u64))?;
+
+            let mut data = Vec::with_capacity(header.hashes.offset);
+            (&mut self.reader)
+                .take(header.hashes.offset.try_into()?)
+                .read_to_end(&mut data)?;
+            names.push(header.read_name_copy(&data)?.into());
+        }
+        Ok(names)
+    }
 }
 
 impl<'b, E: ByteOrder> BdatFile<'b> for LegacyBytes<'b, E> {
     type TableOut = LegacyTable<'b>;
 
     fn get_tables(&mut self) -> Result<Vec<LegacyTable<'b>>> {
-        let mut tables = Vec::with_capacity(self.header.table_count);
+        (0..self.header.table_count)
+            .map(|i| Ok(self.get_table(i)?.expect("table index out of bounds")))
+            .collect()
+    }
+
+    fn table_count(&self) -> usize {
+        self.header.table_count
+    }
+
+    fn table_shapes(&mut self) -> Result<Vec<(usize, usize)>> {
+        (0..self.header.table_count)
+            .map(|i| {
+                self.table_reader(i)?
+                    .expect("table index out of bounds")
+                    .shape()
+            })
+            .collect()
+    }
+
+    fn table_names(&mut self) -> Result<Vec<Label<'b>>> {
+        let mut names = Vec::with_capacity(self.header.table_count);
         for (i, offset) in self.header.table_offsets.iter().enumerate() {
-            tables.push(match &self.data {
+            names.push(match &self.data {
                 Cow::Owned(buf) => {
-                    TableReader::<E>::from_reader(Cursor::new(&buf[*offset..]), self.version)?
-                        .read()?
+                    let header = TableHeader::read::<E>(Cursor::new(&buf[*offset..]), self.version)
+                        .map_err(at_table(i))?;
+                    header.read_name_copy(&buf[*offset..])?.into()
+                }
+                Cow::Borrowed(data) => {
+                    // Tables were already unscrambled when the file was read
+                    let header = &self.table_headers[i];
+                    header.read_name(&data[*offset..])?.into()
                 }
-                Cow::Borrowed(data) => TableReader::<E>::from_slice(
-                    &data[*offset..],
-                    self.version,
-                    self.table_headers.get(i).cloned(),
-                )?
-                .read()?,
             });
         }
-        Ok(tables)
-    }
-
-    fn table_count(&self) -> usize {
-        self.header.table_count
+        Ok(names)
     }
 }
 
@@ -738,3 +1094,14 @@ fn eof<T>(_: T) -> BdatError {
     )
     .into()
 }
+
+/// Fills in the index of the table that caused a table-level error, for errors that
+/// are raised before the table's position in the file is known.
+fn at_table(index: usize) -> impl FnOnce(BdatError) -> BdatError {
+    move |e| match e {
+        BdatError::EndiannessMismatch { table_index: None } => BdatError::EndiannessMismatch {
+            table_index: Some(index),
+        },
+        e => e,
+    }
+}
@@ -18,8 +18,31 @@ pub fn scramble(data: &mut [u8], key: u16) {
     scramble_chunks(data, key)
 }
 
-/// Calculates the checksum for an unscrambled BDAT table.  
-/// The checksum can then be used as the scramble key.
+/// Checks that [`unscramble`] restores the data after [`scramble`] with the given key.
+///
+/// The data itself is not modified, as both operations work on a copy.
+pub fn verify_inverse(data: &[u8], key: u16) -> bool {
+    let mut copy = data.to_vec();
+    scramble(&mut copy, key);
+    unscramble(&mut copy, key);
+    copy == data
+}
+
+/// Calculates the checksum for an unscrambled legacy BDAT table.
+///
+/// The checksum covers everything after the first `0x20` bytes of the table header, up to
+/// the end of the table. The slice must start at the table header and contain the full
+/// table, without any data from the next table. Tables up to `0x20` bytes long have a
+/// checksum of 0.
+///
+/// Scrambled tables store their checksum in the table header, at offset `0x16`, and use it
+/// as the key to scramble column names and the string table. The checksum is always
+/// calculated on the **unscrambled** data. To edit a scrambled table in place, unscramble
+/// it with the old key, make the changes, then recalculate the checksum, store it in the
+/// header, and scramble the table again with the new key. Tables with an outdated checksum
+/// are rejected when [`LegacyReadOptions::verify_checksum`] is enabled.
+///
+/// [`LegacyReadOptions::verify_checksum`]: crate::legacy::LegacyReadOptions::verify_checksum
 pub fn calc_checksum(full_table: &[u8]) -> u16 {
     if full_table.len() <= 0x20 {
         return 0;
@@ -152,6 +175,28 @@ pub mod tests {
         assert_reverse(super::scramble_chunks);
     }
 
+    #[test]
+    fn inverse() {
+        // xorshift32, for reproducible pseudo-random data
+        let mut state = 0x2545_f491u32;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+        for len in 0..64 {
+            let data = (0..len).map(|_| next() as u8).collect::<Vec<_>>();
+            for _ in 0..64 {
+                assert!(super::verify_inverse(&data, next() as u16), "{data:02x?}");
+            }
+        }
+        let data = (0..4097).map(|_| next() as u8).collect::<Vec<_>>();
+        for key in [0, 1, KEY, 0xff00, u16::MAX] {
+            assert!(super::verify_inverse(&data, key));
+        }
+    }
+
     #[test]
     fn checksum() {
         let mut table = vec![0u8; 0x20];
@@ -9,10 +9,13 @@ use byteorder::{ByteOrder, WriteBytesExt};
 use super::hash::HashTable;
 use super::scramble::{calc_checksum, scramble};
 use super::util::{pad_2, pad_32, pad_4, pad_64};
-use super::{LegacyWriteOptions, COLUMN_NODE_SIZE, COLUMN_NODE_SIZE_WII, HEADER_SIZE};
+use super::{
+    LegacyEncoding, LegacyWriteOptions, COLUMN_NODE_SIZE, COLUMN_NODE_SIZE_WII, HEADER_SIZE,
+};
 use crate::error::Result;
 use crate::io::BDAT_MAGIC;
 use crate::legacy::{LegacyColumn, LegacyFlag, LegacyRow, LegacyTable};
+use crate::table::builder::legacy_row_count;
 use crate::{BdatError, Cell, LegacyVersion, Value, ValueType};
 
 /// Writes a full BDAT file to a writer.
@@ -42,6 +45,7 @@ struct HeaderData {
     row_data_offset: usize,
     final_padding: usize,
     checksum: u16,
+    scrambled: bool,
 }
 
 /// Writes cells from a row.
@@ -107,7 +111,8 @@ struct WiiColumnNode {
 
 #[derive(Debug)]
 enum StringNode {
-    String(Rc<str>),
+    /// Encoded string, without the nul terminator
+    String(Rc<[u8]>),
     WiiColumn(WiiColumnNode),
 }
 
@@ -138,8 +143,10 @@ impl<W: Write + Seek, E: ByteOrder + 'static> FileWriter<W, E> {
     ) -> Result<()> {
         let tables = tables.into_iter().by_ref().collect::<Vec<_>>();
         let mut tables = tables.iter().map(|t| t.borrow()).collect::<Vec<_>>();
-        // Tables must be ordered by name
-        tables.sort_unstable_by_key(|t| &t.name);
+        // Tables must be ordered by name, unless the caller explicitly opted out
+        if !self.opts.preserve_order {
+            tables.sort_unstable_by_key(|t| &t.name);
+        }
 
         let (table_bytes, table_offsets, total_len, table_count) = tables
             .into_iter()
@@ -198,6 +205,8 @@ impl<'a, 't, E: ByteOrder + 'static> TableWriter<'a, 't, E> {
     }
 
     fn write(mut self) -> Result<Vec<u8>> {
+        // Fail early, the header is only written at the end
+        self.row_count()?;
         self.make_layout()?;
         // Header space - nice workaround for a non-const (but with an upper bound) header size
         self.buf
@@ -255,13 +264,23 @@ impl<'a, 't, E: ByteOrder + 'static> TableWriter<'a, 't, E> {
 
         // Finally, scramble sections if enabled
         if self.opts.scramble {
-            self.rescramble();
+            self.rescramble()?;
         }
 
         Ok(self.buf.into_inner())
     }
 
     fn make_layout(&mut self) -> Result<()> {
+        if let Some(col) = self
+            .table
+            .columns()
+            .find(|c| !c.value_type().is_supported(self.version.into()))
+        {
+            return Err(BdatError::UnsupportedType(
+                col.value_type(),
+                self.version.into(),
+            ));
+        }
         self.init_names();
 
         let info_offset = self.version.table_header_size();
@@ -295,6 +314,11 @@ impl<'a, 't, E: ByteOrder + 'static> TableWriter<'a, 't, E> {
         }
     }
 
+    /// Returns the row count for the table header, checking that all row IDs fit in a `u16`.
+    fn row_count(&self) -> Result<u16> {
+        Ok(legacy_row_count(self.table.base_id, self.table.rows.len())?)
+    }
+
     fn write_header(&mut self) -> Result<()> {
         let columns = self.columns.as_ref().unwrap();
 
@@ -329,7 +353,7 @@ impl<'a, 't, E: ByteOrder + 'static> TableWriter<'a, 't, E> {
         self.buf
             .write_u16::<E>(self.header.row_data_offset.try_into()?)?;
         // Number of rows
-        self.buf.write_u16::<E>(self.table.rows.len().try_into()?)?;
+        self.buf.write_u16::<E>(self.row_count()?)?;
         // ID of the first row
         self.buf.write_u16::<E>(self.table.base_id)?;
         // UNKNOWN - asserted 2 when reading
@@ -369,17 +393,27 @@ impl<'a, 't, E: ByteOrder + 'static> TableWriter<'a, 't, E> {
         Ok(())
     }
 
-    fn rescramble(&mut self) {
+    fn rescramble(&mut self) -> Result<()> {
+        // Scrambling twice would make the table unrecoverable
+        if self.header.scrambled {
+            return Err(BdatError::AlreadyScrambled);
+        }
         let key = self.header.checksum;
         scramble(
             &mut self.buf.get_mut()[self.names.base_offset..self.header.hash_table_offset],
             key,
         );
+        // The final padding is part of the string table, and gets scrambled along with it,
+        // so the checksum stays valid after unscrambling
         scramble(
             &mut self.buf.get_mut()[self.strings.base_offset
-                ..self.strings.base_offset + self.strings.size_bytes_current()],
+                ..self.strings.base_offset
+                    + self.strings.size_bytes_current()
+                    + self.header.final_padding],
             key,
         );
+        self.header.scrambled = true;
+        Ok(())
     }
 }
 
@@ -550,7 +584,12 @@ impl<'a, 'b, 't, E: ByteOrder> RowWriter<'a, 'b, 't, E> {
             Value::SignedByte(b) => writer.write_i8(*b),
             Value::SignedShort(s) => writer.write_i16::<E>(*s),
             Value::SignedInt(i) => writer.write_i32::<E>(*i),
-            Value::String(s) => writer.write_u32::<E>(self.table.strings.insert(s).try_into()?),
+            Value::String(s) => writer.write_u32::<E>(
+                self.table
+                    .strings
+                    .insert_encoded(s, self.table.opts.encoding)?
+                    .try_into()?,
+            ),
             Value::Float(f) => {
                 let mut f = *f;
                 f.make_known(self.table.version.into());
@@ -712,14 +751,23 @@ impl StringTable {
     }
 
     fn insert(&mut self, text: &str) -> usize {
+        self.insert_bytes(text, text.as_bytes())
+    }
+
+    /// Inserts a string cell value, converting it to the given encoding.
+    fn insert_encoded(&mut self, text: &str, encoding: LegacyEncoding) -> Result<usize> {
+        Ok(self.insert_bytes(text, &encoding.encode(text)?))
+    }
+
+    fn insert_bytes(&mut self, text: &str, bytes: &[u8]) -> usize {
         if let (false, Some(ptr)) = (self.keep_duplicates, self.offsets_by_name.get(text)) {
             return *ptr + self.base_offset;
         }
-        let len = text.len();
+        let len = bytes.len();
         let text: Rc<str> = Rc::from(text);
         let offset = self.len;
         self.len += pad_2(len + 1);
-        self.table.push(StringNode::String(text.clone()));
+        self.table.push(StringNode::String(Rc::from(bytes)));
         if !self.keep_duplicates {
             self.offsets_by_name.insert(text, offset);
         }
@@ -747,9 +795,9 @@ impl StringTable {
     fn write<E: ByteOrder>(&self, mut writer: impl Write) -> Result<()> {
         for text in &self.table {
             match text {
-                StringNode::String(text) => {
-                    let len = text.len() + 1;
-                    writer.write_all(text.as_bytes())?;
+                StringNode::String(bytes) => {
+                    let len = bytes.len() + 1;
+                    writer.write_all(bytes)?;
                     writer.write_u8(0)?;
                     for _ in len..pad_2(len) {
                         writer.write_u8(0)?;
@@ -778,3 +826,33 @@ impl StringTable {
         self.max_len
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::legacy::{LegacyRow, LegacyTableBuilder};
+    use crate::{BdatError, FormatConvertError, LegacyVersion, SwitchEndian};
+
+    #[test]
+    fn row_count_exceeded() {
+        // Bypass the builder's checks, as tables could be modified after building
+        let mut table = LegacyTableBuilder::with_name("Test").build();
+        table.rows = vec![LegacyRow::new(vec![]); u16::MAX as usize + 1];
+        let res = crate::legacy::to_vec::<SwitchEndian>([&table], LegacyVersion::Switch);
+        assert!(matches!(
+            res,
+            Err(BdatError::FormatConvert(
+                FormatConvertError::MaxRowCountExceeded
+            ))
+        ));
+
+        table.rows.truncate(u16::MAX as usize);
+        table.base_id = 2;
+        let res = crate::legacy::to_vec::<SwitchEndian>([&table], LegacyVersion::Switch);
+        assert!(matches!(
+            res,
+            Err(BdatError::FormatConvert(
+                FormatConvertError::UnsupportedRowId(0x10000)
+            ))
+        ));
+    }
+}
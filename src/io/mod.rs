@@ -1,3 +1,7 @@
+use std::fmt::Display;
+
+use crate::ValueType;
+
 pub(crate) mod detect;
 pub(crate) mod legacy;
 pub(crate) mod modern;
@@ -6,7 +10,7 @@ mod read;
 
 pub use read::BdatFile;
 
-const BDAT_MAGIC: [u8; 4] = [b'B', b'D', b'A', b'T'];
+pub(crate) const BDAT_MAGIC: [u8; 4] = [b'B', b'D', b'A', b'T'];
 
 /// Alias for [`byteorder::LittleEndian`], i.e. the byte order used in Xenoblade 3D and
 /// in the Switch games.
@@ -44,6 +48,22 @@ pub enum LegacyVersion {
     X,
 }
 
+/// Sizes (in bytes) of the fixed-length structures in a BDAT format.
+///
+/// See [`BdatVersion::format_constants`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FormatConstants {
+    /// Size of a table header
+    pub table_header_size: usize,
+    /// Size of a column definition. For legacy formats, this is the size of a column
+    /// node, excluding the column's name and info table.
+    pub column_def_size: usize,
+    /// Size of an entry in a table's hash table. For legacy formats, this is the size of a
+    /// slot in the column name table; for modern formats, the size of a row hash entry.
+    pub hash_entry_size: usize,
+}
+
 impl BdatVersion {
     pub fn is_legacy(&self) -> bool {
         *self != BdatVersion::Modern
@@ -53,15 +73,96 @@ impl BdatVersion {
         !self.is_legacy()
     }
 
+    /// Returns the value types that can be written to tables of this version.
+    ///
+    /// [`ValueType::Unknown`] is never included, as it can't be written. For other types,
+    /// this agrees with [`ValueType::is_supported`], which the legacy writer checks before
+    /// writing a table.
+    ///
+    /// ```
+    /// use bdat::{BdatVersion, LegacyVersion, ValueType};
+    ///
+    /// let legacy = BdatVersion::Legacy(LegacyVersion::Switch);
+    /// assert!(!legacy.supported_value_types().contains(&ValueType::HashRef));
+    /// assert!(!legacy.supported_value_types().contains(&ValueType::DebugString));
+    /// assert!(BdatVersion::Modern.supported_value_types().contains(&ValueType::HashRef));
+    /// ```
+    pub fn supported_value_types(&self) -> &'static [ValueType] {
+        use ValueType::*;
+        match self {
+            Self::Legacy(_) => &[
+                UnsignedByte,
+                UnsignedShort,
+                UnsignedInt,
+                SignedByte,
+                SignedShort,
+                SignedInt,
+                String,
+                Float,
+            ],
+            Self::Modern => &[
+                UnsignedByte,
+                UnsignedShort,
+                UnsignedInt,
+                SignedByte,
+                SignedShort,
+                SignedInt,
+                String,
+                Float,
+                HashRef,
+                Percent,
+                DebugString,
+                Unknown12,
+                MessageId,
+            ],
+        }
+    }
+
     /// Gets whether the version forces labels to be hashed.
     pub fn are_labels_hashed(&self) -> bool {
         self.is_modern()
     }
+
+    /// Returns the size in bytes of the table header.
+    pub const fn table_header_size(&self) -> usize {
+        match self {
+            Self::Legacy(v) => v.table_header_size(),
+            Self::Modern => modern::LEN_TABLE_HEADER,
+        }
+    }
+
+    /// Returns the sizes of fixed-length structures for this version.
+    ///
+    /// ```
+    /// use bdat::{BdatVersion, LegacyVersion};
+    ///
+    /// let consts = BdatVersion::Legacy(LegacyVersion::Switch).format_constants();
+    /// assert_eq!(64, consts.table_header_size);
+    /// assert_eq!(48, BdatVersion::Modern.format_constants().table_header_size);
+    /// ```
+    pub const fn format_constants(&self) -> FormatConstants {
+        match self {
+            Self::Legacy(v) => FormatConstants {
+                table_header_size: v.table_header_size(),
+                column_def_size: if v.is_wii_table_format() {
+                    legacy::COLUMN_NODE_SIZE_WII
+                } else {
+                    legacy::COLUMN_NODE_SIZE
+                },
+                hash_entry_size: legacy::HASH_SLOT_SIZE,
+            },
+            Self::Modern => FormatConstants {
+                table_header_size: modern::LEN_TABLE_HEADER,
+                column_def_size: modern::LEN_COLUMN_DEF,
+                hash_entry_size: modern::LEN_HASH_DEF,
+            },
+        }
+    }
 }
 
 impl LegacyVersion {
     /// Returns the size in bytes of the table header.
-    pub(crate) const fn table_header_size(&self) -> usize {
+    pub const fn table_header_size(&self) -> usize {
         if self.is_wii_table_format() {
             legacy::HEADER_SIZE_WII
         } else {
@@ -74,6 +175,26 @@ impl LegacyVersion {
     }
 }
 
+impl Display for BdatVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Legacy(v) => write!(f, "legacy {v}"),
+            Self::Modern => f.write_str("modern (XC3)"),
+        }
+    }
+}
+
+impl Display for LegacyVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Wii => "Wii (XC1)",
+            Self::New3ds => "3DS (XC3D)",
+            Self::Switch => "Switch (XC2/XCDE)",
+            Self::X => "Wii U (XCX)",
+        })
+    }
+}
+
 impl From<LegacyVersion> for BdatVersion {
     fn from(value: LegacyVersion) -> Self {
         Self::Legacy(value)
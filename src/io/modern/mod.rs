@@ -14,13 +14,27 @@ use byteorder::ByteOrder;
 use crate::BdatFile;
 
 mod read;
+mod repair;
 mod write;
 
-pub use read::FileReader;
+/// Version number found after the "BDAT" magic in the file header
+pub(crate) const FILE_VERSION: u32 = 0x01_00_10_04;
+/// Version number found after the "BDAT" magic in table headers
+pub(super) const TABLE_VERSION: u32 = 0x30_04;
+/// Size of a table header, up to the string table length
+pub(super) const LEN_TABLE_HEADER: usize = 48;
+/// Size of a column definition (type + name offset)
+pub(super) const LEN_COLUMN_DEF: usize = 3;
+/// Size of a hash table entry (hash + row index)
+pub(super) const LEN_HASH_DEF: usize = 8;
+
+pub use read::{FileReader, ModernRows};
+pub use repair::repair_header;
 
 #[derive(Debug)]
 pub(crate) struct FileHeader {
     pub table_count: usize,
+    pub(crate) file_size: usize,
     pub(crate) table_offsets: Vec<usize>,
 }
 
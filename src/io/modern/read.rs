@@ -3,6 +3,7 @@ use std::{
     convert::TryFrom,
     io::{Cursor, Read, Seek, SeekFrom},
     marker::PhantomData,
+    ops::Range,
 };
 
 use byteorder::{ByteOrder, ReadBytesExt};
@@ -16,10 +17,7 @@ use crate::{
     BdatFile, Label, Utf, Value, ValueType,
 };
 
-use super::FileHeader;
-
-const LEN_COLUMN_DEF_V2: usize = 3;
-const LEN_HASH_DEF_V2: usize = 8;
+use super::{FileHeader, FILE_VERSION, LEN_COLUMN_DEF, LEN_HASH_DEF, TABLE_VERSION};
 
 #[doc(hidden)]
 pub struct FileReader<R, E> {
@@ -31,6 +29,7 @@ pub struct FileReader<R, E> {
 struct TableData<'r> {
     data: Cow<'r, [u8]>,
     string_table_offset: usize,
+    string_table_len: usize,
 }
 
 pub trait ModernRead<'b> {
@@ -40,6 +39,9 @@ pub trait ModernRead<'b> {
     /// Get a slice (or buffer) to the full binary stream for a single table.
     fn read_table_data(&mut self, length: usize) -> Result<Cow<'b, [u8]>>;
 
+    /// Get a slice (or buffer) to a part of the current table, relative to the table's start.
+    fn read_table_range(&mut self, range: Range<usize>) -> Result<Cow<'b, [u8]>>;
+
     /// Seek the current position to the next table at the given offset.
     fn seek_table(&mut self, offset: usize) -> Result<()>;
 }
@@ -54,20 +56,68 @@ struct TableReader<R, E> {
     _endianness: PhantomData<E>,
 }
 
+/// A lazy iterator over the rows of a modern table, returned by [`FileReader::table_rows`].
+///
+/// Rows are only parsed when requested, so this is cheaper than reading the full table
+/// when only a few rows are needed.
+pub struct ModernRows<'b, E> {
+    data: TableData<'b>,
+    name: Label<'b>,
+    base_id: u32,
+    reserved: [u8; 4],
+    primary_key: Option<usize>,
+    columns: Vec<ModernColumn<'b>>,
+    offset_row: usize,
+    row_length: usize,
+    row_count: usize,
+    next_row: usize,
+    _endianness: PhantomData<E>,
+}
+
 impl<'b, R, E> FileReader<R, E>
 where
     R: ModernRead<'b>,
     E: ByteOrder,
 {
     pub(crate) fn read_file(mut reader: R) -> Result<Self> {
-        if reader.read_u32()? == u32::from_le_bytes(BDAT_MAGIC) {
-            if reader.read_u32()? != 0x01_00_10_04 {
-                return Err(BdatError::MalformedBdat(Scope::File));
-            }
-            Self::new_with_header(reader)
-        } else {
-            Err(BdatError::MalformedBdat(Scope::File))
+        let magic = read_magic::<E>(&mut reader)?;
+        if magic != BDAT_MAGIC {
+            return Err(BdatError::UnexpectedMagic { found: magic });
+        }
+        if reader.read_u32()? != FILE_VERSION {
+            return Err(BdatError::MalformedBdat(Scope::File));
         }
+        Self::new_with_header(reader)
+    }
+
+    /// Returns the byte range occupied by the table at the given index, relative to the
+    /// start of the file, or `None` if there is no such table.
+    ///
+    /// The range ends at the next table's offset, or at the reported file size for the
+    /// last table. This can be used to patch a single table without rewriting the file.
+    ///
+    /// As tables are padded to a 4-byte boundary, the range includes the padding after the
+    /// table, which is not covered by the lengths in the table header.
+    pub fn table_byte_range(&self, index: usize) -> Option<Range<usize>> {
+        let start = *self.header.table_offsets.get(index)?;
+        let end = match self.header.table_offsets.get(index + 1) {
+            Some(&next) => next,
+            None => self.header.file_size,
+        };
+        (start <= end).then_some(start..end)
+    }
+
+    /// Returns a lazy iterator over the rows of the table at the given index.
+    ///
+    /// Unlike [`BdatFile::get_tables`], rows are parsed on demand.
+    ///
+    /// Returns `None` if there is no such table.
+    pub fn table_rows(&mut self, index: usize) -> Result<Option<ModernRows<'b, E>>> {
+        let Some(&offset) = self.header.table_offsets.get(index) else {
+            return Ok(None);
+        };
+        self.tables.reader.seek_table(offset)?;
+        self.tables.read_rows_v2().map(Some)
     }
 
     fn read_table(&mut self) -> Result<ModernTable<'b>> {
@@ -97,7 +147,7 @@ impl<'b, R: ModernRead<'b>, E: ByteOrder> HeaderReader<R, E> {
         let table_count = self.reader.read_u32()? as usize;
         let mut table_offsets = Vec::with_capacity(table_count);
 
-        self.reader.read_u32()?; // File size
+        let file_size = self.reader.read_u32()? as usize;
 
         for _ in 0..table_count {
             table_offsets.push(self.reader.read_u32()? as usize);
@@ -105,6 +155,7 @@ impl<'b, R: ModernRead<'b>, E: ByteOrder> HeaderReader<R, E> {
 
         Ok(FileHeader {
             table_count,
+            file_size,
             table_offsets,
         })
     }
@@ -119,18 +170,37 @@ impl<'b, R: ModernRead<'b>, E: ByteOrder> TableReader<R, E> {
     }
 
     fn read_table_v2(&mut self) -> Result<ModernTable<'b>> {
-        if self.reader.read_u32()? != u32::from_le_bytes(BDAT_MAGIC)
-            || self.reader.read_u32()? != 0x3004
-        {
-            return Err(BdatError::MalformedBdat(Scope::Table));
+        let rows = self.read_rows_v2()?;
+        let name = rows.name.clone();
+        let base_id = rows.base_id;
+        let reserved = rows.reserved;
+        let primary_key = rows.primary_key;
+        let columns = rows.columns.clone();
+        // Collecting into Result<Vec> loses the exact size hint
+        let mut row_data = Vec::with_capacity(rows.len());
+        for row in rows {
+            row_data.push(row?);
         }
 
+        let mut table = ModernTableBuilder::with_name(name)
+            .set_base_id(base_id)
+            .set_columns(columns)
+            .set_rows(row_data)
+            .build_with_primary_key(primary_key);
+        table.reserved = reserved;
+        Ok(table)
+    }
+
+    /// Reads the table header, name and columns, returning a lazy reader for the rows.
+    fn read_rows_v2(&mut self) -> Result<ModernRows<'b, E>> {
+        self.read_magic_v2()?;
+
         let columns = self.reader.read_u32()? as usize;
         let rows = self.reader.read_u32()? as usize;
         let base_id = self.reader.read_u32()?;
-        if self.reader.read_u32()? != 0 {
-            panic!("Found unknown value at index 0x14 that was not 0");
-        }
+        // Unknown, always zero in known tables. Kept as-is for write-back
+        let mut reserved = [0; 4];
+        E::write_u32(&mut reserved, self.reader.read_u32()?);
 
         let offset_col = self.reader.read_u32()? as usize;
         let offset_hash = self.reader.read_u32()? as usize;
@@ -143,8 +213,8 @@ impl<'b, R: ModernRead<'b>, E: ByteOrder> TableReader<R, E> {
         let str_length = self.reader.read_u32()? as usize;
 
         let lengths = [
-            offset_col + LEN_COLUMN_DEF_V2 * columns,
-            offset_hash + LEN_HASH_DEF_V2 * rows,
+            offset_col + LEN_COLUMN_DEF * columns,
+            offset_hash + LEN_HASH_DEF * rows,
             offset_row + row_length * rows,
             offset_string + str_length,
         ];
@@ -153,14 +223,13 @@ impl<'b, R: ModernRead<'b>, E: ByteOrder> TableReader<R, E> {
             .max_by_key(|&i| i)
             .expect("could not determine table length");
         let table_raw = self.reader.read_table_data(*table_len)?;
-        let table_data = TableData::new(table_raw, offset_string);
+        let table_data = TableData::new(table_raw, offset_string, str_length);
 
         let name = table_data.get_name::<E>()?;
         let mut col_data = Vec::with_capacity(columns);
-        let mut row_data = Vec::with_capacity(rows);
 
         for i in 0..columns {
-            let col = &table_data.data[offset_col + i * LEN_COLUMN_DEF_V2..];
+            let col = &table_data.data[offset_col + i * LEN_COLUMN_DEF..];
             let ty =
                 ValueType::try_from(col[0]).map_err(|_| BdatError::UnknownValueType(col[0]))?;
             let name_offset = (&col[1..]).read_u16::<E>()?;
@@ -169,22 +238,152 @@ impl<'b, R: ModernRead<'b>, E: ByteOrder> TableReader<R, E> {
             col_data.push(ModernColumn::new(ty, label));
         }
 
-        for i in 0..rows {
-            let row = &table_data.data[offset_row + i * row_length..];
-            let mut values = Vec::with_capacity(col_data.len());
-            let mut cursor = Cursor::new(row);
-            for col in &col_data {
-                let value = Self::read_value(&table_data, &mut cursor, col.value_type)?;
-                values.push(value);
+        let expected_length = col_data.iter().map(ModernColumn::data_size).sum();
+        if row_length != expected_length {
+            // All rows share the header's row length, so report the first one
+            return Err(BdatError::RowSizeMismatch {
+                row: base_id,
+                expected: expected_length,
+                actual: row_length,
+            });
+        }
+
+        let primary_key = Self::find_primary_key(
+            &table_data.data,
+            &col_data,
+            offset_hash,
+            offset_row,
+            row_length,
+            rows,
+        );
+
+        Ok(ModernRows {
+            data: table_data,
+            name,
+            base_id,
+            reserved,
+            primary_key,
+            columns: col_data,
+            offset_row,
+            row_length,
+            row_count: rows,
+            next_row: 0,
+            _endianness: PhantomData,
+        })
+    }
+
+    /// Finds the column that was used to build the table's hash table, by looking up the
+    /// first entry's hash in the row it points to.
+    ///
+    /// If the hash table is empty but there are rows, the table has no primary key. If the
+    /// column can't be determined, this defaults to the first hash column.
+    fn find_primary_key(
+        data: &[u8],
+        columns: &[ModernColumn],
+        offset_hash: usize,
+        offset_row: usize,
+        row_length: usize,
+        rows: usize,
+    ) -> Option<usize> {
+        let first_hash_col = columns
+            .iter()
+            .position(|c| c.value_type() == ValueType::HashRef);
+        if rows == 0 {
+            return first_hash_col;
+        }
+        let entries = match offset_row.checked_sub(offset_hash) {
+            Some(len) => rows.min(len / LEN_HASH_DEF),
+            None => rows,
+        };
+        if entries == 0 {
+            return None;
+        }
+
+        let hash = E::read_u32(&data[offset_hash..]);
+        let index = E::read_u32(&data[offset_hash + 4..]) as usize;
+        if index >= rows {
+            return first_hash_col;
+        }
+        let row = &data[offset_row + index * row_length..];
+        let mut value_offset = 0;
+        for (i, col) in columns.iter().enumerate() {
+            if col.value_type() == ValueType::HashRef
+                && row.len() >= value_offset + 4
+                && E::read_u32(&row[value_offset..]) == hash
+            {
+                return Some(i);
             }
-            row_data.push(ModernRow::new(values));
+            value_offset += col.data_size();
         }
+        first_hash_col
+    }
 
-        Ok(ModernTableBuilder::with_name(name)
-            .set_base_id(base_id)
-            .set_columns(col_data)
-            .set_rows(row_data)
-            .build())
+    fn read_table_name_v2(&mut self) -> Result<Label<'b>> {
+        self.read_magic_v2()?;
+
+        // Skip column count, row count, base ID, the unknown value, offsets for columns, hashes,
+        // and rows, and row length
+        for _ in 0..8 {
+            self.reader.read_u32()?;
+        }
+        let offset_string = self.reader.read_u32()? as usize;
+        let str_length = self.reader.read_u32()? as usize;
+
+        // Only the string table is read, the name is at its start
+        let strings = self
+            .reader
+            .read_table_range(offset_string..offset_string.saturating_add(str_length))?;
+        TableData::new(strings, 0, str_length).get_name::<E>()
+    }
+
+    fn read_table_shape_v2(&mut self) -> Result<(usize, usize)> {
+        self.read_magic_v2()?;
+        let columns = self.reader.read_u32()? as usize;
+        let rows = self.reader.read_u32()? as usize;
+        Ok((rows, columns))
+    }
+
+    fn read_magic_v2(&mut self) -> Result<()> {
+        if read_magic::<E>(&mut self.reader)? != BDAT_MAGIC
+            || self.reader.read_u32()? != TABLE_VERSION
+        {
+            return Err(BdatError::MalformedBdat(Scope::Table));
+        }
+        Ok(())
+    }
+}
+
+impl<'b, E: ByteOrder> ModernRows<'b, E> {
+    /// Returns the table's name.
+    pub fn name(&self) -> &Label<'b> {
+        &self.name
+    }
+
+    /// Returns the ID of the first row.
+    pub fn base_id(&self) -> u32 {
+        self.base_id
+    }
+
+    /// Returns the table's column definitions.
+    pub fn columns(&self) -> &[ModernColumn<'b>] {
+        &self.columns
+    }
+
+    /// Returns the raw bytes of the reserved header field. See [`ModernTable::reserved`].
+    pub fn reserved(&self) -> [u8; 4] {
+        self.reserved
+    }
+
+    fn read_row(&self, index: usize) -> Result<ModernRow<'b>> {
+        let row = &self.data.data[self.offset_row + index * self.row_length..];
+        let mut values = Vec::with_capacity(self.columns.len());
+        let mut cursor = Cursor::new(row);
+        for col in &self.columns {
+            let value = Self::read_value(&self.data, &mut cursor, col.value_type)?;
+            values.push(value);
+        }
+        debug_assert_eq!(self.row_length, cursor.position() as usize);
+        Ok(ModernRow::new(values))
     }
 
     fn read_value(
@@ -215,11 +414,40 @@ impl<'b, R: ModernRead<'b>, E: ByteOrder> TableReader<R, E> {
     }
 }
 
+/// Reads the 4 magic bytes at the current position, in file order regardless of the
+/// reader's byte order.
+fn read_magic<'b, E: ByteOrder>(reader: &mut impl ModernRead<'b>) -> Result<[u8; 4]> {
+    let mut magic = [0; 4];
+    E::write_u32(&mut magic, reader.read_u32()?);
+    Ok(magic)
+}
+
+impl<'b, E: ByteOrder> Iterator for ModernRows<'b, E> {
+    type Item = Result<ModernRow<'b>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_row >= self.row_count {
+            return None;
+        }
+        let row = self.read_row(self.next_row);
+        self.next_row += 1;
+        Some(row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.row_count - self.next_row;
+        (len, Some(len))
+    }
+}
+
+impl<'b, E: ByteOrder> ExactSizeIterator for ModernRows<'b, E> {}
+
 impl<'r> TableData<'r> {
-    fn new(data: Cow<'r, [u8]>, strings_offset: usize) -> TableData<'r> {
+    fn new(data: Cow<'r, [u8]>, strings_offset: usize, strings_len: usize) -> TableData<'r> {
         Self {
             data,
             string_table_offset: strings_offset,
+            string_table_len: strings_len,
         }
     }
 
@@ -233,15 +461,26 @@ impl<'r> TableData<'r> {
         self.get_label::<E>(offset)
     }
 
+    /// Returns the string table data, starting from the given offset.
+    fn strings_from(&self, offset: usize) -> Result<&[u8]> {
+        let end = self.string_table_offset + self.string_table_len;
+        self.data
+            .get(self.string_table_offset + offset..end)
+            .ok_or(BdatError::MalformedBdat(Scope::Table))
+    }
+
     /// Reads a null-terminated UTF-8 encoded string from the string table at the given offset
+    ///
+    /// If the string table ends before a nul terminator is found, the string ends with
+    /// the table.
     fn get_string(&self, offset: usize, limit: usize) -> Result<Utf<'r>> {
         let str_ptr = self.string_table_offset + offset;
-        let len = self.data[str_ptr..]
-            .split(|&b| b == 0)
-            .take(1)
-            .flatten()
-            .take(limit)
-            .count();
+        let bytes = self.strings_from(offset)?;
+        let len = bytes
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(bytes.len())
+            .min(limit);
         let str = match &self.data {
             Cow::Borrowed(data) => {
                 Cow::Borrowed(std::str::from_utf8(&data[str_ptr..str_ptr + len])?)
@@ -259,16 +498,18 @@ impl<'r> TableData<'r> {
         E: ByteOrder,
     {
         if self.are_labels_hashed() {
-            Ok(Label::Hash(
-                (&self.data[self.string_table_offset + offset..]).read_u32::<E>()?,
-            ))
+            let hash = self
+                .strings_from(offset)?
+                .get(..4)
+                .ok_or(BdatError::MalformedBdat(Scope::Table))?;
+            Ok(Label::Hash(E::read_u32(hash)))
         } else {
             Ok(Label::String(self.get_string(offset, usize::MAX)?))
         }
     }
 
     fn are_labels_hashed(&self) -> bool {
-        self.data[self.string_table_offset] == 0
+        self.data.get(self.string_table_offset) == Some(&0)
     }
 }
 
@@ -277,9 +518,22 @@ where
     E: ByteOrder,
 {
     fn read_table_data(&mut self, length: usize) -> Result<Cow<'b, [u8]>> {
-        Ok(Cow::Borrowed(
-            &self.data.clone().into_inner()[self.table_offset..self.table_offset + length],
-        ))
+        self.data
+            .get_ref()
+            .get(self.table_offset..self.table_offset.saturating_add(length))
+            .map(Cow::Borrowed)
+            .ok_or(BdatError::MalformedBdat(Scope::Table))
+    }
+
+    fn read_table_range(&mut self, range: Range<usize>) -> Result<Cow<'b, [u8]>> {
+        self.data
+            .get_ref()
+            .get(
+                self.table_offset.saturating_add(range.start)
+                    ..self.table_offset.saturating_add(range.end),
+            )
+            .map(Cow::Borrowed)
+            .ok_or(BdatError::MalformedBdat(Scope::Table))
     }
 
     #[inline]
@@ -300,6 +554,16 @@ where
     E: ByteOrder,
 {
     fn read_table_data(&mut self, length: usize) -> Result<Cow<'b, [u8]>> {
+        // Don't trust the header with the allocation size, the table can't possibly be larger
+        // than what's left in the stream
+        let remaining = self
+            .stream
+            .seek(SeekFrom::End(0))?
+            .saturating_sub(self.table_offset as u64);
+        if length as u64 > remaining {
+            return Err(BdatError::MalformedBdat(Scope::Table));
+        }
+
         let mut table_raw = vec![0u8; length];
         self.stream
             .seek(SeekFrom::Start(self.table_offset as u64))?;
@@ -307,6 +571,20 @@ where
         Ok(table_raw.into())
     }
 
+    fn read_table_range(&mut self, range: Range<usize>) -> Result<Cow<'b, [u8]>> {
+        let start = self.table_offset as u64 + range.start as u64;
+        let remaining = self.stream.seek(SeekFrom::End(0))?.saturating_sub(start);
+        let length = range.end.saturating_sub(range.start);
+        if range.start > range.end || length as u64 > remaining {
+            return Err(BdatError::MalformedBdat(Scope::Table));
+        }
+
+        let mut data = vec![0u8; length];
+        self.stream.seek(SeekFrom::Start(start))?;
+        self.stream.read_exact(&mut data)?;
+        Ok(data.into())
+    }
+
     #[inline]
     fn read_u32(&mut self) -> Result<u32> {
         Ok(self.stream.read_u32::<E>()?)
@@ -345,4 +623,26 @@ where
     fn table_count(&self) -> usize {
         self.header.table_count
     }
+
+    fn table_shapes(&mut self) -> Result<Vec<(usize, usize)>> {
+        let mut shapes = Vec::with_capacity(self.header.table_count);
+
+        for &offset in &self.header.table_offsets {
+            self.tables.reader.seek_table(offset)?;
+            shapes.push(self.tables.read_table_shape_v2()?);
+        }
+
+        Ok(shapes)
+    }
+
+    fn table_names(&mut self) -> Result<Vec<Label<'b>>> {
+        let mut names = Vec::with_capacity(self.header.table_count);
+
+        for &offset in &self.header.table_offsets {
+            self.tables.reader.seek_table(offset)?;
+            names.push(self.tables.read_table_name_v2()?);
+        }
+
+        Ok(names)
+    }
 }
@@ -0,0 +1,94 @@
+use byteorder::ByteOrder;
+
+use super::{FILE_VERSION, LEN_COLUMN_DEF, LEN_HASH_DEF, LEN_TABLE_HEADER, TABLE_VERSION};
+use crate::error::{BdatError, Result, Scope};
+use crate::io::BDAT_MAGIC;
+
+/// Magic + version + table count + file size
+const LEN_FILE_HEADER: usize = 16;
+
+/// Rebuilds the file header of a modern BDAT file, based on the tables found in the file.
+///
+/// Tables are located by scanning the data for table headers, after the file header. The
+/// table offset list and the file size are then recalculated, and the file version is
+/// restored if it was changed. If the new offset list doesn't fit in the old header, the
+/// tables are moved accordingly.
+///
+/// This can be used to recover files that were edited by tools that did not update the
+/// file header.
+///
+/// The data must start with the "BDAT" magic, otherwise [`BdatError::MalformedBdat`] is
+/// returned.
+///
+/// ```
+/// use bdat::{BdatResult, SwitchEndian};
+///
+/// fn repair(data: &mut Vec<u8>) -> BdatResult<()> {
+///     bdat::modern::repair_header::<SwitchEndian>(data)?;
+///     let file = bdat::modern::from_bytes::<SwitchEndian>(data)?;
+///     Ok(())
+/// }
+/// ```
+pub fn repair_header<E: ByteOrder>(data: &mut Vec<u8>) -> Result<()> {
+    if data.len() < LEN_FILE_HEADER || data[..4] != BDAT_MAGIC {
+        return Err(BdatError::MalformedBdat(Scope::File));
+    }
+
+    let mut tables = Vec::new();
+    let mut offset = 8;
+    while offset + LEN_TABLE_HEADER <= data.len() {
+        match table_len::<E>(&data[offset..]) {
+            Some(len) => {
+                tables.push(offset);
+                offset += len.next_multiple_of(4);
+            }
+            None => offset += 4,
+        }
+    }
+    let Some(&tables_start) = tables.first() else {
+        return Err(BdatError::MalformedBdat(Scope::File));
+    };
+
+    let header_len = LEN_FILE_HEADER + tables.len() * 4;
+    let table_data = data.split_off(tables_start);
+    data.clear();
+    data.extend_from_slice(&BDAT_MAGIC);
+    write_u32::<E>(data, FILE_VERSION);
+    write_u32::<E>(data, tables.len().try_into()?);
+    write_u32::<E>(data, (header_len + table_data.len()).try_into()?);
+    for table in tables {
+        write_u32::<E>(data, (table - tables_start + header_len).try_into()?);
+    }
+    data.extend_from_slice(&table_data);
+    Ok(())
+}
+
+/// Returns the length of the table at the start of the data, or `None` if the data doesn't
+/// start with a valid table header. Tables can't be shorter than their header.
+fn table_len<E: ByteOrder>(data: &[u8]) -> Option<usize> {
+    if data[..4] != BDAT_MAGIC || E::read_u32(&data[4..]) != TABLE_VERSION {
+        return None;
+    }
+    let field = |i: usize| E::read_u32(&data[8 + i * 4..]) as usize;
+    let (columns, rows) = (field(0), field(1));
+    let (offset_col, offset_hash, offset_row) = (field(4), field(5), field(6));
+    let (row_length, offset_string, str_length) = (field(7), field(8), field(9));
+
+    let len = [
+        offset_col + LEN_COLUMN_DEF * columns,
+        offset_hash + LEN_HASH_DEF * rows,
+        offset_row + row_length * rows,
+        offset_string + str_length,
+    ]
+    .into_iter()
+    .max()?;
+    (LEN_TABLE_HEADER..=data.len())
+        .contains(&len)
+        .then_some(len)
+}
+
+fn write_u32<E: ByteOrder>(data: &mut Vec<u8>, value: u32) {
+    let mut buf = [0; 4];
+    E::write_u32(&mut buf, value);
+    data.extend_from_slice(&buf);
+}
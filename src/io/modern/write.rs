@@ -9,11 +9,11 @@ use std::{
 use byteorder::{ByteOrder, WriteBytesExt};
 
 use crate::io::BDAT_MAGIC;
-use crate::modern::ModernTable;
+use crate::modern::{ModernColumn, ModernTable};
+use crate::BdatError;
 use crate::{error::Result, Label, Value};
-use crate::{BdatError, ValueType};
 
-use super::FileHeader;
+use super::{FileHeader, FILE_VERSION, LEN_HASH_DEF, TABLE_VERSION};
 
 pub(crate) struct BdatWriter<W, E> {
     stream: W,
@@ -42,52 +42,59 @@ where
         &mut self,
         tables: impl IntoIterator<Item = impl Borrow<ModernTable<'t>>>,
     ) -> Result<()> {
-        let (table_bytes, table_offsets, total_len, table_count) = tables
-            .into_iter()
-            .map(|table| {
-                let mut data = vec![];
-                let cursor = Cursor::new(&mut data);
-
-                BdatWriter::<_, E>::new(cursor)
-                    .write_table(table.borrow())
-                    .map(|_| data)
-            })
-            .try_fold(
-                (Vec::new(), Vec::new(), 0, 0),
-                |(mut tot_bytes, mut offsets, len, count), table_bytes| {
-                    table_bytes.map(|mut bytes| {
-                        let new_len = bytes.len();
-                        (
-                            {
-                                tot_bytes.append(&mut bytes);
-                                tot_bytes
-                            },
-                            {
-                                offsets.push(len);
-                                offsets
-                            },
-                            len + new_len,
-                            count + 1,
-                        )
-                    })
-                },
-            )?;
-
+        let tables = tables.into_iter().collect::<Vec<_>>();
+        let tables = tables.iter().map(Borrow::borrow).collect::<Vec<_>>();
+        let table_bytes = Self::serialize_tables(&tables)?;
+
+        // Sizes and offsets are relative to the table data, write_header adds the header length
+        let mut table_offsets = Vec::with_capacity(table_bytes.len());
+        let mut total_len = 0;
+        for bytes in &table_bytes {
+            table_offsets.push(total_len);
+            total_len += bytes.len();
+        }
         let header = FileHeader {
-            table_count,
+            table_count: table_bytes.len(),
+            file_size: total_len,
             table_offsets,
         };
 
-        self.write_header(header, total_len)?;
-        self.stream.write_all(&table_bytes)?;
+        self.write_header(header)?;
+        for bytes in table_bytes {
+            self.stream.write_all(&bytes)?;
+        }
 
         Ok(())
     }
 
-    pub fn write_header(&mut self, header: FileHeader, table_data_len: usize) -> Result<()> {
+    /// Serializes each table into its own buffer, in order.
+    #[cfg(not(feature = "parallel"))]
+    fn serialize_tables(tables: &[&ModernTable]) -> Result<Vec<Vec<u8>>> {
+        tables.iter().map(|t| Self::serialize_table(t)).collect()
+    }
+
+    /// Serializes each table into its own buffer, in order. Tables are serialized in
+    /// parallel.
+    #[cfg(feature = "parallel")]
+    fn serialize_tables(tables: &[&ModernTable]) -> Result<Vec<Vec<u8>>> {
+        use rayon::prelude::*;
+
+        tables
+            .par_iter()
+            .map(|t| Self::serialize_table(t))
+            .collect()
+    }
+
+    fn serialize_table(table: &ModernTable) -> Result<Vec<u8>> {
+        let mut data = vec![];
+        BdatWriter::<_, E>::new(Cursor::new(&mut data)).write_table(table)?;
+        Ok(data)
+    }
+
+    pub fn write_header(&mut self, header: FileHeader) -> Result<()> {
         let magic_len = {
             self.stream.write_all(&BDAT_MAGIC)?;
-            self.w_u32(0x01_00_10_04)?;
+            self.w_u32(FILE_VERSION)?;
             8
         };
 
@@ -95,7 +102,7 @@ where
 
         self.w_u32(u32::try_from(header.table_count)?)?;
         // File size
-        self.w_u32(u32::try_from(table_data_len)? + header_len)?;
+        self.w_u32(u32::try_from(header.file_size)? + header_len)?;
         for offset in header.table_offsets {
             self.w_u32(u32::try_from(offset)? + header_len)?;
         }
@@ -122,8 +129,8 @@ where
             let mut data = Vec::with_capacity(columns.len() * (1 + 4));
 
             for (i, col) in table.columns.as_slice().iter().enumerate() {
-                if col.value_type() == ValueType::HashRef {
-                    primary_col.get_or_insert_with(|| (col.label.clone(), i));
+                if table.primary_key() == Some(i) {
+                    primary_col = Some((col.label.clone(), i));
                 }
                 data.write_u8(col.value_type as u8)?;
                 data.write_u16::<E>(u16::try_from(label_table.get(col.label.as_ref()))?)?;
@@ -135,9 +142,22 @@ where
         // List of row and cell data
         let (row_table, row_len) = {
             let mut data = vec![];
-            let mut row_len = 0;
+            let row_len = table
+                .columns
+                .as_slice()
+                .iter()
+                .map(ModernColumn::data_size)
+                .sum::<usize>();
 
             for row in table.rows() {
+                let actual = row.values.iter().map(Value::byte_size).sum();
+                if actual != row_len {
+                    return Err(BdatError::RowSizeMismatch {
+                        row: row.id(),
+                        expected: row_len,
+                        actual,
+                    });
+                }
                 for (value_idx, value) in row.values.iter().enumerate() {
                     match (&primary_col, value) {
                         (Some((_, i)), Value::HashRef(hash)) if *i == value_idx => {
@@ -147,9 +167,6 @@ where
                     }
                     Self::write_value(&mut data, value, &mut label_table)?
                 }
-                if row_len == 0 {
-                    row_len = data.len();
-                }
             }
 
             (data, row_len)
@@ -173,7 +190,7 @@ where
                 ))));
             }
 
-            let mut buf = Vec::with_capacity(primary_keys.len() * 8);
+            let mut buf = Vec::with_capacity(primary_keys.len() * LEN_HASH_DEF);
             for (hash, i) in primary_keys {
                 buf.write_u32::<E>(hash)?;
                 buf.write_u32::<E>(i - base_id)?;
@@ -184,12 +201,12 @@ where
         let ser_strings_table = label_table.write::<E>()?;
 
         self.stream.write_all(&BDAT_MAGIC)?; // "BDAT"
-        self.w_u32(0x30_04)?; // Table
+        self.w_u32(TABLE_VERSION)?;
 
         self.w_u32(column_count)?;
         self.w_u32(row_count)?;
         self.w_u32(base_id)?;
-        self.w_u32(0)?; // Unknown, always zero. Our deserialization fails if this is ever made != 0
+        self.stream.write_all(&table.reserved)?; // Unknown, always zero in known tables
 
         // Build tables. Order probably doesn't matter, but we stick to the order the game uses:
         // columns, hashes, row, strings
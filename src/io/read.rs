@@ -1,8 +1,9 @@
 use crate::compat::CompatTable;
-use crate::error::Result;
+use crate::error::{BdatError, Result};
 use crate::table::legacy::LegacyTable;
 use crate::table::modern::ModernTable;
 use crate::Label;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::io::Cursor;
 use std::marker::PhantomData;
@@ -34,18 +35,71 @@ pub trait BdatFile<'b> {
     /// Returns the number of tables in the BDAT file.
     fn table_count(&self) -> usize;
 
+    /// Reads the names of all tables in the BDAT source, in file order.
+    ///
+    /// Unlike [`get_tables`], the crate's readers only read table headers and names, without
+    /// parsing columns or rows. The default implementation reads all tables.
+    ///
+    /// [`get_tables`]: BdatFile::get_tables
+    fn table_names(&mut self) -> Result<Vec<Label<'b>>>
+    where
+        Self::TableOut: TableName<'b>,
+    {
+        self.get_tables()
+            .map(|tables| tables.iter().map(TableName::name).collect())
+    }
+
+    /// Returns the `(row_count, column_count)` pair of all tables in the BDAT source, in
+    /// file order.
+    ///
+    /// Like [`table_names`], the crate's readers don't read any rows. The default
+    /// implementation reads all tables.
+    ///
+    /// [`table_names`]: BdatFile::table_names
+    fn table_shapes(&mut self) -> Result<Vec<(usize, usize)>>
+    where
+        Self::TableOut: TableShape,
+    {
+        self.get_tables()
+            .map(|tables| tables.iter().map(TableShape::shape).collect())
+    }
+
     /// Reads all tables from the BDAT source, then groups them by name.
+    ///
+    /// If multiple tables have the same name, only the last one is kept. Use
+    /// [`into_table_map`] to fail instead.
+    ///
+    /// [`into_table_map`]: BdatFile::into_table_map
     fn get_tables_by_name(&mut self) -> Result<HashMap<Label<'b>, Self::TableOut>>
     where
         Self::TableOut: TableName<'b>,
         Self: 'b,
     {
-        self.get_tables().map(|tables| {
-            tables
-                .into_iter()
-                .map(|t| (t.name(), t)) // TODO
-                .collect()
-        })
+        self.get_tables()
+            .map(|tables| tables.into_iter().map(|t| (t.name(), t)).collect())
+    }
+
+    /// Reads all tables from the BDAT source, then maps them by name.
+    ///
+    /// Returns [`BdatError::DuplicateTable`] if multiple tables have the same name.
+    fn into_table_map(mut self) -> Result<HashMap<Label<'b>, Self::TableOut>>
+    where
+        Self::TableOut: TableName<'b>,
+        Self: Sized + 'b,
+    {
+        let tables = self.get_tables()?;
+        let mut map = HashMap::with_capacity(tables.len());
+        for table in tables {
+            match map.entry(table.name()) {
+                Entry::Occupied(e) => {
+                    return Err(BdatError::DuplicateTable(e.key().clone().into_owned()))
+                }
+                Entry::Vacant(e) => {
+                    e.insert(table);
+                }
+            }
+        }
+        Ok(map)
     }
 }
 
@@ -53,6 +107,11 @@ pub trait TableName<'b> {
     fn name(&self) -> Label<'b>;
 }
 
+pub trait TableShape {
+    /// Returns the `(row_count, column_count)` pair of the table.
+    fn shape(&self) -> (usize, usize);
+}
+
 impl<'b, E> BdatSlice<'b, E> {
     pub fn new(bytes: &'b [u8]) -> Self {
         Self {
@@ -90,3 +149,21 @@ impl<'b> TableName<'b> for CompatTable<'b> {
         self.name_cloned()
     }
 }
+
+impl<'b> TableShape for ModernTable<'b> {
+    fn shape(&self) -> (usize, usize) {
+        (self.row_count(), self.column_count())
+    }
+}
+
+impl<'b> TableShape for LegacyTable<'b> {
+    fn shape(&self) -> (usize, usize) {
+        (self.row_count(), self.column_count())
+    }
+}
+
+impl<'b> TableShape for CompatTable<'b> {
+    fn shape(&self) -> (usize, usize) {
+        (self.row_count(), self.column_count())
+    }
+}
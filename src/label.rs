@@ -6,6 +6,21 @@ use std::borrow::Cow;
 use std::fmt::Display;
 
 /// A name for a BDAT element (table, column, ID, etc.)
+///
+/// ## Equality
+/// Labels are compared by variant first, so a [`Label::String`] is never equal to the
+/// [`Label::Hash`] of the same string. This also applies to hash-based collections, which
+/// may hold both forms as distinct keys. To compare labels that may be in different forms,
+/// hash them first with [`Label::into_hash`]:
+///
+/// ```
+/// use bdat::{BdatVersion, Label};
+///
+/// let string = Label::from("Name");
+/// let hash = Label::Hash(bdat::hash::murmur3_str("Name"));
+/// assert_ne!(string, hash);
+/// assert_eq!(string.into_hash(BdatVersion::Modern), hash);
+/// ```
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Label<'buf> {
@@ -29,6 +44,9 @@ impl<'buf> Label<'buf> {
     ///
     /// If `force_hash` is `true`, the label will be re-hashed
     /// if it is [`Label::String`].
+    ///
+    /// Hashes without angle brackets are treated as plain-text names. To parse them as
+    /// hashes, use [`Label::parse_flexible`].
     pub fn parse<S: Into<Utf<'buf>>>(text: S, force_hash: bool) -> Self {
         let text = text.into();
         if text.len() == 10 && text.as_bytes()[0] == b'<' {
@@ -43,6 +61,33 @@ impl<'buf> Label<'buf> {
         }
     }
 
+    /// Like [`Label::parse`], but also recognizes bare hashes without angle brackets, which
+    /// some tools emit.
+    ///
+    /// The format is as follows:  
+    /// * `<01ABCDEF>` or `01ABCDEF` (8 hex digits) => `Label::Hash(0x01abcdef)`
+    /// * s => `Label::String(s)`
+    ///
+    /// Note that plain-text names that happen to be 8 hex digits long (e.g. `DEADBEEF`) are
+    /// also parsed as hashes, which is why this is not the default.
+    ///
+    /// ```
+    /// use bdat::Label;
+    ///
+    /// assert_eq!(Label::parse_flexible("01abcdef", false), Label::Hash(0x01abcdef));
+    /// assert_eq!(Label::parse_flexible("<01ABCDEF>", false), Label::Hash(0x01abcdef));
+    /// assert_eq!(Label::parse("01abcdef", false), Label::String("01abcdef".into()));
+    /// ```
+    pub fn parse_flexible<S: Into<Utf<'buf>>>(text: S, force_hash: bool) -> Self {
+        let text = text.into();
+        if text.len() == 8 && text.bytes().all(|b| b.is_ascii_hexdigit()) {
+            if let Ok(n) = u32::from_str_radix(&text, 16) {
+                return Label::Hash(n);
+            }
+        }
+        Self::parse(text, force_hash)
+    }
+
     /// If needed, turns the label into a hashed label.
     pub fn into_hash(self, version: BdatVersion) -> Self {
         if !version.are_labels_hashed() {
@@ -54,6 +99,26 @@ impl<'buf> Label<'buf> {
         }
     }
 
+    /// Returns whether the label is a hash ([`Label::Hash`]).
+    pub fn is_hashed(&self) -> bool {
+        matches!(self, Self::Hash(_))
+    }
+
+    /// Returns whether the label is a plain-text string ([`Label::String`]).
+    pub fn is_string(&self) -> bool {
+        matches!(self, Self::String(_))
+    }
+
+    /// Returns the hash value if the label is hashed.
+    ///
+    /// String labels are not hashed by this function, use [`Label::into_hash`] for that.
+    pub fn as_hash(&self) -> Option<u32> {
+        match self {
+            Self::Hash(h) => Some(*h),
+            Self::String(_) => None,
+        }
+    }
+
     /// An alternative to [`ToString::to_string`] that returns a reference to the label if it's
     /// already a string.
     pub fn to_string_convert(&self) -> Utf {
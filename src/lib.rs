@@ -91,6 +91,7 @@
 //!
 //! fn read_detect() -> BdatResult<()> {
 //!     // Mutable access is required, as this might be a legacy table.
+//!     // Use `bdat::from_bytes_ref` to copy scrambled legacy tables instead.
 //!     let mut data = [0u8; 0];
 //!     // Endianness is also detected automatically.
 //!     let mut bdat_file = bdat::from_bytes(&mut data)?;
@@ -122,6 +123,8 @@
 //! appropriate module implementation.
 //!
 //! Tables obtained with the auto-detecting functions must be extracted or converted first.
+//! Conversely, [`modern::ModernTable::into_compat`] and [`legacy::LegacyTable::into_compat`] wrap
+//! tables back into a [`compat::CompatTable`].
 //!
 //! ```
 //! use bdat::{BdatResult, LegacyVersion, SwitchEndian, WiiEndian};
@@ -146,17 +149,41 @@
 //! `Deserialize`.
 //!
 //! While the crate doesn't support serializing/deserializing BDAT to Rust types, this can be used
-//! to transcode BDAT to other formats.  
+//! to transcode BDAT to other formats. Whole tables can be (de)serialized too, see the
+//! [`serde`] module.  
 //! The [bdat-toolset] crate will convert BDAT to CSV and JSON, and JSON to BDAT.
 //!
+//! ## Shift-JIS support
+//! When the `encoding` feature flag is enabled, string cells in legacy tables can be read and
+//! written as Shift-JIS, which is used by some Japanese-region files. See
+//! [`LegacyReadOptions::encoding`] and [`LegacyWriteOptions::encoding`].
+//!
+//! ## Parallel writing
+//! When the `parallel` feature flag is enabled, the modern writer serializes tables in
+//! parallel, using `rayon`. The output is the same as without the feature.
+//!
+//! ## Archives
+//! When the `archive` feature flag is enabled, the [`archive`] module can be used to find BDAT
+//! files inside game archives.
+//!
+//! ## Testing
+//! When the `testing` feature flag is enabled, the [`testing`] module provides helpers to
+//! check that files survive a read-write round trip.
+//!
 //! [MONOLITHSOFT]: https://www.monolithsoft.co.jp/
 //! [bdat-toolset]: https://github.com/RoccoDev/bdat-rs/tree/master/toolset
 //! [`LegacyTable`]: crate::legacy::LegacyTable
 //! [`ModernTable`]: crate::modern::ModernTable
+//! [`LegacyReadOptions::encoding`]: crate::legacy::LegacyReadOptions::encoding
+//! [`LegacyWriteOptions::encoding`]: crate::legacy::LegacyWriteOptions::encoding
 
+#[cfg(feature = "archive")]
+pub mod archive;
 pub mod hash;
 #[cfg(feature = "serde")]
 pub mod serde;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 pub(crate) mod error;
 pub(crate) mod io;
@@ -168,9 +195,10 @@ mod public;
 pub use error::BdatError;
 pub use error::Result as BdatResult;
 pub use io::detect::*;
-pub use io::{BdatFile, BdatVersion, LegacyVersion, SwitchEndian, WiiEndian};
+pub use io::{BdatFile, BdatVersion, FormatConstants, LegacyVersion, SwitchEndian, WiiEndian};
 pub use label::Label;
 pub use public::*;
 pub use table::cell::*;
 pub use table::compat;
+pub use table::convert::FormatConvertError;
 pub use table::row::*;
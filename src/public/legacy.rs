@@ -2,4 +2,5 @@
 
 pub use crate::io::legacy::*;
 pub use crate::table::builder::LegacyTableBuilder;
+pub use crate::table::convert::{ConversionIssue, ConversionReport};
 pub use crate::table::legacy::*;
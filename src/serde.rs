@@ -1,17 +1,37 @@
 //! Serde implementations for crate types (requires feature `serde`)
+//!
+//! Whole tables can be serialized and deserialized as well. Tables are represented as a
+//! struct with the table's name, base ID, columns, and rows, where each row is a sequence
+//! of cells, in column order. When deserializing, cells are parsed according to the type
+//! of their column.
+//!
+//! ```
+//! use bdat::modern::{ModernColumn, ModernRow, ModernTable, ModernTableBuilder};
+//! use bdat::{Label, Value, ValueType};
+//!
+//! let table = ModernTableBuilder::with_name(Label::Hash(1))
+//!     .add_column(ModernColumn::new(ValueType::UnsignedInt, Label::Hash(2)))
+//!     .add_row(ModernRow::new(vec![Value::UnsignedInt(10)]))
+//!     .build();
+//! let json = serde_json::to_string(&table).unwrap();
+//! let back: ModernTable = serde_json::from_str(&json).unwrap();
+//! assert_eq!(table, back);
+//! ```
 
 use crate::legacy::float::BdatReal;
+use crate::legacy::{LegacyColumn, LegacyRow, LegacyTable, LegacyTableBuilder};
+use crate::modern::{ModernColumn, ModernRow, ModernTable, ModernTableBuilder};
 use crate::table::private::ColumnSerialize;
-use crate::{Cell, Label, Value, ValueType};
+use crate::{Cell, Label, Utf, Value, ValueType};
 use serde::de::value::MapAccessDeserializer;
 use serde::de::MapAccess;
-use serde::ser::SerializeMap;
+use serde::ser::{SerializeMap, SerializeSeq, SerializeStruct};
 use serde::{
     de::{self, DeserializeSeed, Visitor},
     ser, Deserialize, Deserializer, Serialize, Serializer,
 };
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 use std::ops::Deref;
 
@@ -37,9 +57,40 @@ enum ValueTypeFields {
 
 struct HexVisitor;
 
+struct InferredVisitor;
+
 /// An implementation of [`DeserializeSeed`] for [`Cell`]s.
 pub struct CellSeed<'a, C: ColumnSerialize>(&'a C);
 
+/// Serializes a legacy row as a sequence of cells.
+struct SerializeLegacyRow<'a, 'b> {
+    columns: &'a [LegacyColumn<'b>],
+    row: &'a LegacyRow<'b>,
+}
+
+#[derive(Deserialize)]
+struct ModernTableData<'b> {
+    name: Label<'b>,
+    #[serde(default = "default_base_id")]
+    base_id: u32,
+    /// Absent: use the first hash column, like [`ModernTableBuilder::build`]
+    #[serde(default, deserialize_with = "deserialize_present")]
+    primary_key: Option<Option<usize>>,
+    #[serde(default)]
+    reserved: [u8; 4],
+    columns: Vec<ModernColumn<'b>>,
+    rows: Vec<Vec<serde_value::Value>>,
+}
+
+#[derive(Deserialize)]
+struct LegacyTableData<'b> {
+    name: Utf<'b>,
+    #[serde(default = "default_base_id")]
+    base_id: u16,
+    columns: Vec<LegacyColumn<'b>>,
+    rows: Vec<Vec<serde_value::Value>>,
+}
+
 impl<'b, 't, C: ColumnSerialize> SerializeCell<'b, 't, C> {
     pub fn from_ref(column: C, cell: &'b Cell<'t>) -> Self {
         SerializeCell {
@@ -79,6 +130,165 @@ impl<'b, 't, C: ColumnSerialize> Serialize for SerializeCell<'b, 't, C> {
     }
 }
 
+impl<'a, 'b> Serialize for SerializeLegacyRow<'a, 'b> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.columns.len()))?;
+        for (column, cell) in self.columns.iter().zip(self.row.cells()) {
+            seq.serialize_element(&SerializeCell::from_ref(column, cell))?;
+        }
+        seq.end()
+    }
+}
+
+impl<'b> Serialize for ModernTable<'b> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let rows: Vec<_> = self.rows.iter().map(|r| &r.values).collect();
+        let mut table = serializer.serialize_struct("ModernTable", 6)?;
+        table.serialize_field("name", &self.name)?;
+        table.serialize_field("base_id", &self.base_id)?;
+        table.serialize_field("primary_key", &self.primary_key())?;
+        table.serialize_field("reserved", &self.reserved)?;
+        table.serialize_field("columns", self.columns.as_slice())?;
+        table.serialize_field("rows", &rows)?;
+        table.end()
+    }
+}
+
+impl<'de, 'b> Deserialize<'de> for ModernTable<'b> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let data = ModernTableData::deserialize(deserializer)?;
+        if let Some(Some(key)) = data.primary_key {
+            match data.columns.get(key) {
+                Some(c) if c.value_type == ValueType::HashRef => {}
+                _ => return Err(de::Error::custom("primary key must be a hash column")),
+            }
+        }
+        let rows = data
+            .rows
+            .into_iter()
+            .map(|row| {
+                check_row_len::<D::Error>(&row, data.columns.len())?;
+                data.columns
+                    .iter()
+                    .zip(row)
+                    .map(|(c, v)| c.value_type.deser_value(v).map_err(de::Error::custom))
+                    .collect::<Result<_, _>>()
+                    .map(ModernRow::new)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let primary_key = data.primary_key.unwrap_or_else(|| {
+            data.columns
+                .iter()
+                .position(|c| c.value_type == ValueType::HashRef)
+        });
+        if let Some(key) = primary_key {
+            // Building the row hash table panics on duplicate keys
+            let mut keys = HashSet::with_capacity(rows.len());
+            for row in &rows {
+                if let Some(Value::HashRef(hash)) = row.values.get(key) {
+                    if !keys.insert(*hash) {
+                        return Err(de::Error::custom(format_args!(
+                            "duplicate primary key {}",
+                            Label::Hash(*hash)
+                        )));
+                    }
+                }
+            }
+        }
+
+        let mut table = ModernTableBuilder::with_name(data.name)
+            .set_base_id(data.base_id)
+            .set_columns(data.columns)
+            .set_rows(rows)
+            .build_with_primary_key(primary_key);
+        table.reserved = data.reserved;
+        Ok(table)
+    }
+}
+
+impl<'b> Serialize for LegacyTable<'b> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let columns = self.columns.as_slice();
+        let rows: Vec<_> = self
+            .rows
+            .iter()
+            .map(|row| SerializeLegacyRow { columns, row })
+            .collect();
+        let mut table = serializer.serialize_struct("LegacyTable", 4)?;
+        table.serialize_field("name", &self.name)?;
+        table.serialize_field("base_id", &self.base_id)?;
+        table.serialize_field("columns", columns)?;
+        table.serialize_field("rows", &rows)?;
+        table.end()
+    }
+}
+
+impl<'de, 'b> Deserialize<'de> for LegacyTable<'b> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let data = LegacyTableData::deserialize(deserializer)?;
+        let rows = data
+            .rows
+            .into_iter()
+            .map(|row| {
+                check_row_len::<D::Error>(&row, data.columns.len())?;
+                data.columns
+                    .iter()
+                    .zip(row)
+                    .map(|(c, v)| CellSeed::from(c).deserialize(v).map_err(de::Error::custom))
+                    .collect::<Result<_, _>>()
+                    .map(LegacyRow::new)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        LegacyTableBuilder::with_name(data.name)
+            .set_base_id(data.base_id)
+            .set_columns(data.columns)
+            .set_rows(rows)
+            .try_build()
+            .map_err(de::Error::custom)
+    }
+}
+
+fn check_row_len<E: de::Error>(row: &[serde_value::Value], columns: usize) -> Result<(), E> {
+    if row.len() != columns {
+        return Err(de::Error::invalid_length(
+            row.len(),
+            &format!("{columns} cells").as_str(),
+        ));
+    }
+    Ok(())
+}
+
+/// Same default as [`ModernTableBuilder::with_name`] and [`LegacyTableBuilder::with_name`].
+fn default_base_id<T: From<u8>>() -> T {
+    1.into()
+}
+
+/// Distinguishes between an absent field (`None`) and an explicit `null` (`Some(None)`).
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
 impl<'b> Serialize for Value<'b> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -132,6 +342,111 @@ impl ValueType {
     }
 }
 
+impl<'de> Value<'de> {
+    /// Deserializes a [`Value`], inferring its type from the shape of the data.
+    ///
+    /// Unsigned numbers are mapped to the smallest unsigned integer type that fits them,
+    /// negative numbers to the smallest signed type, and floating-point numbers to
+    /// [`Value::Float`]. Strings shaped like `<XXXXXXXX>` become [`Value::HashRef`], other
+    /// strings become [`Value::String`].
+    ///
+    /// This is lossy: types such as [`Value::Percent`] or [`Value::MessageId`] can never
+    /// be produced, and a number's type depends on its value. When the column type is known,
+    /// prefer [`ValueType::deser_value`] or [`CellSeed`].
+    ///
+    /// ```
+    /// use bdat::Value;
+    ///
+    /// let value = Value::deserialize_inferred(serde_json::json!(300)).unwrap();
+    /// assert_eq!(Value::UnsignedShort(300), value);
+    /// ```
+    pub fn deserialize_inferred<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(InferredVisitor)
+    }
+}
+
+impl<'de> Visitor<'de> for InferredVisitor {
+    type Value = Value<'de>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("number or string")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if let Ok(b) = u8::try_from(v) {
+            Ok(Value::UnsignedByte(b))
+        } else if let Ok(s) = u16::try_from(v) {
+            Ok(Value::UnsignedShort(s))
+        } else {
+            u32::try_from(v)
+                .map(Value::UnsignedInt)
+                .map_err(|_| de::Error::invalid_value(de::Unexpected::Unsigned(v), &self))
+        }
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if let Ok(u) = u64::try_from(v) {
+            self.visit_u64(u)
+        } else if let Ok(b) = i8::try_from(v) {
+            Ok(Value::SignedByte(b))
+        } else if let Ok(s) = i16::try_from(v) {
+            Ok(Value::SignedShort(s))
+        } else {
+            i32::try_from(v)
+                .map(Value::SignedInt)
+                .map_err(|_| de::Error::invalid_value(de::Unexpected::Signed(v), &self))
+        }
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Value::Float(BdatReal::Unknown(v as f32)))
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Self::infer_str(v).unwrap_or(Value::String(Cow::Borrowed(v))))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Self::infer_str(v).unwrap_or_else(|| Value::String(Cow::Owned(v.to_string()))))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Self::infer_str(&v).unwrap_or(Value::String(Cow::Owned(v))))
+    }
+}
+
+impl InferredVisitor {
+    /// Parses hash strings (`<XXXXXXXX>`) into [`Value::HashRef`].
+    fn infer_str<'de>(v: &str) -> Option<Value<'de>> {
+        let hex = v.strip_prefix('<')?.strip_suffix('>')?;
+        if hex.len() != 8 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        u32::from_str_radix(hex, 16).ok().map(Value::HashRef)
+    }
+}
+
 impl<'de> Visitor<'de> for HexVisitor {
     type Value = u32;
 
@@ -473,6 +788,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deser_inferred() {
+        let values: Vec<Value> = serde_json::from_str::<Vec<serde_json::Value>>(
+            r#"[1, 256, 70000, -1, -200, -40000, 1.5, "Hello", "<01ABCDEF>", "<xyz>"]"#,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|v| Value::deserialize_inferred(v).unwrap())
+        .collect();
+        assert_eq!(
+            values,
+            [
+                Value::UnsignedByte(1),
+                Value::UnsignedShort(256),
+                Value::UnsignedInt(70000),
+                Value::SignedByte(-1),
+                Value::SignedShort(-200),
+                Value::SignedInt(-40000),
+                Value::Float(1.5.into()),
+                Value::String("Hello".into()),
+                Value::HashRef(0x01abcdef),
+                Value::String("<xyz>".into()),
+            ]
+        );
+
+        assert!(Value::deserialize_inferred(serde_json::json!(u64::MAX)).is_err());
+        assert!(Value::deserialize_inferred(serde_json::json!([1])).is_err());
+    }
+
     #[test]
     #[should_panic]
     fn json_deser_hash_overflow() {
@@ -551,4 +895,119 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn json_modern_table() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTable, ModernTableBuilder};
+        use crate::Label;
+
+        let mut table = ModernTableBuilder::with_name(Label::Hash(0xABCDEF01))
+            .set_base_id(5)
+            .add_column(ModernColumn::new(ValueType::HashRef, Label::Hash(1)))
+            .add_column(ModernColumn::new(ValueType::String, Label::Hash(2)))
+            .add_column(ModernColumn::new(ValueType::Float, Label::Hash(3)))
+            .add_row(ModernRow::new(vec![
+                Value::HashRef(10),
+                Value::String("Hello".into()),
+                Value::Float(1.5.into()),
+            ]))
+            .add_row(ModernRow::new(vec![
+                Value::HashRef(20),
+                Value::String("World".into()),
+                Value::Float((-0.25).into()),
+            ]))
+            .build();
+        table.set_reserved([1, 2, 3, 4]);
+
+        let json = serde_json::to_string(&table).unwrap();
+        let back: ModernTable = serde_json::from_str(&json).unwrap();
+        assert_eq!(table, back);
+        assert_eq!(Some(0), back.primary_key());
+        assert_eq!(6, back.get_row_by_hash(20).unwrap().id());
+
+        table.set_primary_key(None);
+        let json = serde_json::to_string(&table).unwrap();
+        let back: ModernTable = serde_json::from_str(&json).unwrap();
+        assert_eq!(None, back.primary_key());
+
+        let err = serde_json::from_str::<ModernTable>(
+            r#"{"name":{"Hash":1},"columns":[{"value_type":1,"label":{"Hash":2}}],"rows":[[]]}"#,
+        );
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn json_modern_table_keys() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTable, ModernTableBuilder};
+        use crate::Label;
+
+        let table = ModernTableBuilder::with_name(Label::Hash(0xABCDEF01))
+            .add_column(ModernColumn::new(ValueType::HashRef, Label::Hash(1)))
+            .add_row(ModernRow::new(vec![Value::HashRef(10)]))
+            .add_row(ModernRow::new(vec![Value::HashRef(20)]))
+            .build();
+        let mut json = serde_json::to_value(&table).unwrap();
+
+        // Missing base ID: same default as the builder
+        json.as_object_mut().unwrap().remove("base_id");
+        let back: ModernTable = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(1, back.base_id());
+        assert_eq!(table, back);
+
+        json["rows"][1] = json["rows"][0].clone();
+        let err = serde_json::from_value::<ModernTable>(json.clone()).unwrap_err();
+        assert!(err.to_string().contains("duplicate primary key"));
+
+        json["primary_key"] = 0.into();
+        json["columns"][0]["value_type"] = serde_json::to_value(ValueType::String).unwrap();
+        json["rows"] = serde_json::json!([]);
+        let err = serde_json::from_value::<ModernTable>(json).unwrap_err();
+        assert!(err.to_string().contains("must be a hash column"));
+    }
+
+    #[test]
+    fn json_legacy_table() {
+        use crate::legacy::{LegacyColumnBuilder, LegacyRow, LegacyTable, LegacyTableBuilder};
+
+        let table = LegacyTableBuilder::with_name("Table1")
+            .set_base_id(3)
+            .add_column(
+                LegacyColumnBuilder::new(ValueType::UnsignedInt, "Flags".into())
+                    .set_flags(vec![
+                        LegacyFlag::new_bit("Flag1", 0),
+                        LegacyFlag::new("Flag2", 0b110, 1),
+                    ])
+                    .build(),
+            )
+            .add_column(
+                LegacyColumnBuilder::new(ValueType::SignedShort, "List".into())
+                    .set_count(2)
+                    .build(),
+            )
+            .add_column(LegacyColumn::new(ValueType::String, "Name".into()))
+            .add_row(LegacyRow::new(vec![
+                Cell::Flags(vec![1, 2]),
+                Cell::List(vec![Value::SignedShort(-1), Value::SignedShort(2)]),
+                Cell::Single(Value::String("Row".into())),
+            ]))
+            .build();
+
+        let json = serde_json::to_string(&table).unwrap();
+        let back: LegacyTable = serde_json::from_str(&json).unwrap();
+        assert_eq!(table, back);
+        assert_eq!(3, back.base_id());
+    }
+
+    #[test]
+    fn json_legacy_flag() {
+        let flag = LegacyFlag::new("Flag", 0b1110_0000, 5);
+        let json = serde_json::to_string(&flag).unwrap();
+        assert_eq!(r#"{"label":"Flag","mask":224,"index":5}"#, json);
+        assert_eq!(flag, serde_json::from_str::<LegacyFlag>(&json).unwrap());
+
+        let err = serde_json::from_str::<LegacyFlag>(r#"{"label":"Flag","mask":224}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("missing field `index`"), "{err}");
+    }
 }
@@ -5,6 +5,7 @@ use super::{
     modern::ModernTable,
     private::{Column, Table},
 };
+use crate::{Value, ValueType};
 
 /// Builder for [`ModernTable`]
 pub type ModernTableBuilder<'b> = TableBuilderImpl<'b, ModernTable<'b>>;
@@ -18,6 +19,7 @@ pub struct TableBuilderImpl<'buf, T: Table<'buf>> {
     pub(crate) columns: ColumnMap<T::BuilderColumn, <T::BuilderColumn as Column>::Name>,
     pub(crate) base_id: T::Id,
     pub(crate) rows: Vec<T::BuilderRow>,
+    pub(crate) extra: T::BuilderExtra,
 }
 
 impl<'b, T> TableBuilderImpl<'b, T>
@@ -30,6 +32,7 @@ where
             base_id: 1.into(), // more sensible default, it's very rare for a table to have 0
             columns: ColumnMap::default(),
             rows: vec![],
+            extra: Default::default(),
         }
     }
 
@@ -44,6 +47,7 @@ where
             columns,
             base_id,
             rows,
+            extra: Default::default(),
         }
     }
 
@@ -58,6 +62,16 @@ where
         self
     }
 
+    /// Adds new rows at the end of the table, keeping existing rows.
+    ///
+    /// Like with [`add_row`], row IDs follow from the rows' position in the table.
+    ///
+    /// [`add_row`]: TableBuilderImpl::add_row
+    pub fn extend_rows(mut self, rows: impl IntoIterator<Item = impl Into<T::BuilderRow>>) -> Self {
+        self.rows.extend(rows.into_iter().map(Into::into));
+        self
+    }
+
     /// Sets the entire row list for the table.
     pub fn set_rows(mut self, rows: Vec<T::BuilderRow>) -> Self {
         self.rows = rows;
@@ -77,6 +91,91 @@ where
 
 /// Modern builder -> Modern table
 impl<'b> ModernTableBuilder<'b> {
+    /// Sorts rows by the hash in their primary key column, in ascending order.
+    ///
+    /// The primary key is the one the table would be built with: the key of the table this
+    /// builder was created from, or the first hash column (see [`ModernTable::primary_key`]).
+    /// Rows without a key are moved to the end, in their original order.
+    ///
+    /// **This changes row IDs**: rows are still numbered contiguously starting from the
+    /// base ID, so a row's new ID is determined by its position after sorting.
+    pub fn sort_rows_by_hash(mut self) -> Self {
+        let key = self.primary_key();
+        self.rows.sort_by_key(|row| {
+            let hash = match key.and_then(|k| row.values.get(k)) {
+                Some(Value::HashRef(hash)) => Some(*hash),
+                _ => None,
+            };
+            (hash.is_none(), hash)
+        });
+        self
+    }
+
+    /// Returns the primary key the table will be built with.
+    pub(crate) fn primary_key(&self) -> Option<usize> {
+        self.extra.primary_key.unwrap_or_else(|| {
+            self.columns
+                .iter()
+                .position(|c| c.value_type == ValueType::HashRef)
+        })
+    }
+
+    /// Sorts columns by their label (see [`ModernColumn::cmp_by_label`]), reordering
+    /// the values of each row accordingly.
+    ///
+    /// Columns with the same label keep their relative order.
+    ///
+    /// [`ModernColumn::cmp_by_label`]: crate::modern::ModernColumn::cmp_by_label
+    pub fn sort_columns_by_label(self) -> Self {
+        let columns = self.columns.as_slice();
+        let mut order = (0..columns.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| columns[a].cmp_by_label(&columns[b]));
+        self.reorder_columns(&order)
+    }
+
+    /// Reorders columns to match the column order of a reference table, usually the table
+    /// this builder was created from. The values of each row are reordered accordingly.
+    ///
+    /// Columns are matched by label. Columns that are not in the reference table are moved
+    /// to the end, keeping their relative order. This keeps the row layout close to the
+    /// original file's when rewriting a table that had columns added or reordered.
+    pub fn preserve_column_order(self, from: &ModernTable) -> Self {
+        let columns = self.columns.as_slice();
+        let mut order = (0..columns.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| {
+            from.columns
+                .label_map
+                .position(&columns[i].label)
+                .unwrap_or(usize::MAX)
+        });
+        self.reorder_columns(&order)
+    }
+
+    /// Moves columns (and row values) so that the column at `order[i]` becomes the `i`-th
+    /// column.
+    fn reorder_columns(mut self, order: &[usize]) -> Self {
+        let mut columns = std::mem::take(&mut self.columns)
+            .into_raw()
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        self.columns = order.iter().filter_map(|&i| columns[i].take()).collect();
+        if let Some(Some(key)) = self.extra.primary_key {
+            self.extra.primary_key = Some(order.iter().position(|&i| i == key));
+        }
+        for row in &mut self.rows {
+            let mut values = std::mem::take(&mut row.values)
+                .into_iter()
+                .map(Some)
+                .collect::<Vec<_>>();
+            row.values = order
+                .iter()
+                .filter_map(|&i| values.get_mut(i)?.take())
+                .collect();
+        }
+        self
+    }
+
     pub fn try_build(self) -> Result<ModernTable<'b>, FormatConvertError> {
         // No need for MaxRowCountExceeded here, we panic on row insertions if
         // the limit is reached, and all legacy table formats have a lower limit
@@ -87,18 +186,45 @@ impl<'b> ModernTableBuilder<'b> {
     pub fn build(self) -> ModernTable<'b> {
         self.try_build().unwrap()
     }
+
+    /// Builds the table, using the given column as its primary key instead of the first
+    /// hash column. See [`ModernTable::set_primary_key`] for details.
+    ///
+    /// ## Panics
+    /// Panics if the column does not exist or is not of type [`ValueType::HashRef`], or
+    /// if two rows have the same key.
+    ///
+    /// [`ValueType::HashRef`]: crate::ValueType::HashRef
+    pub fn build_with_primary_key(self, column: Option<usize>) -> ModernTable<'b> {
+        ModernTable::with_primary_key(self, column)
+    }
+}
+
+/// Returns the row count of a legacy table, checking that all row IDs fit in a `u16`.
+///
+/// The base ID and row count are separate header fields, so the last row's ID can be
+/// `u16::MAX`. Otherwise, the error reports the last row's ID.
+pub(crate) fn legacy_row_count(base_id: u16, rows: usize) -> Result<u16, FormatConvertError> {
+    let rows = u16::try_from(rows).map_err(|_| FormatConvertError::MaxRowCountExceeded)?;
+    let last_id = (base_id as u32 + rows as u32).saturating_sub(1);
+    if last_id > u16::MAX as u32 {
+        return Err(FormatConvertError::UnsupportedRowId(last_id));
+    }
+    Ok(rows)
 }
 
 /// Legacy builder -> Legacy table
 impl<'b> LegacyTableBuilder<'b> {
+    /// Builds the table, checking that its row IDs are supported by the legacy format.
+    ///
+    /// Rows are always numbered contiguously, starting from the base ID, so the table's
+    /// base ID and row count match the header fields it will be written with. This fails with
+    /// [`FormatConvertError::MaxRowCountExceeded`] if there are more than `u16::MAX` rows, or
+    /// with [`FormatConvertError::UnsupportedRowId`] if the last row's ID would overflow a
+    /// `u16`. The last row's ID can be `u16::MAX`, as the base ID and row count are separate
+    /// header fields.
     pub fn try_build(self) -> Result<LegacyTable<'b>, FormatConvertError> {
-        let rows =
-            u16::try_from(self.rows.len()).map_err(|_| FormatConvertError::MaxRowCountExceeded)?;
-        if self.base_id.checked_add(rows).is_none() {
-            // If there are enough rows to overflow from base_id, then we definitely have a row
-            // with ID u16::MAX
-            return Err(FormatConvertError::UnsupportedRowId(u16::MAX as u32));
-        }
+        legacy_row_count(self.base_id, self.rows.len())?;
         Ok(LegacyTable::new(self))
     }
 
@@ -66,6 +66,18 @@ pub enum Cell<'b> {
 }
 
 /// A value in a Bdat cell
+///
+/// Values can also be created from Rust primitives and strings:
+/// ```
+/// use bdat::Value;
+///
+/// assert_eq!(Value::UnsignedInt(10), 10u32.into());
+/// assert_eq!(Value::SignedShort(-1), (-1i16).into());
+/// assert_eq!(Value::String("hi".into()), "hi".into());
+/// ```
+///
+/// Unsigned integers always map to their plain variants (e.g. `u32` to [`Value::UnsignedInt`],
+/// not [`Value::HashRef`]), and `f32` maps to [`Value::Float`].
 #[derive(EnumKind, Debug, Clone, PartialEq)]
 #[enum_kind(
     ValueType,
@@ -234,7 +246,7 @@ impl<'b> Value<'b> {
     /// This does **not** format other values, use the Display trait for that.
     ///
     /// **Note**: this will potentially copy the string, if the table is borrowing its source.
-    /// To avoid copies, use [`Value::as_str`].
+    /// To avoid copies, use [`Value::as_str`] or [`Value::string_cow`].
     ///
     /// # Panics
     /// If the value is not stored as a string.
@@ -255,21 +267,53 @@ impl<'b> Value<'b> {
             _ => panic!("value is not a string"),
         }
     }
+
+    /// Returns the underlying string value, or `None` if the value is not stored as a string.
+    ///
+    /// If the string is borrowed from the table's source (`'b`), it is returned without
+    /// copying, and can outlive `self`. Otherwise, the string is cloned.
+    pub fn string_cow(&self) -> Option<Cow<'b, str>> {
+        match self {
+            Self::String(s) | Self::DebugString(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the size of this value when serialized in a row.
+    ///
+    /// This is the same as the [`ValueType::data_len`] of the value's type. For strings,
+    /// this is the size of the string table offset, not the length of the string.
+    pub fn byte_size(&self) -> usize {
+        ValueType::from(self).data_len()
+    }
 }
 
+/// The size of a single cell for each [`ValueType`], indexed by the type's discriminant.
+///
+/// Strings are stored as offsets into the string table, so their size is that of the offset.
+///
+/// ```
+/// use bdat::{ValueType, VALUE_TYPE_SIZES};
+///
+/// assert_eq!(2, VALUE_TYPE_SIZES[ValueType::MessageId as usize]);
+/// ```
+pub const VALUE_TYPE_SIZES: [usize; 14] = [0, 1, 2, 4, 1, 2, 4, 4, 4, 4, 1, 4, 1, 2];
+
 impl ValueType {
     /// Returns the size of a single cell with this value type.
-    pub fn data_len(self) -> usize {
-        use ValueType::*;
-        match self {
-            Unknown => 0,
-            UnsignedByte | SignedByte | Percent | Unknown12 => 1,
-            UnsignedShort | SignedShort | MessageId => 2,
-            UnsignedInt | SignedInt | String | Float | HashRef | DebugString => 4,
-        }
+    ///
+    /// See [`VALUE_TYPE_SIZES`] for the full size table.
+    pub const fn data_len(self) -> usize {
+        VALUE_TYPE_SIZES[self as usize]
     }
 
     /// Returns whether the given version supports the value type.
+    ///
+    /// Modern tables support all value types. Legacy tables only support the integer types,
+    /// [`String`] and [`Float`].
+    ///
+    /// [`String`]: ValueType::String
+    /// [`Float`]: ValueType::Float
     pub fn is_supported(self, version: BdatVersion) -> bool {
         use ValueType::*;
         match self {
@@ -279,6 +323,28 @@ impl ValueType {
     }
 }
 
+impl Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Unknown => "unknown",
+            Self::UnsignedByte => "unsigned byte",
+            Self::UnsignedShort => "unsigned short",
+            Self::UnsignedInt => "unsigned int",
+            Self::SignedByte => "signed byte",
+            Self::SignedShort => "signed short",
+            Self::SignedInt => "signed int",
+            Self::String => "string",
+            Self::Float => "float",
+            Self::HashRef => "hash reference",
+            Self::Percent => "percent",
+            Self::DebugString => "debug string",
+            Self::Unknown12 => "unknown (12)",
+            Self::MessageId => "message ID",
+        };
+        f.write_str(name)
+    }
+}
+
 impl From<ValueType> for u8 {
     fn from(t: ValueType) -> Self {
         t as u8
@@ -358,6 +424,28 @@ from_value!(i16, Value::SignedShort);
 from_value!(i8, Value::SignedByte);
 from_value!(BdatReal, Value::Float);
 
+macro_rules! into_value {
+    ($val:ty, $variant:path) => {
+        impl<'b> From<$val> for Value<'b> {
+            fn from(value: $val) -> Self {
+                $variant(value.into())
+            }
+        }
+    };
+}
+
+into_value!(u32, Value::UnsignedInt);
+into_value!(u16, Value::UnsignedShort);
+into_value!(u8, Value::UnsignedByte);
+into_value!(i32, Value::SignedInt);
+into_value!(i16, Value::SignedShort);
+into_value!(i8, Value::SignedByte);
+into_value!(BdatReal, Value::Float);
+into_value!(f32, Value::Float);
+into_value!(String, Value::String);
+into_value!(&'b str, Value::String);
+into_value!(Utf<'b>, Value::String);
+
 impl<'t, 'tb> FromValue<'t, 'tb> for f32 {
     fn extract(value: &Value<'_>) -> Option<Self> {
         BdatReal::extract(value).map(Into::into)
@@ -381,3 +469,59 @@ impl<'t, 'tb> FromValue<'t, 'tb> for &'t str {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{Value, ValueType, VALUE_TYPE_SIZES};
+
+    #[test]
+    fn supported_value_types() {
+        use crate::{BdatVersion, LegacyVersion};
+
+        for version in [BdatVersion::Modern, LegacyVersion::Switch.into()] {
+            let expected = (1..VALUE_TYPE_SIZES.len() as u8)
+                .map(|t| ValueType::try_from(t).unwrap())
+                .filter(|t| t.is_supported(version))
+                .collect::<Vec<_>>();
+            assert_eq!(expected, version.supported_value_types(), "{version}");
+        }
+    }
+
+    #[test]
+    fn string_cow() {
+        let source = String::from("borrowed");
+        let value = Value::String(Cow::Borrowed(&source));
+        let cow = value.string_cow();
+        drop(value);
+        let Some(Cow::Borrowed(s)) = cow else {
+            panic!("expected borrowed string");
+        };
+        assert_eq!(s.as_ptr(), source.as_ptr());
+
+        let value = Value::DebugString(Cow::Owned("owned".to_string()));
+        let cow = value.string_cow();
+        drop(value);
+        assert!(matches!(cow, Some(Cow::Owned(s)) if s == "owned"));
+        assert_eq!(Value::UnsignedInt(1).string_cow(), None);
+    }
+
+    #[test]
+    fn value_type_sizes() {
+        use ValueType::*;
+
+        for (i, &size) in VALUE_TYPE_SIZES.iter().enumerate() {
+            let ty = ValueType::try_from(i as u8).unwrap();
+            let expected = match ty {
+                Unknown => 0,
+                UnsignedByte | SignedByte | Percent | Unknown12 => 1,
+                UnsignedShort | SignedShort | MessageId => 2,
+                UnsignedInt | SignedInt | String | Float | HashRef | DebugString => 4,
+            };
+            assert_eq!(expected, size, "{ty:?}");
+            assert_eq!(size, ty.data_len(), "{ty:?}");
+        }
+        assert!(ValueType::try_from(VALUE_TYPE_SIZES.len() as u8).is_err());
+    }
+}
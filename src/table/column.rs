@@ -14,6 +14,8 @@ pub struct ColumnMap<C: Column, L = <C as Column>::Name> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct NameMap<L> {
     positions: Vec<(L, usize)>,
+    /// Number of labels pushed, including duplicates that were not added to `positions`
+    len: usize,
 }
 
 #[derive(Clone, Copy)]
@@ -28,6 +30,11 @@ impl<C: Column> ColumnMap<C, C::Name> {
         self.columns.push(column);
     }
 
+    /// Regenerates the label index from the current column labels.
+    pub(crate) fn rebuild(&mut self) {
+        self.label_map = self.columns.iter().map(C::clone_label).collect();
+    }
+
     pub(crate) fn as_slice(&self) -> &[C] {
         &self.columns
     }
@@ -56,10 +63,13 @@ where
             .map(|i| self.positions[i].1)
     }
 
+    /// Adds the label for the next column. If the label is already present, lookups keep
+    /// returning the first column with that label.
     pub fn push(&mut self, label: L) {
         if let Err(idx) = self.positions.binary_search_by_key(&&label, |(l, _)| l) {
-            self.positions.insert(idx, (label, self.positions.len()));
+            self.positions.insert(idx, (label, self.len));
         }
+        self.len += 1;
     }
 }
 
@@ -99,6 +109,7 @@ impl<L> Default for NameMap<L> {
     fn default() -> Self {
         Self {
             positions: Default::default(),
+            len: 0,
         }
     }
 }
@@ -9,14 +9,18 @@
 //! [`modern`]: crate::modern
 //! [`legacy`]: crate::legacy
 
+use std::collections::hash_map::DefaultHasher;
 use std::convert::Infallible;
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
 
 use super::column::CompatColumnMap;
 use super::private::{CellAccessor, Column, ColumnSerialize, LabelMap, Table};
 use super::util::CompatIter;
+use crate::hash::murmur3_str;
 use crate::legacy::{LegacyColumn, LegacyFlag, LegacyRow, LegacyTable};
 use crate::modern::{ModernColumn, ModernRow, ModernTable};
-use crate::{BdatResult, Cell, Label, RowId, RowRef, ValueType};
+use crate::{BdatResult, Cell, Label, RowId, RowRef, Value, ValueType};
 
 /// A BDAT table view with version metadata.
 ///
@@ -239,6 +243,21 @@ impl<'b> CompatTable<'b> {
         }
     }
 
+    /// Changes the table's name, converting it to a format-supported label if needed.
+    ///
+    /// Unlike [`set_name`](Self::set_name), this doesn't panic on hashed labels for
+    /// legacy tables: the hash is stored in its `<XXXXXXXX>` string form instead, which
+    /// [`Label::parse`] turns back into a hash.
+    pub fn set_name_lossy(&mut self, name: Label<'b>) {
+        match self {
+            Self::Modern(m) => m.set_name(name),
+            Self::Legacy(l) => l.set_name(match name {
+                Label::String(s) => s,
+                hash => hash.to_string().into(),
+            }),
+        }
+    }
+
     /// Gets the minimum row ID in the table.
     pub fn base_id(&self) -> RowId {
         match self {
@@ -256,14 +275,7 @@ impl<'b> CompatTable<'b> {
     /// ## Panics
     /// If there is no row for the given ID.
     pub fn row(&self, id: RowId) -> CompatRowRef<'_, 'b> {
-        match self {
-            Self::Modern(m) => m
-                .row(id)
-                .map(CompatRef::Modern, CompatColumnMap::Modern(&m.columns)),
-            Self::Legacy(l) => l
-                .row(id.try_into().expect("invalid id for legacy row"))
-                .map(CompatRef::Legacy, CompatColumnMap::Legacy(&l.columns)),
-        }
+        self.get_row(id).expect("row not found")
     }
 
     /// Attempts to get a row by its ID.  
@@ -329,6 +341,22 @@ impl<'b> CompatTable<'b> {
         }
     }
 
+    /// Gets an iterator that visits every cell in the table, as
+    /// `(row ID, column label, cell)` triples.
+    ///
+    /// Rows are visited in order, and cells in column order within each row. Like
+    /// [`CompatRow::cells`], cells are cloned. For legacy tables, column labels are
+    /// wrapped into [`Label::String`].
+    pub fn iter_cells_flat(&self) -> impl Iterator<Item = (RowId, Label<'_>, Cell<'b>)> {
+        self.rows().flat_map(move |row| {
+            let id = row.id();
+            let cells = row.cells().collect::<Vec<_>>();
+            self.columns()
+                .zip(cells)
+                .map(move |(col, cell)| (id, col.label(), cell))
+        })
+    }
+
     /// Gets an owning iterator over this table's column definitions.
     ///
     /// Columns from modern tables will be returned as-is. In the case of legacy
@@ -340,6 +368,16 @@ impl<'b> CompatTable<'b> {
         }
     }
 
+    /// Returns a list of this table's column labels, in column order.
+    ///
+    /// For legacy tables, labels are wrapped into [`Label::String`].
+    pub fn column_labels(&self) -> Vec<Label<'b>> {
+        match self {
+            Self::Modern(m) => m.column_labels(),
+            Self::Legacy(l) => l.column_labels().into_iter().map(Label::String).collect(),
+        }
+    }
+
     pub fn row_count(&self) -> usize {
         versioned!(&self, row_count())
     }
@@ -347,6 +385,48 @@ impl<'b> CompatTable<'b> {
     pub fn column_count(&self) -> usize {
         versioned!(&self, column_count())
     }
+
+    /// Gets the column definition at the given index, if present.
+    ///
+    /// Lookups by label always find the first column with that label. This can be used to
+    /// reach other columns with the same label, along with [`RowRef::get_at`].
+    pub fn column_at(&self, index: usize) -> Option<CompatColumnRef<'_, 'b>> {
+        match self {
+            Self::Modern(m) => m.column_at(index).map(CompatColumnRef::Modern),
+            Self::Legacy(l) => l.column_at(index).map(CompatColumnRef::Legacy),
+        }
+    }
+
+    /// Returns a hash of the table's schema, i.e. the ordered list of column labels,
+    /// value types, counts and flag layouts.
+    ///
+    /// Tables with the same schema have the same fingerprint, so this can be used to quickly
+    /// group compatible tables. String labels are hashed first, meaning that a column named
+    /// `foo` matches a column labeled with the hash of `foo`. Fingerprints are only stable
+    /// within the same build of the crate, and should not be persisted.
+    pub fn schema_fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for col in self.columns() {
+            label_hash(&col.label()).hash(&mut hasher);
+            (col.value_type() as u8).hash(&mut hasher);
+            col.count().hash(&mut hasher);
+            col.flags().len().hash(&mut hasher);
+            for flag in col.flags() {
+                murmur3_str(flag.label()).hash(&mut hasher);
+                flag.mask().hash(&mut hasher);
+                flag.shift_amount().hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+}
+
+/// Returns the hash of a label, hashing the label's text if needed.
+fn label_hash(label: &Label) -> u32 {
+    match label {
+        Label::Hash(h) => *h,
+        Label::String(s) => murmur3_str(s),
+    }
 }
 
 impl<'b> CompatColumn<'b> {
@@ -359,6 +439,59 @@ impl<'b> CompatColumn<'b> {
 }
 
 impl<'buf> CompatColumn<'buf> {
+    /// If the column is modern, returns the underlying column.
+    ///
+    /// ## Panics
+    /// Panics if the column is not modern.  
+    /// For a panic-free function that converts instead, use [`try_into_modern`].
+    ///
+    /// [`try_into_modern`]: Self::try_into_modern
+    pub fn into_modern(self) -> ModernColumn<'buf> {
+        match self {
+            Self::Modern(m) => m,
+            _ => panic!("not modern"),
+        }
+    }
+
+    /// If the column is legacy, returns the underlying column.
+    ///
+    /// ## Panics
+    /// Panics if the column is not legacy.  
+    /// For a panic-free function that converts instead, use [`try_into_legacy`].
+    ///
+    /// [`try_into_legacy`]: Self::try_into_legacy
+    pub fn into_legacy(self) -> LegacyColumn<'buf> {
+        match self {
+            Self::Legacy(l) => l,
+            _ => panic!("not legacy"),
+        }
+    }
+
+    /// Returns a modern column as close to the underlying column as possible.
+    ///
+    /// * If the column is modern, this does nothing and returns it.
+    /// * If the column is legacy, it tries to convert it to the
+    ///   modern format, and returns the result.
+    pub fn try_into_modern(self) -> BdatResult<ModernColumn<'buf>> {
+        match self {
+            Self::Modern(m) => Ok(m),
+            Self::Legacy(l) => Ok(l.try_into()?),
+        }
+    }
+
+    /// Returns a legacy column as close to the underlying column as possible.
+    ///
+    /// * If the column is legacy, this does nothing and returns it.
+    /// * If the column is modern, it tries to convert it to the
+    ///   legacy format, and returns the result. Hashed labels are not supported
+    ///   by legacy tables, so this fails if the column's label is a hash.
+    pub fn try_into_legacy(self) -> BdatResult<LegacyColumn<'buf>> {
+        match self {
+            Self::Modern(m) => Ok(m.try_into()?),
+            Self::Legacy(l) => Ok(l),
+        }
+    }
+
     /// Returns the column's label. For legacy tables,
     /// this is wrapped into a [`Label::String`].
     pub fn label(&self) -> Label {
@@ -471,6 +604,30 @@ impl<'b> CompatRow<'b> {
             CompatRow::Legacy(l) => CompatIter::Legacy(l.into_cells()),
         }
     }
+
+    /// Returns an iterator over this row's values, taking ownership of the row.
+    ///
+    /// Values from modern rows are moved without cloning. Legacy cells are flattened: list
+    /// cells yield each of their values, and flag cells yield each flag's value as a
+    /// [`Value::UnsignedInt`]. For legacy rows, the number of values may then differ from
+    /// the column count.
+    pub fn into_values(self) -> impl Iterator<Item = Value<'b>> {
+        match self {
+            CompatRow::Modern(m) => CompatIter::Modern(m.into_values()),
+            CompatRow::Legacy(l) => CompatIter::Legacy(l.into_cells().flat_map(|cell| {
+                // Nested CompatIters act as a 3-way Either
+                match cell {
+                    Cell::Single(v) => CompatIter::Modern(std::iter::once(v)),
+                    Cell::List(values) => {
+                        CompatIter::Legacy(CompatIter::Modern(values.into_iter()))
+                    }
+                    Cell::Flags(flags) => CompatIter::Legacy(CompatIter::Legacy(
+                        flags.into_iter().map(Value::UnsignedInt),
+                    )),
+                }
+            })),
+        }
+    }
 }
 
 impl<'t, 'b> CompatRef<'t, 'b> {
@@ -508,6 +665,17 @@ impl<'t, 'buf> From<&'t ModernColumn<'buf>> for CompatColumnRef<'t, 'buf> {
     }
 }
 
+/// Formats a summary of the underlying table. See the [`Display`] implementations for
+/// [`ModernTable`] and [`LegacyTable`].
+impl<'b> Display for CompatTable<'b> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Modern(m) => m.fmt(f),
+            Self::Legacy(l) => l.fmt(f),
+        }
+    }
+}
+
 impl<'buf> Table<'buf> for CompatTable<'buf> {
     type Id = RowId;
     type Name = Label<'buf>;
@@ -515,6 +683,7 @@ impl<'buf> Table<'buf> for CompatTable<'buf> {
     type BuilderRow = Infallible; // uninstantiable
     type Column = CompatColumn<'buf>;
     type BuilderColumn = CompatColumn<'buf>;
+    type BuilderExtra = ();
 }
 
 impl<'t, 'b> CellAccessor for CompatRef<'t, 'b> {
@@ -2,7 +2,7 @@ use thiserror::Error;
 
 use crate::legacy::{LegacyColumn, LegacyRow, LegacyTable, LegacyTableBuilder};
 use crate::modern::{ModernColumn, ModernRow, ModernTable, ModernTableBuilder};
-use crate::{BdatVersion, Cell, LegacyVersion, RowId, ValueType};
+use crate::{BdatVersion, Cell, LegacyVersion, RowId, Utf, ValueType};
 
 use super::column::ColumnMap;
 
@@ -34,6 +34,48 @@ pub enum FormatConvertError {
     UnsupportedLabelType,
 }
 
+/// Lists the columns of a legacy table that can't be converted to the modern format.
+///
+/// Converting a table with any such column to a [`ModernTable`] fails, so the report can be
+/// used to inform users beforehand. See [`LegacyTable::modern_conversion_report`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConversionReport<'b> {
+    issues: Vec<(Utf<'b>, ConversionIssue)>,
+}
+
+/// The reason why a legacy column can't be converted to the modern format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionIssue {
+    /// The column has list cells, with the given number of values. Modern tables only support
+    /// single-value cells.
+    List(usize),
+    /// The column's value is split into the given number of flags. Modern tables don't
+    /// support flags.
+    Flags(usize),
+    /// The column's value type is not supported by modern tables.
+    UnsupportedType(ValueType),
+}
+
+impl<'b> ConversionReport<'b> {
+    pub(crate) fn new(issues: Vec<(Utf<'b>, ConversionIssue)>) -> Self {
+        Self { issues }
+    }
+
+    /// Returns whether the table can be converted without losing data.
+    pub fn is_lossless(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns an iterator over the problematic columns, as pairs of `(column label, issue)`.
+    ///
+    /// A column may appear more than once if it has multiple issues.
+    pub fn issues(&self) -> impl Iterator<Item = (&str, ConversionIssue)> {
+        self.issues
+            .iter()
+            .map(|(label, issue)| (label.as_ref(), *issue))
+    }
+}
+
 // Modern table -> Legacy table
 
 impl<'b> TryFrom<ModernColumn<'b>> for LegacyColumn<'b> {
@@ -85,14 +127,7 @@ impl<'b> TryFrom<ModernTable<'b>> for LegacyTable<'b> {
             .into_iter()
             .map(TryInto::try_into)
             .collect();
-        let row_len =
-            u16::try_from(rows.len()).map_err(|_| FormatConvertError::MaxRowCountExceeded)?;
-        if base_id.checked_add(row_len).is_none() {
-            // If there are enough rows to overflow from base_id, then we definitely have a row
-            // with ID u16::MAX
-            return Err(FormatConvertError::UnsupportedRowId(u16::MAX as u32));
-        }
-        Ok(LegacyTableBuilder::from_table(name, base_id, columns?, rows).build())
+        LegacyTableBuilder::from_table(name, base_id, columns?, rows).try_build()
     }
 }
 
@@ -1,12 +1,15 @@
 //! Legacy (XC1 up to DE) format types
 
-use crate::{compat::CompatTable, Cell, RowRef, Utf, ValueType};
+use std::fmt::Display;
+
+use crate::{compat::CompatTable, BdatVersion, Cell, RowRef, Utf, ValueType};
 
 use super::{
     builder::LegacyTableBuilder,
     column::ColumnMap,
+    convert::{ConversionIssue, ConversionReport},
     private::{CellAccessor, Column, ColumnSerialize, LabelMap, Table},
-    util::EnumId,
+    util::{EnumId, RowIdRange},
 };
 
 /// The BDAT table representation in legacy formats, used for all games before Xenoblade 3.
@@ -52,6 +55,7 @@ pub struct LegacyRow<'b> {
 
 /// A column definition from a legacy BDAT table
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LegacyColumn<'buf> {
     pub(crate) value_type: ValueType,
     pub(crate) label: Utf<'buf>,
@@ -92,6 +96,10 @@ impl<'b> LegacyTable<'b> {
         }
     }
 
+    fn row_ids(&self) -> RowIdRange {
+        RowIdRange::new(self.base_id, self.rows.len())
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -136,9 +144,9 @@ impl<'b> LegacyTable<'b> {
     /// from the index of the row in the table's row list. That is because
     /// BDAT tables can have arbitrary start IDs.
     pub fn get_row(&self, id: u16) -> Option<LegacyRowRef<'_, 'b>> {
-        let index = id.checked_sub(self.base_id)?;
+        let index = self.row_ids().index_of(id)?;
         self.rows
-            .get(index as usize)
+            .get(index)
             .map(|row| RowRef::new(id as u32, row, &self.columns))
     }
 
@@ -149,12 +157,28 @@ impl<'b> LegacyTable<'b> {
     /// from the index of the row in the table's row list. That is because
     /// BDAT tables can have arbitrary start IDs.
     pub fn get_row_mut(&mut self, id: u16) -> Option<LegacyRowMut<'_, 'b>> {
-        let index = id.checked_sub(self.base_id)?;
+        let index = self.row_ids().index_of(id)?;
         self.rows
-            .get_mut(index as usize)
+            .get_mut(index)
             .map(|row| RowRef::new(id as u32, row, &self.columns))
     }
 
+    /// Checks whether two rows have the same cells, except in the given columns.
+    ///
+    /// If either row doesn't exist, this returns `false`. Labels that are not in the
+    /// table are ignored.
+    pub fn rows_equal_ignoring(&self, a: u16, b: u16, ignore: &[&str]) -> bool {
+        let (Some(a), Some(b)) = (self.get_row(a), self.get_row(b)) else {
+            return false;
+        };
+        let ignored = self.columns().map(|c| ignore.contains(&c.label()));
+        a.cells
+            .iter()
+            .zip(&b.cells)
+            .zip(ignored)
+            .all(|((a, b), ignored)| ignored || a == b)
+    }
+
     /// Gets an iterator that visits this table's rows
     pub fn rows(&self) -> impl Iterator<Item = LegacyRowRef<'_, 'b>> {
         self.rows
@@ -183,7 +207,11 @@ impl<'b> LegacyTable<'b> {
     /// Gets an owning iterator over this table's rows, in pairs of
     /// `(row ID, row)`.
     pub fn into_rows_id(self) -> impl Iterator<Item = (u16, LegacyRow<'b>)> {
-        self.rows.into_iter().enum_id(self.base_id)
+        // IDs are counted as u32, as the last row's ID can be u16::MAX
+        self.rows
+            .into_iter()
+            .enum_id(self.base_id as u32)
+            .map(|(id, row)| (id as u16, row))
     }
 
     /// Gets an iterator that visits this table's column definitions
@@ -191,17 +219,55 @@ impl<'b> LegacyTable<'b> {
         self.columns.iter()
     }
 
+    /// Gets the column definition at the given index, if present.
+    ///
+    /// Lookups by label always find the first column with that label. This can be used to
+    /// reach other columns with the same label, along with [`RowRef::get_at`].
+    pub fn column_at(&self, index: usize) -> Option<&LegacyColumn<'b>> {
+        self.columns.as_slice().get(index)
+    }
+
     /// Gets an iterator over mutable references to this table's
     /// column definitions.
+    ///
+    /// **Note**: if column labels are changed, [`rebuild_column_index`] must be called
+    /// afterwards, otherwise lookups by label will use the old labels.
+    ///
+    /// [`rebuild_column_index`]: Self::rebuild_column_index
     pub fn columns_mut(&mut self) -> impl Iterator<Item = &mut LegacyColumn<'b>> {
         self.columns.as_mut_slice().iter_mut()
     }
 
+    /// Updates the index used to look up columns by label, after column labels were
+    /// changed through [`columns_mut`].
+    ///
+    /// [`columns_mut`]: Self::columns_mut
+    pub fn rebuild_column_index(&mut self) {
+        self.columns.rebuild();
+    }
+
     /// Gets an owning iterator over this table's column definitions
     pub fn into_columns(self) -> impl Iterator<Item = LegacyColumn<'b>> {
         self.columns.into_raw().into_iter()
     }
 
+    /// Wraps this table into a [`CompatTable`], same as `CompatTable::from`.
+    ///
+    /// The table can be extracted again using [`CompatTable::into_legacy`].
+    pub fn into_compat(self) -> CompatTable<'b> {
+        self.into()
+    }
+
+    /// Returns a list of this table's column labels, in column order.
+    pub fn column_labels(&self) -> Vec<Utf<'b>> {
+        self.columns.iter().map(|c| c.label.clone()).collect()
+    }
+
+    /// Returns a list of this table's column value types, in column order.
+    pub fn column_types(&self) -> Vec<ValueType> {
+        self.columns.iter().map(|c| c.value_type).collect()
+    }
+
     pub fn row_count(&self) -> usize {
         self.rows.len()
     }
@@ -209,6 +275,33 @@ impl<'b> LegacyTable<'b> {
     pub fn column_count(&self) -> usize {
         self.columns.as_slice().len()
     }
+
+    /// Checks which columns prevent the table from being converted to a [`ModernTable`].
+    ///
+    /// The table is not modified, and no conversion is attempted.
+    ///
+    /// [`ModernTable`]: crate::modern::ModernTable
+    pub fn modern_conversion_report(&self) -> ConversionReport<'b> {
+        let mut issues = Vec::new();
+        for column in self.columns.iter() {
+            if !column.value_type.is_supported(BdatVersion::Modern) {
+                issues.push((
+                    column.label.clone(),
+                    ConversionIssue::UnsupportedType(column.value_type),
+                ));
+            }
+            if column.count > 1 {
+                issues.push((column.label.clone(), ConversionIssue::List(column.count)));
+            }
+            if !column.flags.is_empty() {
+                issues.push((
+                    column.label.clone(),
+                    ConversionIssue::Flags(column.flags.len()),
+                ));
+            }
+        }
+        ConversionReport::new(issues)
+    }
 }
 
 impl<'b> LegacyRow<'b> {
@@ -308,6 +401,30 @@ impl<'tb> LegacyFlag<'tb> {
     pub fn shift_amount(&self) -> usize {
         self.flag_index
     }
+
+    /// Returns the indices of the bits covered by this flag's mask, in ascending order.
+    ///
+    /// Bits are numbered like in [`LegacyFlag::new_bit`].
+    ///
+    /// ```
+    /// use bdat::legacy::LegacyFlag;
+    ///
+    /// let flag = LegacyFlag::new("Flag", 0b1101, 0);
+    /// assert_eq!(vec![0, 2, 3], flag.bits().collect::<Vec<_>>());
+    /// assert!(flag.covers_bit(2));
+    /// assert!(!flag.covers_bit(1));
+    /// ```
+    pub fn bits(&self) -> impl Iterator<Item = u32> {
+        let mask = self.mask;
+        (0..u32::BITS).filter(move |bit| mask & (1 << bit) != 0)
+    }
+
+    /// Returns whether the bit at the given index is covered by this flag's mask.
+    ///
+    /// Bits are numbered like in [`LegacyFlag::new_bit`].
+    pub fn covers_bit(&self, bit: u32) -> bool {
+        bit < u32::BITS && self.mask & (1 << bit) != 0
+    }
 }
 
 impl<'tb> LegacyColumnBuilder<'tb> {
@@ -340,6 +457,7 @@ impl<'buf> Table<'buf> for LegacyTable<'buf> {
     type BuilderRow = LegacyRow<'buf>;
     type Column = LegacyColumn<'buf>;
     type BuilderColumn = LegacyColumn<'buf>;
+    type BuilderExtra = ();
 }
 
 impl<'a, 'b> CellAccessor for &'a LegacyRow<'b> {
@@ -370,6 +488,34 @@ impl<'b> From<LegacyTable<'b>> for CompatTable<'b> {
     }
 }
 
+/// Formats a one-line summary of the table (name, row and column counts, base ID).
+///
+/// The alternate form (`{:#}`) also lists the table's columns, one per line.
+impl<'b> Display for LegacyTable<'b> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (legacy): {} columns, {} rows, base ID {}",
+            self.name,
+            self.column_count(),
+            self.row_count(),
+            self.base_id
+        )?;
+        if f.alternate() {
+            for col in self.columns() {
+                write!(f, "\n  {}: {:?}", col.label, col.value_type)?;
+                if col.count > 1 {
+                    write!(f, "[{}]", col.count)?;
+                }
+                for flag in &col.flags {
+                    write!(f, "\n    {} (mask {:#x})", flag.label, flag.mask)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<'t, 'b> LabelMap for &'t ColumnMap<LegacyColumn<'b>, Utf<'b>> {
     type Name = Utf<'b>;
 
@@ -1,14 +1,22 @@
 //! Modern (XC3) format types
 
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+use thiserror::Error;
+
 use crate::compat::CompatTable;
 use crate::hash::PreHashedMap;
 use crate::legacy::LegacyFlag;
 use crate::modern::ModernTableBuilder;
-use crate::{Label, RowId, RowRef, Value, ValueType};
+use crate::{BdatVersion, Label, RowId, RowRef, Value, ValueType};
 
 use super::column::ColumnMap;
 use super::private::{CellAccessor, Column, ColumnSerialize, LabelMap, Table};
-use super::util::EnumId;
+use super::util::{EnumId, RowIdRange};
 
 /// The BDAT table representation in modern formats, currently used in Xenoblade 3.
 ///
@@ -60,6 +68,8 @@ pub struct ModernTable<'b> {
     pub(crate) base_id: u32,
     pub(crate) columns: ColumnMap<ModernColumn<'b>, Label<'b>>,
     pub(crate) rows: Vec<ModernRow<'b>>,
+    pub(crate) reserved: [u8; 4],
+    primary_key: Option<usize>,
     #[cfg(feature = "hash-table")]
     row_hash_table: PreHashedMap<u32, RowId>,
 }
@@ -75,6 +85,7 @@ pub struct ModernRow<'b> {
 
 /// A column definition from a modern BDAT table
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModernColumn<'buf> {
     pub(crate) value_type: ValueType,
     pub(crate) label: Label<'buf>,
@@ -86,15 +97,43 @@ pub type ModernRowRef<'t, 'buf> = RowRef<&'t ModernRow<'buf>, &'t ColumnMap<Mode
 pub type ModernRowMut<'t, 'buf> =
     RowRef<&'t mut ModernRow<'buf>, &'t ColumnMap<ModernColumn<'buf>>>;
 
+/// Error returned by [`ModernRowMut::set_as`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SetError {
+    /// There is no column with the given label.
+    #[error("no such column: {0}")]
+    MissingColumn(Label<'static>),
+    /// The value's type doesn't match the column's type.
+    #[error("type mismatch: column has type {expected:?}, but the value has type {actual:?}")]
+    TypeMismatch {
+        expected: ValueType,
+        actual: ValueType,
+    },
+}
+
 impl<'b> ModernTable<'b> {
     pub(crate) fn new(builder: ModernTableBuilder<'b>) -> Self {
+        let primary_key = builder.primary_key();
+        Self::with_primary_key(builder, primary_key)
+    }
+
+    pub(crate) fn with_primary_key(
+        builder: ModernTableBuilder<'b>,
+        primary_key: Option<usize>,
+    ) -> Self {
+        if let Some(column) = primary_key {
+            let ty = builder.columns.as_slice()[column].value_type;
+            assert_eq!(ValueType::HashRef, ty, "primary key must be a hash column");
+        }
         Self {
             name: builder.name,
             columns: builder.columns,
             base_id: builder.base_id,
             #[cfg(feature = "hash-table")]
-            row_hash_table: build_id_map_checked(&builder.rows, builder.base_id),
+            row_hash_table: build_id_map_checked(&builder.rows, builder.base_id, primary_key),
             rows: builder.rows,
+            reserved: builder.extra.reserved,
+            primary_key,
         }
     }
 
@@ -111,6 +150,58 @@ impl<'b> ModernTable<'b> {
         self.base_id
     }
 
+    /// Returns the index of the primary key column, whose values are used to find rows by
+    /// their hashed ID (see [`get_row_by_hash`]).
+    ///
+    /// By default, this is the first column of type [`ValueType::HashRef`]. For tables read
+    /// from a file, this is the column that was used to build the file's hash table.
+    ///
+    /// [`get_row_by_hash`]: ModernTable::get_row_by_hash
+    pub fn primary_key(&self) -> Option<usize> {
+        self.primary_key
+    }
+
+    /// Returns the primary key column along with its index. See [`primary_key`].
+    ///
+    /// [`primary_key`]: ModernTable::primary_key
+    pub fn primary_key_column(&self) -> Option<(usize, &ModernColumn<'b>)> {
+        let index = self.primary_key?;
+        Some((index, &self.columns.as_slice()[index]))
+    }
+
+    /// Sets the primary key column, by index. If `None`, rows can't be found by hash, and
+    /// the table is written without a hash table.
+    ///
+    /// When the `hash-table` feature is enabled, the hash index is rebuilt accordingly.
+    ///
+    /// ## Panics
+    /// Panics if the column does not exist or is not of type [`ValueType::HashRef`], or
+    /// if two rows have the same key.
+    pub fn set_primary_key(&mut self, column: Option<usize>) {
+        if let Some(column) = column {
+            let ty = self.columns.as_slice()[column].value_type;
+            assert_eq!(ValueType::HashRef, ty, "primary key must be a hash column");
+        }
+        self.primary_key = column;
+        #[cfg(feature = "hash-table")]
+        {
+            self.row_hash_table = build_id_map_checked(&self.rows, self.base_id, column);
+        }
+    }
+
+    /// Returns the raw bytes of the reserved header field at offset 0x14, in file order.
+    ///
+    /// This is zero in all known tables, but other values are preserved when the table is
+    /// written back.
+    pub fn reserved(&self) -> [u8; 4] {
+        self.reserved
+    }
+
+    /// Sets the raw bytes of the reserved header field. See [`reserved`](Self::reserved).
+    pub fn set_reserved(&mut self, reserved: [u8; 4]) {
+        self.reserved = reserved;
+    }
+
     /// Gets a row by its ID.
     ///
     /// Note: the ID is the row's numerical ID, which could be different
@@ -154,9 +245,9 @@ impl<'b> ModernTable<'b> {
     /// from the index of the row in the table's row list. That is because
     /// BDAT tables can have arbitrary start IDs.
     pub fn get_row(&self, id: RowId) -> Option<ModernRowRef<'_, 'b>> {
-        let index = id.checked_sub(self.base_id)?;
+        let index = self.row_ids().index_of(id)?;
         self.rows
-            .get(index as usize)
+            .get(index)
             .map(move |row| RowRef::new(id, row, &self.columns))
     }
 
@@ -167,9 +258,9 @@ impl<'b> ModernTable<'b> {
     /// from the index of the row in the table's row list. That is because
     /// BDAT tables can have arbitrary start IDs.
     pub fn get_row_mut(&mut self, id: RowId) -> Option<ModernRowMut<'_, 'b>> {
-        let index = id.checked_sub(self.base_id)?;
+        let index = self.row_ids().index_of(id)?;
         self.rows
-            .get_mut(index as usize)
+            .get_mut(index)
             .map(|row| RowRef::new(id, row, &self.columns))
     }
 
@@ -198,6 +289,39 @@ impl<'b> ModernTable<'b> {
             .expect("no row with given hash")
     }
 
+    /// Returns all rows whose value for the given column matches the predicate, in
+    /// row order.
+    ///
+    /// If the table has no such column, no rows are returned.
+    pub fn find_rows(
+        &self,
+        label: &Label,
+        pred: impl Fn(&Value) -> bool,
+    ) -> Vec<ModernRowRef<'_, 'b>> {
+        let Some(column) = self.columns.label_map.position(label) else {
+            return Vec::new();
+        };
+        self.rows()
+            .filter(|row| pred(&row.values[column]))
+            .collect()
+    }
+
+    /// Checks whether two rows have the same values, except in the given columns.
+    ///
+    /// If either row doesn't exist, this returns `false`. Labels that are not in the
+    /// table are ignored.
+    pub fn rows_equal_ignoring(&self, a: RowId, b: RowId, ignore: &[Label]) -> bool {
+        let (Some(a), Some(b)) = (self.get_row(a), self.get_row(b)) else {
+            return false;
+        };
+        let ignored = self.columns().map(|c| ignore.contains(&c.label));
+        a.values
+            .iter()
+            .zip(&b.values)
+            .zip(ignored)
+            .all(|((a, b), ignored)| ignored || a == b)
+    }
+
     /// Gets an iterator that visits this table's rows
     pub fn rows(&self) -> impl Iterator<Item = ModernRowRef<'_, 'b>> {
         self.rows
@@ -206,6 +330,19 @@ impl<'b> ModernTable<'b> {
             .map(|(id, row)| RowRef::new(id, row, &self.columns))
     }
 
+    /// Gets an iterator over the rows with IDs in the given range.
+    ///
+    /// The range is clamped to the table's rows, so IDs that are out of bounds are skipped.
+    /// Unlike `rows().skip(n).take(m)`, this starts directly at the first row in the range.
+    pub fn rows_in_range(&self, ids: Range<RowId>) -> impl Iterator<Item = ModernRowRef<'_, 'b>> {
+        let indices = self.row_ids().indices_of(ids);
+        let first_id = self.base_id + indices.start as RowId;
+        self.rows[indices]
+            .iter()
+            .enum_id(first_id)
+            .map(|(id, row)| RowRef::new(id, row, &self.columns))
+    }
+
     /// Gets an iterator over mutable references to this table's
     /// rows.
     ///
@@ -227,6 +364,26 @@ impl<'b> ModernTable<'b> {
             .map(|(id, row)| RowRef::new(id, row, &self.columns))
     }
 
+    /// Retains only the rows for which the predicate returns `true`, similarly to
+    /// [`Vec::retain`]. The predicate is called with each row's current ID.
+    ///
+    /// Row IDs are always contiguous, so the remaining rows are renumbered starting from the
+    /// table's base ID, which does not change. Rows are never left with holes in their IDs.
+    ///
+    /// When the `hash-table` feature is enabled, the hash index is rebuilt accordingly.
+    pub fn retain_rows(&mut self, mut f: impl FnMut(RowId, &ModernRow<'b>) -> bool) {
+        let mut id = self.base_id;
+        self.rows.retain(|row| {
+            let keep = f(id, row);
+            id += 1;
+            keep
+        });
+        #[cfg(feature = "hash-table")]
+        {
+            self.row_hash_table = build_id_map_checked(&self.rows, self.base_id, self.primary_key);
+        }
+    }
+
     /// Gets an owning iterator over this table's rows
     pub fn into_rows(self) -> impl Iterator<Item = ModernRow<'b>> {
         self.rows.into_iter()
@@ -243,24 +400,153 @@ impl<'b> ModernTable<'b> {
         self.columns.iter()
     }
 
+    /// Hashes the table name and all column labels, so the table can be written in a
+    /// consistent form.
+    ///
+    /// String labels are replaced with their hash, while already hashed labels are left
+    /// untouched. The original strings are not kept, so they need to be looked up from a
+    /// hash table to be restored.
+    pub fn hash_all_labels(&mut self) {
+        let name = std::mem::replace(&mut self.name, Label::Hash(0));
+        self.name = name.into_hash(BdatVersion::Modern);
+        self.columns = std::mem::take(&mut self.columns)
+            .into_iter()
+            .map(|mut col| {
+                col.label = col.label.into_hash(BdatVersion::Modern);
+                col
+            })
+            .collect();
+    }
+
     /// Gets an iterator over mutable references to this table's
     /// column definitions.
+    ///
+    /// **Note**: if column labels are changed, [`rebuild_column_index`] must be called
+    /// afterwards, otherwise lookups by label will use the old labels.
+    ///
+    /// [`rebuild_column_index`]: Self::rebuild_column_index
     pub fn columns_mut(&mut self) -> impl Iterator<Item = &mut ModernColumn<'b>> {
         self.columns.as_mut_slice().iter_mut()
     }
 
+    /// Updates the index used to look up columns by label, after column labels were
+    /// changed through [`columns_mut`].
+    ///
+    /// [`columns_mut`]: Self::columns_mut
+    pub fn rebuild_column_index(&mut self) {
+        self.columns.rebuild();
+    }
+
     /// Gets an owning iterator over this table's column definitions
     pub fn into_columns(self) -> impl Iterator<Item = ModernColumn<'b>> {
         self.columns.into_raw().into_iter()
     }
 
+    /// Wraps this table into a [`CompatTable`], same as `CompatTable::from`.
+    ///
+    /// The table can be extracted again using [`CompatTable::into_modern`].
+    pub fn into_compat(self) -> CompatTable<'b> {
+        self.into()
+    }
+
+    /// Returns a list of this table's column labels, in column order.
+    pub fn column_labels(&self) -> Vec<Label<'b>> {
+        self.columns.iter().map(|c| c.label.clone()).collect()
+    }
+
+    /// Returns a list of this table's column value types, in column order.
+    pub fn column_types(&self) -> Vec<ValueType> {
+        self.columns.iter().map(|c| c.value_type).collect()
+    }
+
     pub fn row_count(&self) -> usize {
         self.rows.len()
     }
 
+    /// Checks whether two tables hold the same data, regardless of how
+    /// it is laid out.
+    ///
+    /// Unlike the derived [`PartialEq`] implementation, which compares
+    /// columns and rows positionally, this function:
+    /// * matches columns by their label, so the column order doesn't matter.
+    ///   Columns with the same label must also have the same type.
+    /// * matches rows by their hashed ID (see [`ModernTable::primary_key`]), so the row order
+    ///   doesn't matter. Rows without a hashed ID are matched by their numerical ID instead.
+    ///
+    /// The table names must still be equal.
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        if self.name != other.name
+            || self.column_count() != other.column_count()
+            || self.row_count() != other.row_count()
+        {
+            return false;
+        }
+
+        // Position of each of our columns in the other table
+        let Some(positions) = self
+            .columns()
+            .map(|col| {
+                other
+                    .columns
+                    .label_map
+                    .position(&col.label)
+                    .filter(|&pos| other.columns.as_slice()[pos].value_type == col.value_type)
+            })
+            .collect::<Option<Vec<_>>>()
+        else {
+            return false;
+        };
+
+        self.rows().all(|row| {
+            let other_row = match self.key_of(&row) {
+                Some(hash) => other.find_row_by_hash(hash),
+                None => other.get_row(row.id()).map(|r| *r),
+            };
+            other_row.is_some_and(|other_row| {
+                row.values
+                    .iter()
+                    .zip(&positions)
+                    .all(|(value, &pos)| other_row.values.get(pos) == Some(value))
+            })
+        })
+    }
+
     pub fn column_count(&self) -> usize {
         self.columns.as_slice().len()
     }
+
+    /// Gets the column definition at the given index, if present.
+    ///
+    /// Lookups by label always find the first column with that label. This can be used to
+    /// reach other columns with the same label, along with [`RowRef::get_at`].
+    pub fn column_at(&self, index: usize) -> Option<&ModernColumn<'b>> {
+        self.columns.as_slice().get(index)
+    }
+}
+
+impl<'b> ModernTable<'b> {
+    fn row_ids(&self) -> RowIdRange {
+        RowIdRange::new(self.base_id, self.rows.len())
+    }
+
+    #[cfg(feature = "hash-table")]
+    fn find_row_by_hash(&self, hash_id: u32) -> Option<&ModernRow<'b>> {
+        self.row_hash_table
+            .get(&hash_id)
+            .and_then(|&id| self.rows.get(self.row_ids().index_of(id)?))
+    }
+
+    #[cfg(not(feature = "hash-table"))]
+    fn find_row_by_hash(&self, hash_id: u32) -> Option<&ModernRow<'b>> {
+        self.rows
+            .iter()
+            .find(|row| self.key_of(row) == Some(hash_id))
+    }
+
+    /// Returns the row's value for the primary key column, if any.
+    pub(crate) fn key_of(&self, row: &ModernRow) -> Option<u32> {
+        row_key(row, self.primary_key)
+    }
 }
 
 impl<'b> ModernRow<'b> {
@@ -280,12 +566,80 @@ impl<'b> ModernRow<'b> {
 
     /// Searches the row's cells for a ID hash field, returning the ID
     /// of this row if found.
+    ///
+    /// This returns the first hash value in the row, which might not belong to the table's
+    /// primary key column (see [`ModernTable::primary_key`]).
     pub fn id_hash(&self) -> Option<RowId> {
         self.values.iter().find_map(|value| match value {
             Value::HashRef(id) => Some(*id),
             _ => None,
         })
     }
+
+    /// Returns a hash of the row's values, including their types.
+    ///
+    /// Rows with equal values have the same hash, so this can be used to quickly skip
+    /// unchanged rows when comparing tables. Like [`CompatTable::schema_fingerprint`],
+    /// hashes are only stable within the same build of the crate.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for value in &self.values {
+            (ValueType::from(value) as u8).hash(&mut hasher);
+            match value {
+                Value::Unknown => {}
+                Value::UnsignedByte(v) | Value::Percent(v) | Value::Unknown12(v) => {
+                    v.hash(&mut hasher)
+                }
+                Value::UnsignedShort(v) | Value::MessageId(v) => v.hash(&mut hasher),
+                Value::UnsignedInt(v) | Value::HashRef(v) => v.hash(&mut hasher),
+                Value::SignedByte(v) => v.hash(&mut hasher),
+                Value::SignedShort(v) => v.hash(&mut hasher),
+                Value::SignedInt(v) => v.hash(&mut hasher),
+                Value::String(s) | Value::DebugString(s) => s.hash(&mut hasher),
+                Value::Float(f) => f32::from(*f).to_bits().hash(&mut hasher),
+            }
+        }
+        hasher.finish()
+    }
+}
+
+impl<'t, 'b> ModernRowMut<'t, 'b> {
+    /// Replaces the value of the cell at the given column.
+    ///
+    /// The value must have the same type as the column, e.g. a `u32` can only be used for
+    /// [`ValueType::UnsignedInt`] columns. The previous value is left unchanged if the column
+    /// doesn't exist or if the types don't match.
+    ///
+    /// **Note**: changing the row's ID hash (see [`ModernRow::id_hash`]) this way does not
+    /// update the table's hash lookup.
+    ///
+    /// ```
+    /// use bdat::modern::{ModernTable, SetError};
+    /// use bdat::label_hash;
+    ///
+    /// fn set_hp(table: &mut ModernTable) -> Result<(), SetError> {
+    ///     table.row_mut(1).set_as(label_hash!("HpMaxLv99"), 9999u32)
+    /// }
+    /// ```
+    pub fn set_as<V: Into<Value<'b>>>(
+        &mut self,
+        column: impl Into<Label<'b>>,
+        value: V,
+    ) -> Result<(), SetError> {
+        let label = column.into();
+        let index = self
+            .columns()
+            .position(&label)
+            .ok_or_else(|| SetError::MissingColumn(label.into_owned()))?;
+        let expected = self.columns().as_slice()[index].value_type;
+        let value = value.into();
+        let actual = ValueType::from(&value);
+        if expected != actual {
+            return Err(SetError::TypeMismatch { expected, actual });
+        }
+        self.values[index] = value;
+        Ok(())
+    }
 }
 
 impl<'tb> ModernColumn<'tb> {
@@ -305,25 +659,47 @@ impl<'tb> ModernColumn<'tb> {
         &self.label
     }
 
+    /// Compares two columns by their label.
+    ///
+    /// Hashed labels are ordered by hash, and come before all string labels, which
+    /// are ordered lexicographically.
+    pub fn cmp_by_label(&self, other: &Self) -> Ordering {
+        self.label.cmp(&other.label)
+    }
+
     /// Returns the total space occupied by a cell of this column.
     pub fn data_size(&self) -> usize {
         self.value_type.data_len()
     }
 }
 
+/// Returns the row's value for the given primary key column, if it is a hash.
+fn row_key(row: &ModernRow, primary_key: Option<usize>) -> Option<u32> {
+    match row.values.get(primary_key?)? {
+        Value::HashRef(hash) => Some(*hash),
+        _ => None,
+    }
+}
+
 /// Builds a primary key index for the table.
 ///
-/// If there is no hash-type column, the map will be empty.
+/// If there is no primary key column, the map will be empty.
 ///
 /// ## Panics
 /// Panics if there are two rows with the same key hash.
 #[cfg(feature = "hash-table")]
-fn build_id_map_checked(rows: &[ModernRow], base_id: u32) -> PreHashedMap<u32, RowId> {
+fn build_id_map_checked(
+    rows: &[ModernRow],
+    base_id: u32,
+    primary_key: Option<usize>,
+) -> PreHashedMap<u32, RowId> {
     use std::collections::hash_map::Entry;
 
     let mut res = PreHashedMap::with_capacity_and_hasher(rows.len(), Default::default());
     for (id, row) in rows.iter().enum_id(base_id) {
-        let Some(hash) = row.id_hash() else { continue };
+        let Some(hash) = row_key(row, primary_key) else {
+            continue;
+        };
         match res.entry(hash) {
             Entry::Occupied(_) => panic!(
                 "failed to build row hash table: duplicate key {:?}",
@@ -342,6 +718,15 @@ impl<'buf> Table<'buf> for ModernTable<'buf> {
     type BuilderRow = ModernRow<'buf>;
     type Column = ModernColumn<'buf>;
     type BuilderColumn = ModernColumn<'buf>;
+    type BuilderExtra = ModernBuilderExtra;
+}
+
+/// Modern table fields that are kept when converting a table into a builder.
+#[derive(Default)]
+pub struct ModernBuilderExtra {
+    /// The table's primary key. `None` if the builder should pick the first hash column.
+    pub(crate) primary_key: Option<Option<usize>>,
+    pub(crate) reserved: [u8; 4],
 }
 
 impl<'a, 'b> CellAccessor for &'a ModernRow<'b> {
@@ -360,9 +745,16 @@ impl<'a, 'b> CellAccessor for &'a mut ModernRow<'b> {
     }
 }
 
+/// The builder keeps the table's primary key (see [`ModernTable::primary_key`]) and reserved
+/// header field.
 impl<'b> From<ModernTable<'b>> for ModernTableBuilder<'b> {
     fn from(value: ModernTable<'b>) -> Self {
-        Self::from_table(value.name, value.base_id, value.columns, value.rows)
+        let mut builder = Self::from_table(value.name, value.base_id, value.columns, value.rows);
+        builder.extra = ModernBuilderExtra {
+            primary_key: Some(value.primary_key),
+            reserved: value.reserved,
+        };
+        builder
     }
 }
 
@@ -372,6 +764,31 @@ impl<'b> From<ModernTable<'b>> for CompatTable<'b> {
     }
 }
 
+/// Formats a one-line summary of the table (name, row and column counts, base ID).
+///
+/// The alternate form (`{:#}`) also lists the table's columns, one per line.
+impl<'b> Display for ModernTable<'b> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (modern): {} columns, {} rows, base ID {}",
+            self.name,
+            self.column_count(),
+            self.row_count(),
+            self.base_id
+        )?;
+        if f.alternate() {
+            for (i, col) in self.columns().enumerate() {
+                write!(f, "\n  {}: {:?}", col.label, col.value_type)?;
+                if self.primary_key == Some(i) {
+                    write!(f, " (primary key)")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<'t, 'b> LabelMap for &'t ColumnMap<ModernColumn<'b>, Label<'b>> {
     type Name = Label<'b>;
 
@@ -442,4 +859,411 @@ mod tests {
                 .get_as::<u32>()
         );
     }
+
+    #[test]
+    fn test_find_rows() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let table = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .add_column(ModernColumn::new(ValueType::UnsignedByte, 1.into()))
+            .extend_rows([1, 3, 2, 3].map(|v| ModernRow::new(vec![Value::UnsignedByte(v)])))
+            .build();
+        let ids = |rows: Vec<super::ModernRowRef>| rows.iter().map(|r| r.id()).collect::<Vec<_>>();
+        assert_eq!(
+            vec![2, 4],
+            ids(table.find_rows(&Label::Hash(1), |v| *v == Value::UnsignedByte(3)))
+        );
+        assert!(table.find_rows(&Label::Hash(2), |_| true).is_empty());
+    }
+
+    #[test]
+    fn test_rebuild_column_index() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let mut table = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .add_column(ModernColumn::new(ValueType::UnsignedByte, 1.into()))
+            .add_row(ModernRow::new(vec![Value::UnsignedByte(5)]))
+            .build();
+        for col in table.columns_mut() {
+            *col = ModernColumn::new(col.value_type(), 2.into());
+        }
+        assert_eq!(None, table.row(1).get_if_present(Label::Hash(2)));
+
+        table.rebuild_column_index();
+        assert_eq!(None, table.row(1).get_if_present(Label::Hash(1)));
+        assert_eq!(Value::UnsignedByte(5), *table.row(1).get(Label::Hash(2)));
+    }
+
+    #[test]
+    fn test_rows_in_range() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let table = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .set_base_id(10)
+            .add_column(ModernColumn::new(ValueType::UnsignedByte, 1.into()))
+            .extend_rows((0..5).map(|v| ModernRow::new(vec![Value::UnsignedByte(v)])))
+            .build();
+        let rows = |ids| {
+            table
+                .rows_in_range(ids)
+                .map(|r| (r.id(), r.get(Label::Hash(1)).get_as::<u8>()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![(11, 1), (12, 2)], rows(11..13));
+        assert_eq!(vec![(13, 3), (14, 4)], rows(13..100));
+        assert_eq!(5, rows(0..100).len());
+        assert!(rows(0..10).is_empty());
+        assert!(rows(15..20).is_empty());
+    }
+
+    #[test]
+    fn test_into_compat() {
+        use crate::modern::{ModernColumn, ModernTableBuilder};
+        use crate::{Label, ValueType};
+
+        let table = ModernTableBuilder::with_name(Label::Hash(1))
+            .add_column(ModernColumn::new(ValueType::UnsignedByte, 2.into()))
+            .build();
+        let compat = table.clone().into_compat();
+        assert!(compat.is_modern());
+        assert_eq!(table, compat.into_modern());
+    }
+
+    #[test]
+    fn test_rows_equal_ignoring() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let table =
+            ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+                .add_column(ModernColumn::new(ValueType::UnsignedByte, 1.into()))
+                .add_column(ModernColumn::new(ValueType::UnsignedInt, 2.into()))
+                .extend_rows([(1, 10), (1, 20), (2, 10)].map(|(a, b)| {
+                    ModernRow::new(vec![Value::UnsignedByte(a), Value::UnsignedInt(b)])
+                }))
+                .build();
+        assert!(!table.rows_equal_ignoring(1, 2, &[]));
+        assert!(table.rows_equal_ignoring(1, 2, &[Label::Hash(2)]));
+        assert!(!table.rows_equal_ignoring(1, 3, &[Label::Hash(2), Label::Hash(3)]));
+        assert!(table.rows_equal_ignoring(1, 3, &[Label::Hash(1)]));
+        assert!(!table.rows_equal_ignoring(1, 4, &[Label::Hash(1), Label::Hash(2)]));
+    }
+
+    #[test]
+    fn test_primary_key_column() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let builder = || {
+            ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+                .add_column(ModernColumn::new(ValueType::UnsignedInt, 0.into()))
+                .add_column(ModernColumn::new(ValueType::HashRef, 1.into()))
+                .add_column(ModernColumn::new(ValueType::HashRef, 2.into()))
+                .add_row(ModernRow::new(vec![
+                    Value::UnsignedInt(1),
+                    Value::HashRef(0xabcdef01),
+                    Value::HashRef(0x12345678),
+                ]))
+        };
+
+        let mut table = builder().build();
+        let (index, column) = table.primary_key_column().unwrap();
+        assert_eq!(1, index);
+        assert_eq!(&Label::Hash(1), column.label());
+
+        let table2 = builder().build_with_primary_key(Some(2));
+        assert_eq!(
+            &Label::Hash(2),
+            table2.primary_key_column().unwrap().1.label()
+        );
+
+        table.set_primary_key(None);
+        assert!(table.primary_key_column().is_none());
+    }
+
+    #[test]
+    fn test_display() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let table = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .add_column(ModernColumn::new(ValueType::HashRef, 0.into()))
+            .add_column(ModernColumn::new(ValueType::UnsignedInt, 1.into()))
+            .add_row(ModernRow::new(vec![
+                Value::HashRef(0xabcdef01),
+                Value::UnsignedInt(256),
+            ]))
+            .build();
+        assert_eq!(
+            "<DEADBEEF> (modern): 2 columns, 1 rows, base ID 1",
+            table.to_string()
+        );
+        assert_eq!(
+            "<DEADBEEF> (modern): 2 columns, 1 rows, base ID 1\n  \
+            <00000000>: HashRef (primary key)\n  <00000001>: UnsignedInt",
+            format!("{table:#}")
+        );
+    }
+
+    #[test]
+    fn test_sort_rows_by_hash() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let table = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .set_base_id(5)
+            .add_column(ModernColumn::new(ValueType::HashRef, 0.into()))
+            .add_column(ModernColumn::new(ValueType::UnsignedInt, 1.into()))
+            .add_row(ModernRow::new(vec![
+                Value::HashRef(0),
+                Value::UnsignedInt(1),
+            ]))
+            .add_row(ModernRow::new(vec![
+                Value::HashRef(0xdeadbeef),
+                Value::UnsignedInt(2),
+            ]))
+            .add_row(ModernRow::new(vec![
+                Value::HashRef(0xabcdef01),
+                Value::UnsignedInt(3),
+            ]))
+            .sort_rows_by_hash()
+            .build();
+        let values = table
+            .rows()
+            .map(|row| (row.id(), row.get(Label::Hash(1)).get_as::<u32>()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(5, 1), (6, 3), (7, 2)], values);
+    }
+
+    #[test]
+    fn test_sort_rows_by_primary_key() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let table = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .add_column(ModernColumn::new(ValueType::HashRef, 0.into()))
+            .add_column(ModernColumn::new(ValueType::HashRef, 1.into()))
+            .add_row(ModernRow::new(vec![Value::HashRef(1), Value::HashRef(20)]))
+            .add_row(ModernRow::new(vec![Value::HashRef(2), Value::HashRef(10)]))
+            .build_with_primary_key(Some(1));
+        let table = ModernTableBuilder::from(table).sort_rows_by_hash().build();
+        assert_eq!(Some(1), table.primary_key());
+        assert_eq!(1, table.row_by_hash(10).id());
+        assert_eq!(2, table.row_by_hash(20).id());
+    }
+
+    #[test]
+    fn test_retain_rows() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let mut table = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .set_base_id(3)
+            .add_column(ModernColumn::new(ValueType::HashRef, 0.into()))
+            .add_column(ModernColumn::new(ValueType::UnsignedInt, 1.into()))
+            .set_rows(
+                (1..=4)
+                    .map(|i| ModernRow::new(vec![Value::HashRef(i), Value::UnsignedInt(i * 10)]))
+                    .collect(),
+            )
+            .build();
+        table.retain_rows(|id, _| id != 3 && id != 5);
+
+        assert_eq!(3, table.base_id());
+        assert_eq!(2, table.row_count());
+        assert_eq!(20, table.row(3).get(Label::Hash(1)).get_as::<u32>());
+        assert_eq!(40, table.row(4).get(Label::Hash(1)).get_as::<u32>());
+        #[cfg(feature = "hash-table")]
+        {
+            assert!(table.get_row_by_hash(1).is_none());
+            assert_eq!(4, table.row_by_hash(4).id());
+        }
+    }
+
+    #[test]
+    fn test_hash_all_labels() {
+        use crate::hash::murmur3_str;
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let mut table = ModernTableBuilder::with_name(Label::String("Table".into()))
+            .add_column(ModernColumn::new(ValueType::UnsignedInt, "Name".into()))
+            .add_column(ModernColumn::new(ValueType::UnsignedInt, 0xDEADBEEF.into()))
+            .add_row(ModernRow::new(vec![
+                Value::UnsignedInt(1),
+                Value::UnsignedInt(2),
+            ]))
+            .build();
+        table.hash_all_labels();
+
+        assert_eq!(&Label::Hash(murmur3_str("Table")), table.name());
+        assert_eq!(
+            vec![Label::Hash(murmur3_str("Name")), Label::Hash(0xDEADBEEF)],
+            table.column_labels()
+        );
+        let row = table.row(1);
+        assert_eq!(1, row.get(Label::Hash(murmur3_str("Name"))).get_as::<u32>());
+    }
+
+    #[test]
+    fn test_semantically_eq() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let row_a = ModernRow::new(vec![Value::HashRef(0xabcdef01), Value::UnsignedInt(256)]);
+        let row_b = ModernRow::new(vec![Value::HashRef(0xdeadbeef), Value::UnsignedInt(100)]);
+        let id_col = ModernColumn::new(ValueType::HashRef, 0.into());
+        let value_col = ModernColumn::new(ValueType::UnsignedInt, 1.into());
+
+        let table = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .add_column(id_col.clone())
+            .add_column(value_col.clone())
+            .add_row(row_a.clone())
+            .add_row(row_b.clone())
+            .build();
+        let reordered = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .add_column(value_col.clone())
+            .add_column(id_col.clone())
+            .add_row(ModernRow::new(row_b.values.iter().rev().cloned().collect()))
+            .add_row(ModernRow::new(row_a.values.iter().rev().cloned().collect()))
+            .build();
+        let changed = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .add_column(id_col)
+            .add_column(value_col)
+            .add_row(row_b)
+            .add_row(ModernRow::new(vec![
+                Value::HashRef(0xabcdef01),
+                Value::UnsignedInt(255),
+            ]))
+            .build();
+
+        assert_ne!(table, reordered);
+        assert!(table.semantically_eq(&reordered));
+        assert!(reordered.semantically_eq(&table));
+        assert!(!table.semantically_eq(&changed));
+    }
+
+    #[test]
+    fn test_content_hash() {
+        use crate::modern::ModernRow;
+        use crate::Value;
+
+        let row = ModernRow::new(vec![Value::UnsignedInt(1), Value::String("a".into())]);
+        let same = ModernRow::new(vec![Value::UnsignedInt(1), Value::String("a".into())]);
+        let changed = ModernRow::new(vec![Value::UnsignedInt(2), Value::String("a".into())]);
+        let retyped = ModernRow::new(vec![Value::HashRef(1), Value::String("a".into())]);
+        assert_eq!(row.content_hash(), same.content_hash());
+        assert_ne!(row.content_hash(), changed.content_hash());
+        assert_ne!(row.content_hash(), retyped.content_hash());
+    }
+
+    #[test]
+    fn test_sort_columns_by_label() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let table = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .add_column(ModernColumn::new(ValueType::UnsignedInt, "b".into()))
+            .add_column(ModernColumn::new(ValueType::UnsignedByte, 2.into()))
+            .add_column(ModernColumn::new(ValueType::SignedInt, "a".into()))
+            .add_column(ModernColumn::new(ValueType::HashRef, 1.into()))
+            .add_row(ModernRow::new(vec![
+                Value::UnsignedInt(1),
+                Value::UnsignedByte(2),
+                Value::SignedInt(3),
+                Value::HashRef(4),
+            ]))
+            .sort_columns_by_label()
+            .build();
+        assert_eq!(
+            vec![
+                Label::Hash(1),
+                Label::Hash(2),
+                Label::from("a"),
+                Label::from("b")
+            ],
+            table.column_labels()
+        );
+        assert_eq!(
+            vec![
+                Value::HashRef(4),
+                Value::UnsignedByte(2),
+                Value::SignedInt(3),
+                Value::UnsignedInt(1)
+            ],
+            table.row(1).values().cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(4, table.row(1).get(Label::Hash(1)).get_as::<u32>());
+    }
+
+    #[test]
+    fn test_preserve_column_order() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+        use crate::{Label, Value, ValueType};
+
+        let original = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .add_column(ModernColumn::new(ValueType::UnsignedInt, 2.into()))
+            .add_column(ModernColumn::new(ValueType::UnsignedByte, 1.into()))
+            .build();
+        let table = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .add_column(ModernColumn::new(ValueType::SignedInt, 3.into()))
+            .add_column(ModernColumn::new(ValueType::UnsignedByte, 1.into()))
+            .add_column(ModernColumn::new(ValueType::UnsignedInt, 2.into()))
+            .add_row(ModernRow::new(vec![
+                Value::SignedInt(3),
+                Value::UnsignedByte(1),
+                Value::UnsignedInt(2),
+            ]))
+            .preserve_column_order(&original)
+            .build();
+        assert_eq!(
+            vec![Label::Hash(2), Label::Hash(1), Label::Hash(3)],
+            table.column_labels()
+        );
+        assert_eq!(
+            vec![
+                Value::UnsignedInt(2),
+                Value::UnsignedByte(1),
+                Value::SignedInt(3)
+            ],
+            table.row(1).values().cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_set_as() {
+        use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder, SetError};
+        use crate::{Label, Value, ValueType};
+
+        let mut table = ModernTableBuilder::with_name(Label::Hash(0xDEADBEEF))
+            .add_column(ModernColumn::new(ValueType::UnsignedInt, 1.into()))
+            .add_column(ModernColumn::new(ValueType::String, 2.into()))
+            .add_row(ModernRow::new(vec![
+                Value::UnsignedInt(1),
+                Value::String("a".into()),
+            ]))
+            .build();
+
+        let mut row = table.row_mut(1);
+        assert_eq!(Ok(()), row.set_as(Label::Hash(1), 10u32));
+        assert_eq!(Ok(()), row.set_as(Label::Hash(2), "b"));
+        assert_eq!(
+            Err(SetError::TypeMismatch {
+                expected: ValueType::UnsignedInt,
+                actual: ValueType::SignedInt
+            }),
+            row.set_as(Label::Hash(1), -1i32)
+        );
+        assert_eq!(
+            Err(SetError::MissingColumn(Label::Hash(3))),
+            row.set_as(Label::Hash(3), 0u8)
+        );
+        assert_eq!(
+            vec![Value::UnsignedInt(10), Value::String("b".into())],
+            table.row(1).values().cloned().collect::<Vec<_>>()
+        );
+    }
 }
@@ -10,6 +10,8 @@ pub trait Table<'buf> {
     type BuilderRow;
     type Column: Column;
     type BuilderColumn: Column;
+    /// Format-specific builder state, kept when a table is turned back into a builder.
+    type BuilderExtra: Default;
 }
 
 pub trait Column {
@@ -45,3 +47,13 @@ pub trait ColumnSerialize {
     fn ser_value_type(&self) -> ValueType;
     fn ser_flags(&self) -> &[LegacyFlag];
 }
+
+impl<C: ColumnSerialize> ColumnSerialize for &C {
+    fn ser_value_type(&self) -> ValueType {
+        (*self).ser_value_type()
+    }
+
+    fn ser_flags(&self) -> &[LegacyFlag<'_>] {
+        (*self).ser_flags()
+    }
+}
@@ -7,6 +7,10 @@ use super::private::{CellAccessor, LabelMap};
 /// Best-fit type for row IDs.
 /// In legacy BDATs, row identifiers are 16-bit.
 /// In modern BDATs, row IDs are 32-bit.
+///
+/// Rows are numbered contiguously, starting from the table's base ID, so the ID of a row
+/// is its index in the table plus the base ID. Lookups with IDs outside of a table's range
+/// (including IDs that don't fit in 16 bits, for legacy tables) return `None`.
 pub type RowId = u32;
 
 /// A reference to a row that also keeps information about the parent table.
@@ -65,9 +69,15 @@ where
         self.id
     }
 
+    pub(crate) fn columns(&self) -> &L {
+        &self.columns
+    }
+
     /// Returns a reference to the cell at the given column.
     ///
-    /// If there is no column with the given label, this returns [`None`].
+    /// If there is no column with the given label, this returns [`None`]. If several columns
+    /// have the same label, the first one is used. See [`get_at`](Self::get_at) to access
+    /// the others.
     pub fn get_if_present(self, column: impl Into<L::Name>) -> Option<R::Target> {
         let index = self.columns.position(&column.into())?;
         self.row.access(index)
@@ -80,6 +90,12 @@ where
     pub fn get(self, column: impl Into<L::Name>) -> R::Target {
         self.get_if_present(column).expect("no such column")
     }
+
+    /// Returns a reference to the cell at the given column index, or [`None`] if the index
+    /// is out of bounds.
+    pub fn get_at(self, index: usize) -> Option<R::Target> {
+        self.row.access(index)
+    }
 }
 
 impl<R, L> Deref for RowRef<R, L> {
@@ -1,10 +1,22 @@
-use std::ops::AddAssign;
+use std::ops::{AddAssign, Range};
+
+use crate::RowId;
 
 pub enum CompatIter<M, L> {
     Modern(M),
     Legacy(L),
 }
 
+/// The IDs of a table's rows, which are numbered contiguously starting from the base ID.
+///
+/// Converts between row IDs and indices in the table's row list. IDs outside of the range
+/// are rejected, rather than overflowing or panicking.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RowIdRange {
+    base_id: RowId,
+    len: usize,
+}
+
 pub struct RowIdIter<I, N> {
     iter: I,
     id: N,
@@ -32,6 +44,33 @@ where
     }
 }
 
+impl RowIdRange {
+    pub fn new(base_id: impl Into<RowId>, len: usize) -> Self {
+        Self {
+            base_id: base_id.into(),
+            len,
+        }
+    }
+
+    /// Returns the index of the row with the given ID, if it is in range.
+    pub fn index_of(&self, id: impl Into<RowId>) -> Option<usize> {
+        let index = usize::try_from(id.into().checked_sub(self.base_id)?).ok()?;
+        (index < self.len).then_some(index)
+    }
+
+    /// Returns the indices of the rows whose IDs are in the given range, clamped to the
+    /// existing rows.
+    pub fn indices_of(&self, ids: Range<RowId>) -> Range<usize> {
+        let index = |id: RowId| {
+            usize::try_from(id.saturating_sub(self.base_id))
+                .unwrap_or(usize::MAX)
+                .min(self.len)
+        };
+        let start = index(ids.start);
+        start..index(ids.end).max(start)
+    }
+}
+
 impl<M, L, I> Iterator for CompatIter<M, L>
 where
     M: Iterator<Item = I>,
@@ -58,3 +97,30 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::RowIdRange;
+
+    #[test]
+    fn row_id_range() {
+        let range = RowIdRange::new(5u16, 3);
+        assert_eq!(None, range.index_of(4u16));
+        assert_eq!(Some(0), range.index_of(5u16));
+        assert_eq!(Some(2), range.index_of(7u32));
+        assert_eq!(None, range.index_of(8u32));
+        assert_eq!(None, range.index_of(u32::MAX));
+
+        let range = RowIdRange::new(u32::MAX, 1);
+        assert_eq!(Some(0), range.index_of(u32::MAX));
+        assert_eq!(None, RowIdRange::new(0u32, 0).index_of(0u32));
+
+        let range = RowIdRange::new(5u16, 3);
+        assert_eq!(0..3, range.indices_of(0..100));
+        assert_eq!(1..2, range.indices_of(6..7));
+        assert_eq!(3..3, range.indices_of(8..10));
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 7..6;
+        assert_eq!(2..2, range.indices_of(reversed));
+    }
+}
@@ -0,0 +1,176 @@
+//! Test utilities for BDAT readers and writers (requires feature `testing`)
+//!
+//! ```
+//! use bdat::{LegacyVersion, SwitchEndian};
+//!
+//! fn check(file: &[u8]) {
+//!     bdat::testing::assert_round_trip::<SwitchEndian>(file, LegacyVersion::Switch.into());
+//! }
+//! ```
+
+use byteorder::ByteOrder;
+
+use crate::legacy::LegacyTable;
+use crate::modern::ModernTable;
+use crate::{BdatFile, BdatVersion};
+
+/// Reads a BDAT file, writes it back, then reads the result again, asserting that the
+/// tables are the same.
+///
+/// Modern tables are compared using [`ModernTable::semantically_eq`], while legacy tables
+/// must be exactly equal.
+///
+/// ## Panics
+/// Panics if the file can't be read or written, or if the tables differ. In that case,
+/// the message describes the first differing table, and the row and column if applicable.
+pub fn assert_round_trip<E: ByteOrder + 'static>(bytes: &[u8], version: BdatVersion) {
+    match version {
+        BdatVersion::Modern => {
+            let tables = crate::modern::from_bytes::<E>(bytes)
+                .and_then(|mut f| f.get_tables())
+                .expect("could not read original file");
+            let out = crate::modern::to_vec::<E>(&tables).expect("could not write file");
+            let new_tables = crate::modern::from_bytes::<E>(&out)
+                .and_then(|mut f| f.get_tables())
+                .expect("could not read written file");
+            assert_tables_eq(&tables, &new_tables, modern_diff);
+        }
+        BdatVersion::Legacy(version) => {
+            let tables = crate::legacy::from_bytes_copy::<E>(bytes, version)
+                .and_then(|mut f| f.get_tables())
+                .expect("could not read original file");
+            let out = crate::legacy::to_vec::<E>(&tables, version).expect("could not write file");
+            let new_tables = crate::legacy::from_bytes_copy::<E>(&out, version)
+                .and_then(|mut f| f.get_tables())
+                .expect("could not read written file");
+            assert_tables_eq(&tables, &new_tables, legacy_diff);
+        }
+    }
+}
+
+fn assert_tables_eq<T>(old: &[T], new: &[T], diff: impl Fn(&T, &T) -> Option<String>) {
+    assert_eq!(old.len(), new.len(), "table count mismatch");
+    for (i, (old, new)) in old.iter().zip(new).enumerate() {
+        if let Some(msg) = diff(old, new) {
+            panic!("table #{i}: {msg}");
+        }
+    }
+}
+
+/// Describes the first difference between two modern tables, if they are not
+/// semantically equal.
+fn modern_diff(old: &ModernTable, new: &ModernTable) -> Option<String> {
+    if old.semantically_eq(new) {
+        return None;
+    }
+    let name = old.name();
+    if name != new.name() {
+        return Some(format!("name mismatch ({name} / {})", new.name()));
+    }
+    for col in old.columns() {
+        match new.columns().find(|c| c.label() == col.label()) {
+            Some(c) if c.value_type() == col.value_type() => {}
+            Some(c) => {
+                return Some(format!(
+                    "{name}, column {}: type mismatch ({:?} / {:?})",
+                    col.label(),
+                    col.value_type(),
+                    c.value_type()
+                ))
+            }
+            None => return Some(format!("{name}, column {} is missing", col.label())),
+        }
+    }
+    for row in old.rows() {
+        let new_row = match old.key_of(&row) {
+            Some(hash) => new.get_row_by_hash(hash),
+            None => new.get_row(row.id()),
+        };
+        let Some(new_row) = new_row else {
+            return Some(format!("{name}, row {} is missing", row.id()));
+        };
+        for (col, value) in old.columns().zip(row.values()) {
+            let new_value = new_row.get_if_present(col.label().clone());
+            if new_value != Some(value) {
+                return Some(format!(
+                    "{name}, row {}, column {}: value mismatch ({value:?} / {new_value:?})",
+                    row.id(),
+                    col.label()
+                ));
+            }
+        }
+    }
+    Some(format!(
+        "{name}: column or row count mismatch ({}x{} / {}x{})",
+        old.column_count(),
+        old.row_count(),
+        new.column_count(),
+        new.row_count()
+    ))
+}
+
+/// Describes the first difference between two legacy tables, if they are not equal.
+fn legacy_diff(old: &LegacyTable, new: &LegacyTable) -> Option<String> {
+    if old == new {
+        return None;
+    }
+    let name = old.name();
+    if name != new.name() {
+        return Some(format!("name mismatch ({name} / {})", new.name()));
+    }
+    for (i, (col, new_col)) in old.columns().zip(new.columns()).enumerate() {
+        if col != new_col {
+            return Some(format!(
+                "{name}, column #{i} ({}): definition mismatch ({col:?} / {new_col:?})",
+                col.label()
+            ));
+        }
+    }
+    for (row, new_row) in old.rows().zip(new.rows()) {
+        if row.id() != new_row.id() {
+            return Some(format!(
+                "{name}: row ID mismatch ({} / {})",
+                row.id(),
+                new_row.id()
+            ));
+        }
+        for ((col, cell), new_cell) in old.columns().zip(row.cells()).zip(new_row.cells()) {
+            if cell != new_cell {
+                return Some(format!(
+                    "{name}, row {}, column {}: value mismatch ({cell:?} / {new_cell:?})",
+                    row.id(),
+                    col.label()
+                ));
+            }
+        }
+    }
+    Some(format!(
+        "{name}: table mismatch (column count {} / {}, row count {} / {})",
+        old.column_count(),
+        new.column_count(),
+        old.row_count(),
+        new.row_count()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+    use crate::{Label, Value, ValueType};
+
+    #[test]
+    fn modern_diff_message() {
+        let table = |value| {
+            ModernTableBuilder::with_name(Label::Hash(1))
+                .add_column(ModernColumn::new(ValueType::UnsignedInt, Label::Hash(2)))
+                .add_row(ModernRow::new(vec![Value::UnsignedInt(value)]))
+                .build()
+        };
+        assert_eq!(super::modern_diff(&table(1), &table(1)), None);
+        assert_eq!(
+            super::modern_diff(&table(1), &table(2)).unwrap(),
+            "<00000001>, row 1, column <00000002>: value mismatch \
+            (UnsignedInt(1) / Some(UnsignedInt(2)))"
+        );
+    }
+}
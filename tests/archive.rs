@@ -0,0 +1,78 @@
+#![cfg(feature = "archive")]
+
+use bdat::{label_hash, BdatFile, BdatVersion, Label, LegacyVersion};
+
+static LEGACY_FILE: &[u8] = include_bytes!("res/test_legacy_1.bdat");
+static LEGACY_WII_FILE: &[u8] = include_bytes!("res/test_legacy_wii_1.bdat");
+static MODERN_FILE: &[u8] = include_bytes!("res/test_modern_1.bdat");
+
+fn build_archive() -> (Vec<u8>, Vec<usize>) {
+    let mut archive = vec![0xAB; 36];
+    let mut offsets = vec![];
+    for file in [LEGACY_FILE, MODERN_FILE, LEGACY_WII_FILE] {
+        offsets.push(archive.len());
+        archive.extend_from_slice(file);
+        archive.resize(archive.len().next_multiple_of(4) + 8, 0xCD);
+    }
+    (archive, offsets)
+}
+
+#[test]
+fn find_files() {
+    let (archive, offsets) = build_archive();
+    let files = bdat::archive::find_files(&archive);
+
+    assert_eq!(
+        offsets,
+        files.iter().map(|f| f.offset()).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        [
+            LegacyVersion::Switch.into(),
+            BdatVersion::Modern,
+            LegacyVersion::Wii.into()
+        ],
+        files
+            .iter()
+            .map(|f| f.version())
+            .collect::<Vec<_>>()
+            .as_slice()
+    );
+
+    let modern = bdat::modern::from_bytes::<bdat::SwitchEndian>(files[1].data())
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    assert_eq!(&label_hash!("Table1"), modern[0].name());
+
+    let mut data = files[0].data().to_vec();
+    let legacy = bdat::from_bytes(&mut data).unwrap().get_tables().unwrap();
+    assert_eq!(1, legacy.len());
+}
+
+#[test]
+fn find_file_with_table() {
+    let (archive, offsets) = build_archive();
+
+    let file = bdat::archive::find_file_with_table(&archive, &Label::from("Table1")).unwrap();
+    assert_eq!(offsets[0], file.offset());
+
+    // Legacy table names are not hashed
+    let file = bdat::archive::find_file_with_table(&archive, &label_hash!("Table1")).unwrap();
+    assert_eq!(offsets[1], file.offset());
+
+    assert!(bdat::archive::find_file_with_table(&archive, &Label::from("Missing")).is_none());
+}
+
+#[test]
+fn find_file_with_table_skips_invalid() {
+    // Looks like a modern file header, but the table offset is out of bounds
+    let mut archive = b"BDAT".to_vec();
+    for n in [0x01_00_10_04u32, 1, 20, 0xFFFF] {
+        archive.extend_from_slice(&n.to_le_bytes());
+    }
+    archive.extend_from_slice(MODERN_FILE);
+
+    let file = bdat::archive::find_file_with_table(&archive, &label_hash!("Table1")).unwrap();
+    assert_eq!(20, file.offset());
+}
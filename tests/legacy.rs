@@ -1,5 +1,8 @@
-use bdat::legacy::LegacyWriteOptions;
-use bdat::{BdatFile, Cell, Label, LegacyVersion, SwitchEndian, Value};
+use std::io::Cursor;
+
+use bdat::compat::CompatTable;
+use bdat::legacy::{FileHeader, LegacyReadOptions, LegacyTable, LegacyWriteOptions, TableHeader};
+use bdat::{BdatError, BdatFile, Cell, Label, LegacyVersion, SwitchEndian, Value};
 
 type FileEndian = SwitchEndian;
 
@@ -15,6 +18,15 @@ fn version_detect() {
     );
 }
 
+#[test]
+fn detected_version() {
+    let mut data = TEST_FILE_1.to_vec();
+    assert_eq!(
+        bdat::from_bytes(&mut data).unwrap().version(),
+        LegacyVersion::Switch.into(),
+    );
+}
+
 #[test]
 fn basic_read() {
     let tables = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
@@ -114,6 +126,53 @@ fn write_back() {
     assert_eq!(tables, new_tables);
 }
 
+#[test]
+fn checksum_verify() {
+    let tables = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    let out = bdat::legacy::to_vec_options::<FileEndian>(
+        &tables,
+        LegacyVersion::Switch,
+        LegacyWriteOptions::new().scramble(true),
+    )
+    .unwrap();
+    let opts = LegacyReadOptions::new().verify_checksum(true);
+
+    let new_tables =
+        bdat::legacy::from_bytes_copy_options::<FileEndian>(&out, LegacyVersion::Switch, opts)
+            .unwrap()
+            .get_tables()
+            .unwrap();
+    assert_eq!(tables, new_tables);
+
+    let mut corrupted = out.clone();
+    *corrupted.last_mut().unwrap() ^= 1;
+
+    // Lenient by default
+    bdat::legacy::from_bytes_copy::<FileEndian>(&corrupted, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+
+    let err = bdat::legacy::from_bytes_copy_options::<FileEndian>(
+        &corrupted,
+        LegacyVersion::Switch,
+        opts,
+    )
+    .unwrap()
+    .get_tables()
+    .unwrap_err();
+    assert!(matches!(err, BdatError::ChecksumMismatch { ref table, .. } if table == "Table1"));
+
+    let err =
+        bdat::legacy::from_bytes_options::<FileEndian>(&mut corrupted, LegacyVersion::Switch, opts)
+            .err()
+            .unwrap();
+    assert!(matches!(err, BdatError::ChecksumMismatch { .. }));
+}
+
 #[test]
 fn duplicate_columns() {
     let tables = [common::duplicate_table_create()];
@@ -143,3 +202,834 @@ fn table_map() {
     // Lifetime test
     assert_ne!(0, table.column_count());
 }
+
+#[test]
+fn into_table_map() {
+    let table = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    let mut bytes =
+        bdat::legacy::to_vec::<FileEndian>([&table, &table], LegacyVersion::Switch).unwrap();
+    let err = bdat::from_bytes(&mut bytes)
+        .unwrap()
+        .into_table_map()
+        .err()
+        .unwrap();
+    assert!(matches!(err, BdatError::DuplicateTable(name) if name == Label::from("Table1")));
+
+    let mut bytes = bdat::legacy::to_vec::<FileEndian>([&table], LegacyVersion::Switch).unwrap();
+    let tables = bdat::from_bytes(&mut bytes)
+        .unwrap()
+        .into_table_map()
+        .unwrap();
+    assert_eq!(table, *tables[&Label::from("Table1")].as_legacy());
+}
+
+#[test]
+fn table_order() {
+    let mut table_b = common::duplicate_table_create();
+    table_b.set_name("B".into());
+    let mut table_a = common::duplicate_table_create();
+    table_a.set_name("A".into());
+    let tables = [table_b, table_a];
+
+    let names = |opts: LegacyWriteOptions| {
+        let mut bytes =
+            bdat::legacy::to_vec_options::<FileEndian>(&tables, LegacyVersion::Switch, opts)
+                .unwrap();
+        bdat::legacy::from_bytes::<FileEndian>(&mut bytes, LegacyVersion::Switch)
+            .unwrap()
+            .get_tables()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(["A", "B"], names(LegacyWriteOptions::new()).as_slice());
+    assert_eq!(
+        ["B", "A"],
+        names(LegacyWriteOptions::new().preserve_order(true)).as_slice()
+    );
+}
+
+#[test]
+fn malformed_table_length() {
+    let mut data = TEST_FILE_1.to_vec();
+    let table_offset = u32::from_le_bytes(data[8..12].try_into().unwrap()) as usize;
+    // Claim a huge string table
+    data[table_offset + 0x1c..table_offset + 0x20].copy_from_slice(&0x7fff_ffffu32.to_le_bytes());
+
+    let err = bdat::legacy::from_bytes_copy::<FileEndian>(&data, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap_err();
+    assert!(matches!(err, BdatError::MalformedBdat(_)));
+
+    let err = bdat::legacy::from_bytes::<FileEndian>(&mut data, LegacyVersion::Switch)
+        .err()
+        .unwrap();
+    assert!(matches!(err, BdatError::MalformedBdat(_)));
+}
+
+#[test]
+fn endianness_mismatch() {
+    let mut data = TEST_FILE_1.to_vec();
+    let table_offset = u32::from_le_bytes(data[8..12].try_into().unwrap()) as usize;
+    // Swap the byte order of the constant (always 2)
+    data[table_offset + 0x14..table_offset + 0x16].copy_from_slice(&2u16.to_be_bytes());
+
+    let err = bdat::legacy::from_bytes_copy::<FileEndian>(&data, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        BdatError::EndiannessMismatch {
+            table_index: Some(0)
+        }
+    ));
+
+    let err = bdat::legacy::from_bytes::<FileEndian>(&mut data, LegacyVersion::Switch)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        BdatError::EndiannessMismatch {
+            table_index: Some(0)
+        }
+    ));
+}
+
+#[test]
+fn column_labels() {
+    let table = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    let labels = table.column_labels();
+    assert_eq!(table.column_count(), labels.len());
+    assert!(labels.iter().any(|l| l == "value_flags"));
+    let types = table.column_types();
+    assert_eq!(
+        table.columns().map(|c| c.value_type()).collect::<Vec<_>>(),
+        types
+    );
+
+    let compat: CompatTable = table.into();
+    let compat_labels = compat.column_labels();
+    assert_eq!(
+        labels.into_iter().map(Label::String).collect::<Vec<_>>(),
+        compat_labels
+    );
+}
+
+#[test]
+fn read_in_place() {
+    // The file size in this file's header doesn't include the file header itself
+    let mut data = TEST_FILE_1.to_vec();
+    let tables = bdat::legacy::from_bytes::<FileEndian>(&mut data, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    let tables_copy =
+        bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
+            .unwrap()
+            .get_tables()
+            .unwrap();
+    assert_eq!(tables_copy, tables);
+}
+
+#[test]
+fn table_byte_range() {
+    let mut tables =
+        bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
+            .unwrap()
+            .get_tables()
+            .unwrap();
+    tables.push(common::duplicate_table_create());
+    let bytes = bdat::legacy::to_vec::<FileEndian>(&tables, LegacyVersion::Switch).unwrap();
+
+    let file = bdat::legacy::from_bytes_copy::<FileEndian>(&bytes, LegacyVersion::Switch).unwrap();
+    let reader =
+        bdat::legacy::from_reader::<_, FileEndian>(Cursor::new(&bytes), LegacyVersion::Switch)
+            .unwrap();
+    assert_eq!(None, file.table_byte_range(2));
+    assert_eq!(file.table_byte_range(1), reader.table_byte_range(1));
+    let range = file.table_byte_range(1).unwrap();
+    assert_eq!(bytes.len(), range.end);
+
+    // Build a file with only that table: table count, file size, offset
+    let mut single = Vec::new();
+    single.extend_from_slice(&1u32.to_le_bytes());
+    single.extend_from_slice(&((12 + range.len()) as u32).to_le_bytes());
+    single.extend_from_slice(&12u32.to_le_bytes());
+    single.extend_from_slice(&bytes[range]);
+    let table = bdat::legacy::from_bytes::<FileEndian>(&mut single, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    assert_eq!(tables[1], table);
+}
+
+#[test]
+fn base_id_round_trip() {
+    use bdat::legacy::LegacyTableBuilder;
+
+    let table = LegacyTableBuilder::from(common::duplicate_table_create())
+        .set_base_id(1000)
+        .build();
+
+    let mut bytes = bdat::legacy::to_vec::<FileEndian>([&table], LegacyVersion::Switch).unwrap();
+    let new_table = bdat::legacy::from_bytes::<FileEndian>(&mut bytes, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    assert_eq!(1000, new_table.base_id());
+    assert_eq!(1000, new_table.rows().next().unwrap().id());
+    assert_eq!(table, new_table);
+}
+
+#[test]
+fn base_id_checked() {
+    use bdat::legacy::{LegacyRow, LegacyTableBuilder};
+    use bdat::FormatConvertError;
+
+    // Base ID 0 must be kept, not replaced with the default of 1
+    let table = LegacyTableBuilder::from(common::duplicate_table_create())
+        .set_base_id(0)
+        .build();
+    let bytes = bdat::legacy::to_vec::<FileEndian>([&table], LegacyVersion::Switch).unwrap();
+    let new_table = bdat::legacy::from_bytes_copy::<FileEndian>(&bytes, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    assert_eq!(0, new_table.base_id());
+    assert_eq!(
+        vec![0, 1],
+        new_table.rows().map(|r| r.id()).collect::<Vec<_>>()
+    );
+
+    // The last row can have ID u16::MAX
+    let table = LegacyTableBuilder::from(common::duplicate_table_create())
+        .set_base_id(u16::MAX - 1)
+        .build();
+    let bytes = bdat::legacy::to_vec::<FileEndian>([&table], LegacyVersion::Switch).unwrap();
+    let new_table = bdat::legacy::from_bytes_copy::<FileEndian>(&bytes, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    assert_eq!(
+        vec![u16::MAX - 1, u16::MAX],
+        new_table
+            .into_rows_id()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>()
+    );
+
+    let builder = LegacyTableBuilder::from(common::duplicate_table_create());
+    assert!(matches!(
+        builder.set_base_id(u16::MAX).try_build(),
+        Err(FormatConvertError::UnsupportedRowId(0x10000))
+    ));
+    let builder = LegacyTableBuilder::with_name("Test")
+        .set_rows(vec![LegacyRow::new(vec![]); u16::MAX as usize + 1]);
+    assert!(matches!(
+        builder.try_build(),
+        Err(FormatConvertError::MaxRowCountExceeded)
+    ));
+}
+
+#[test]
+fn iter_cells_flat() {
+    let table: CompatTable = common::duplicate_table_create().into();
+    let cells = table.iter_cells_flat().collect::<Vec<_>>();
+    assert_eq!(table.row_count() * table.column_count(), cells.len());
+    assert_eq!(
+        (1, Label::String("Label1".into()), Cell::Flags(vec![1])),
+        cells[0]
+    );
+    assert_eq!(
+        (
+            2,
+            Label::String("Label2".into()),
+            Cell::Single(Value::SignedByte(-4))
+        ),
+        cells[5]
+    );
+}
+
+#[test]
+fn row_get_if_present() {
+    let mut table = common::duplicate_table_create();
+    let row = table.row(2);
+    assert_eq!(
+        Some(&Cell::Single(Value::SignedByte(-4))),
+        row.get_if_present("Label2")
+    );
+    assert_eq!(None, row.get_if_present("Missing"));
+    assert!(table.row_mut(2).get_if_present("Missing").is_none());
+
+    let compat: CompatTable = table.into();
+    let row = compat.row(2);
+    assert_eq!(
+        Some(Cell::Single(Value::SignedByte(-4))),
+        row.get_if_present(Label::String("Label2".into()))
+    );
+    assert_eq!(None, row.get_if_present(Label::String("Missing".into())));
+}
+
+#[test]
+fn table_summary() {
+    let table = common::duplicate_table_create();
+    assert_eq!(
+        "Test (legacy): 3 columns, 2 rows, base ID 1",
+        table.to_string()
+    );
+    assert_eq!(
+        "Test (legacy): 3 columns, 2 rows, base ID 1\n  \
+        Label1: SignedInt\n    Flag1 (mask 0x1)\n  \
+        Label1: SignedInt\n    Flag1 (mask 0x1)\n  \
+        Label2: SignedByte",
+        format!("{table:#}")
+    );
+    assert_eq!(table.to_string(), CompatTable::from(table).to_string());
+}
+
+#[test]
+fn transcode_endianness() {
+    use bdat::WiiEndian;
+
+    let tables = [common::duplicate_table_create()];
+    let original = bdat::legacy::to_vec::<SwitchEndian>(&tables, LegacyVersion::Switch).unwrap();
+
+    let mut big_endian = bdat::legacy::transcode_endianness::<SwitchEndian, WiiEndian>(
+        &mut original.clone(),
+        LegacyVersion::Switch,
+    )
+    .unwrap();
+    assert_ne!(original, big_endian);
+    let read = bdat::legacy::from_bytes_copy::<WiiEndian>(&big_endian, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    assert_eq!(&tables[..], &read);
+
+    let back = bdat::legacy::transcode_endianness::<WiiEndian, SwitchEndian>(
+        &mut big_endian,
+        LegacyVersion::Switch,
+    )
+    .unwrap();
+    assert_eq!(original, back);
+}
+
+#[test]
+fn compat_row_into_values() {
+    let table: CompatTable = common::duplicate_table_create().into();
+    let values = table
+        .into_rows()
+        .next()
+        .unwrap()
+        .into_values()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            Value::UnsignedInt(1),
+            Value::UnsignedInt(1),
+            Value::SignedByte(2)
+        ],
+        values
+    );
+}
+
+#[test]
+fn get_table_by_index() {
+    use bdat::legacy::LegacyTableBuilder;
+
+    let tables = [
+        common::duplicate_table_create(),
+        LegacyTableBuilder::with_name("Second").build(),
+    ];
+    let bytes = bdat::legacy::to_vec::<FileEndian>(&tables, LegacyVersion::Switch).unwrap();
+    // Tables may be reordered when writing
+    let expected = bdat::legacy::from_bytes_copy::<FileEndian>(&bytes, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+
+    let mut reader =
+        bdat::legacy::from_reader::<_, FileEndian>(Cursor::new(&bytes), LegacyVersion::Switch)
+            .unwrap();
+    assert_eq!(Some(&expected[1]), reader.get_table(1).unwrap().as_ref());
+    assert_eq!(Some(&expected[0]), reader.get_table(0).unwrap().as_ref());
+    assert!(reader.get_table(2).unwrap().is_none());
+
+    let file = bdat::legacy::from_bytes_copy::<FileEndian>(&bytes, LegacyVersion::Switch).unwrap();
+    assert_eq!(Some(&expected[1]), file.get_table(1).unwrap().as_ref());
+    assert!(file.get_table(2).unwrap().is_none());
+}
+
+#[test]
+fn modern_conversion_report() {
+    use bdat::legacy::{ConversionIssue, LegacyColumnBuilder, LegacyTableBuilder};
+    use bdat::ValueType;
+
+    let table = common::duplicate_table_create();
+    let report = table.modern_conversion_report();
+    assert!(!report.is_lossless());
+    assert_eq!(
+        vec![
+            ("Label1", ConversionIssue::Flags(1)),
+            ("Label1", ConversionIssue::Flags(1))
+        ],
+        report.issues().collect::<Vec<_>>()
+    );
+
+    let table = LegacyTableBuilder::with_name("Table")
+        .add_column(LegacyColumnBuilder::new(ValueType::SignedByte, "Value".into()).build())
+        .add_column(
+            LegacyColumnBuilder::new(ValueType::UnsignedShort, "List".into())
+                .set_count(3)
+                .build(),
+        )
+        .build();
+    let report = table.modern_conversion_report();
+    assert_eq!(
+        vec![("List", ConversionIssue::List(3))],
+        report.issues().collect::<Vec<_>>()
+    );
+}
+
+#[cfg(feature = "encoding")]
+#[test]
+fn shift_jis_strings() {
+    use bdat::legacy::{LegacyColumnBuilder, LegacyEncoding, LegacyRow, LegacyTableBuilder};
+    use bdat::ValueType;
+
+    let table = LegacyTableBuilder::with_name("Table1")
+        .add_column(LegacyColumnBuilder::new(
+            ValueType::String,
+            "Name".to_string().into(),
+        ))
+        .add_row(LegacyRow::new(vec![Cell::Single(Value::String(
+            "シュルク".into(),
+        ))]))
+        .build();
+    let mut bytes = bdat::legacy::to_vec_options::<FileEndian>(
+        [&table],
+        LegacyVersion::Switch,
+        LegacyWriteOptions::new().encoding(LegacyEncoding::ShiftJis),
+    )
+    .unwrap();
+    // Katakana are 2 bytes in Shift-JIS
+    let sjis = [0x83, 0x56, 0x83, 0x85, 0x83, 0x8b, 0x83, 0x4e];
+    assert!(bytes.windows(sjis.len()).any(|w| w == sjis));
+
+    let err = bdat::legacy::from_bytes_copy::<FileEndian>(&bytes, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        BdatError::InvalidString { table: Some(ref name), .. } if name == "Table1"
+    ));
+
+    let new_table = bdat::legacy::from_bytes_options::<FileEndian>(
+        &mut bytes,
+        LegacyVersion::Switch,
+        LegacyReadOptions::new().encoding(LegacyEncoding::ShiftJis),
+    )
+    .unwrap()
+    .get_tables()
+    .unwrap()
+    .remove(0);
+    assert_eq!(table, new_table);
+}
+
+#[test]
+fn modern_only_types_unsupported() {
+    use bdat::legacy::{LegacyColumnBuilder, LegacyRow, LegacyTableBuilder};
+    use bdat::ValueType;
+
+    for (ty, value) in [
+        (ValueType::Percent, Value::Percent(50)),
+        (ValueType::DebugString, Value::DebugString("debug".into())),
+    ] {
+        let table = LegacyTableBuilder::with_name("Table1")
+            .add_column(LegacyColumnBuilder::new(ty, "Column".into()).build())
+            .add_row(LegacyRow::new(vec![Cell::Single(value)]))
+            .build();
+        let err = bdat::legacy::to_vec::<FileEndian>([&table], LegacyVersion::Switch).unwrap_err();
+        assert!(
+            matches!(err, BdatError::UnsupportedType(t, _) if t == ty),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+fn zero_columns_round_trip() {
+    use bdat::legacy::{LegacyRow, LegacyTableBuilder};
+
+    let table = LegacyTableBuilder::with_name("Padding")
+        .add_row(LegacyRow::new(vec![]))
+        .add_row(LegacyRow::new(vec![]))
+        .add_row(LegacyRow::new(vec![]))
+        .build();
+
+    let bytes = bdat::legacy::to_vec::<FileEndian>([&table], LegacyVersion::Switch).unwrap();
+    let new_table = bdat::legacy::from_bytes_copy::<FileEndian>(&bytes, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    assert_eq!(0, new_table.column_count());
+    assert_eq!(3, new_table.row_count());
+    assert_eq!(table, new_table);
+}
+
+#[test]
+fn from_bytes_ref() {
+    let tables = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    let expected = tables
+        .iter()
+        .cloned()
+        .map(CompatTable::from)
+        .collect::<Vec<_>>();
+
+    let plain = bdat::legacy::to_vec::<FileEndian>(&tables, LegacyVersion::Switch).unwrap();
+    let scrambled = bdat::legacy::to_vec_options::<FileEndian>(
+        &tables,
+        LegacyVersion::Switch,
+        LegacyWriteOptions::new().scramble(true),
+    )
+    .unwrap();
+    for data in [plain, scrambled] {
+        let read = bdat::from_bytes_ref(&data).unwrap().get_tables().unwrap();
+        assert_eq!(expected, read);
+    }
+}
+
+#[test]
+fn checksum_region() {
+    let tables = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    let plain = bdat::legacy::to_vec::<FileEndian>(&tables, LegacyVersion::Switch).unwrap();
+    let scrambled = bdat::legacy::to_vec_options::<FileEndian>(
+        &tables,
+        LegacyVersion::Switch,
+        LegacyWriteOptions::new().scramble(true),
+    )
+    .unwrap();
+
+    let range = bdat::legacy::from_bytes_copy::<FileEndian>(&plain, LegacyVersion::Switch)
+        .unwrap()
+        .table_byte_range(0)
+        .unwrap();
+    let checksum = bdat::legacy::scramble::calc_checksum(&plain[range.clone()]);
+    let key = &scrambled[range.start + 0x16..range.start + 0x18];
+    assert_eq!(checksum.to_le_bytes(), key);
+}
+
+#[test]
+fn scramble_final_padding() {
+    let tables = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    let plain = bdat::legacy::to_vec::<FileEndian>(&tables, LegacyVersion::Switch).unwrap();
+    let scrambled = bdat::legacy::to_vec_options::<FileEndian>(
+        &tables,
+        LegacyVersion::Switch,
+        LegacyWriteOptions::new().scramble(true),
+    )
+    .unwrap();
+
+    let file = bdat::legacy::from_bytes_copy::<FileEndian>(&plain, LegacyVersion::Switch).unwrap();
+    for i in 0..tables.len() {
+        let table = &plain[file.table_byte_range(i).unwrap()];
+        let scrambled_table = &scrambled[file.table_byte_range(i).unwrap()];
+        let key = u16::from_le_bytes(scrambled_table[0x16..0x18].try_into().unwrap());
+        // The string table size includes the final padding
+        let offset = u32::from_le_bytes(table[0x18..0x1c].try_into().unwrap()) as usize;
+        let len = u32::from_le_bytes(table[0x1c..0x20].try_into().unwrap()) as usize;
+        assert_eq!(table.len(), offset + len);
+
+        let mut strings = table[offset..].to_vec();
+        bdat::legacy::scramble::scramble(&mut strings, key);
+        assert_eq!(strings, &scrambled_table[offset..]);
+    }
+}
+
+#[test]
+fn scramble_once() {
+    let tables = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    let write = |tables: &[LegacyTable]| {
+        bdat::legacy::to_vec_options::<FileEndian>(
+            tables,
+            LegacyVersion::Switch,
+            LegacyWriteOptions::new().scramble(true),
+        )
+        .unwrap()
+    };
+    let scrambled = write(&tables);
+
+    // Reading unscrambles the tables, so writing them back scrambles them only once
+    let tables = bdat::legacy::from_bytes_copy::<FileEndian>(&scrambled, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    assert_eq!(scrambled, write(&tables));
+
+    let mut data = scrambled.clone();
+    let header = FileHeader::read::<_, FileEndian>(Cursor::new(&data)).unwrap();
+    header
+        .for_each_table_mut(&mut data, |table| {
+            let header =
+                TableHeader::read::<FileEndian>(Cursor::new(&table), LegacyVersion::Switch)?;
+            assert!(matches!(
+                header.scramble_data::<FileEndian>(table),
+                Err(BdatError::AlreadyScrambled)
+            ));
+            header.unscramble_data(table);
+            assert!(matches!(
+                header.scramble_data::<FileEndian>(&mut table[..0x10]),
+                Err(BdatError::MalformedBdat(_))
+            ));
+            header.scramble_data::<FileEndian>(table)
+        })
+        .unwrap();
+    assert_eq!(scrambled, data);
+}
+
+#[test]
+fn table_shapes() {
+    let tables = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    let expected = tables
+        .iter()
+        .map(|t| (t.row_count(), t.column_count()))
+        .collect::<Vec<_>>();
+
+    let shapes =
+        bdat::legacy::from_reader::<_, FileEndian>(Cursor::new(TEST_FILE_1), LegacyVersion::Switch)
+            .unwrap()
+            .table_shapes()
+            .unwrap();
+    assert_eq!(expected, shapes);
+
+    let mut data = TEST_FILE_1.to_vec();
+    let shapes = bdat::from_bytes(&mut data).unwrap().table_shapes().unwrap();
+    assert_eq!(expected, shapes);
+}
+
+#[test]
+fn orphan_flag() {
+    use bdat::legacy::{LegacyColumnBuilder, LegacyFlag, LegacyRow, LegacyTableBuilder};
+    use bdat::ValueType;
+
+    let table = LegacyTableBuilder::with_name("Table1")
+        .add_column(
+            LegacyColumnBuilder::new(ValueType::UnsignedInt, "Flags".into())
+                .set_flags(vec![LegacyFlag::new("Orphan", 0x12345678, 3)])
+                .build(),
+        )
+        .add_row(LegacyRow::new(vec![Cell::Flags(vec![1])]))
+        .build();
+    let mut data = bdat::legacy::to_vec::<FileEndian>(&[table], LegacyVersion::Switch).unwrap();
+
+    // Make the flag's parent pointer dangle
+    let mut pattern = vec![3, 3];
+    pattern.extend_from_slice(&0x12345678u32.to_le_bytes());
+    let pos = data
+        .windows(pattern.len())
+        .position(|w| w == pattern)
+        .unwrap()
+        + pattern.len();
+    data[pos..pos + 2].copy_from_slice(&[0xFF, 0xFF]);
+
+    let err = bdat::legacy::from_bytes_copy::<FileEndian>(&data, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap_err();
+    assert!(matches!(err, BdatError::OrphanFlag { flag_label } if flag_label == "Orphan"));
+
+    let tables = bdat::legacy::from_bytes_copy_options::<FileEndian>(
+        &data,
+        LegacyVersion::Switch,
+        LegacyReadOptions::new().skip_orphan_flags(true),
+    )
+    .unwrap()
+    .get_tables()
+    .unwrap();
+    let column = tables[0].columns().next().unwrap();
+    assert!(column.flags().is_empty());
+    assert_eq!(
+        Some(&Cell::Single(Value::UnsignedInt(8))),
+        tables[0].rows().next().unwrap().cells().next()
+    );
+}
+
+#[test]
+fn compat_row_out_of_range() {
+    let table: CompatTable = common::duplicate_table_create().into();
+    assert!(table.get_row(1).is_some());
+    assert!(table.get_row(3).is_none());
+    assert!(table.get_row(0x10001).is_none());
+}
+
+#[test]
+#[should_panic(expected = "row not found")]
+fn compat_row_out_of_range_panics() {
+    let table: CompatTable = common::duplicate_table_create().into();
+    table.row(0x10001);
+}
+
+#[test]
+fn compat_set_name_lossy() {
+    let mut table: CompatTable = common::duplicate_table_create().into();
+    table.set_name_lossy(Label::Hash(0xDEADBEEF));
+    assert_eq!("<DEADBEEF>", table.as_legacy().name());
+    assert_eq!(
+        Label::Hash(0xDEADBEEF),
+        Label::parse(table.as_legacy().name(), false)
+    );
+
+    table.set_name_lossy(Label::from("Test2"));
+    assert_eq!("Test2", table.as_legacy().name());
+}
+
+#[test]
+fn write_grouped() {
+    let tables = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    let dup = [common::duplicate_table_create()];
+    let files = bdat::legacy::to_vec_grouped::<FileEndian, _>(
+        [("first", &tables[..]), ("second", &dup[..])],
+        LegacyVersion::Switch,
+    )
+    .unwrap();
+    assert_eq!(
+        vec!["first", "second"],
+        files.iter().map(|(n, _)| *n).collect::<Vec<_>>()
+    );
+
+    for ((_, mut bytes), expected) in files.into_iter().zip([&tables[..], &dup[..]]) {
+        let back = bdat::legacy::from_bytes::<FileEndian>(&mut bytes, LegacyVersion::Switch)
+            .unwrap()
+            .get_tables()
+            .unwrap();
+        assert_eq!(expected, back);
+    }
+}
+
+#[test]
+fn duplicate_column_labels() {
+    use bdat::legacy::{LegacyColumnBuilder, LegacyRow, LegacyTableBuilder};
+    use bdat::ValueType;
+
+    let table = LegacyTableBuilder::with_name("Test")
+        .add_column(LegacyColumnBuilder::new(ValueType::UnsignedInt, "Dup".into()).build())
+        .add_column(LegacyColumnBuilder::new(ValueType::UnsignedByte, "Dup".into()).build())
+        .add_row(LegacyRow::new(vec![
+            Cell::Single(Value::UnsignedInt(1)),
+            Cell::Single(Value::UnsignedByte(2)),
+        ]))
+        .build();
+
+    // Label lookups find the first column, positional access reaches both
+    let row = table.row(1);
+    assert_eq!(Cell::Single(Value::UnsignedInt(1)), *row.get("Dup"));
+    assert_eq!(
+        Some(&Cell::Single(Value::UnsignedByte(2))),
+        table.row(1).get_at(1)
+    );
+    assert_eq!(None, table.row(1).get_at(2));
+    assert_eq!(
+        ValueType::UnsignedByte,
+        table.column_at(1).unwrap().value_type()
+    );
+    assert!(table.column_at(2).is_none());
+
+    // Both columns survive a round trip, in order
+    let mut bytes = bdat::legacy::to_vec::<FileEndian>([&table], LegacyVersion::Switch).unwrap();
+    let back = bdat::legacy::from_bytes::<FileEndian>(&mut bytes, LegacyVersion::Switch)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    assert_eq!(table, back[0]);
+
+    let compat: CompatTable = back.into_iter().next().unwrap().into();
+    assert_eq!(
+        ValueType::UnsignedByte,
+        compat.column_at(1).unwrap().value_type()
+    );
+    assert_eq!(
+        Some(Cell::Single(Value::UnsignedByte(2))),
+        compat.row(1).get_at(1)
+    );
+}
+
+#[test]
+fn rebuild_column_index() {
+    use bdat::legacy::{LegacyColumnBuilder, LegacyRow, LegacyTableBuilder};
+    use bdat::ValueType;
+
+    let mut table = LegacyTableBuilder::with_name("Test")
+        .add_column(LegacyColumnBuilder::new(ValueType::UnsignedInt, "Old".into()).build())
+        .add_row(LegacyRow::new(vec![Cell::Single(Value::UnsignedInt(1))]))
+        .build();
+    for col in table.columns_mut() {
+        *col = LegacyColumnBuilder::new(col.value_type(), "New".into()).build();
+    }
+
+    // The index still has the old label until it is rebuilt
+    assert!(table.row(1).get_if_present("New").is_none());
+    table.rebuild_column_index();
+    assert!(table.row(1).get_if_present("Old").is_none());
+    assert_eq!(
+        Cell::Single(Value::UnsignedInt(1)),
+        *table.row(1).get("New")
+    );
+}
+
+#[test]
+fn rows_equal_ignoring() {
+    use bdat::legacy::{LegacyColumnBuilder, LegacyRow, LegacyTableBuilder};
+    use bdat::ValueType;
+
+    let table = LegacyTableBuilder::with_name("Test")
+        .add_column(LegacyColumnBuilder::new(ValueType::UnsignedInt, "Id".into()).build())
+        .add_column(LegacyColumnBuilder::new(ValueType::String, "Name".into()).build())
+        .extend_rows([(1, "a"), (2, "a"), (3, "b")].map(|(id, name)| {
+            LegacyRow::new(vec![
+                Cell::Single(Value::UnsignedInt(id)),
+                Cell::Single(Value::String(name.into())),
+            ])
+        }))
+        .build();
+    assert!(!table.rows_equal_ignoring(1, 2, &[]));
+    assert!(table.rows_equal_ignoring(1, 2, &["Id"]));
+    assert!(!table.rows_equal_ignoring(1, 3, &["Id"]));
+    assert!(table.rows_equal_ignoring(1, 3, &["Id", "Name"]));
+    assert!(!table.rows_equal_ignoring(1, 4, &["Id", "Name"]));
+}
@@ -1,5 +1,6 @@
 use bdat::legacy::{LegacyTable, LegacyWriteOptions};
-use bdat::{BdatFile, Cell, LegacyVersion, SwitchEndian, Value};
+use bdat::{BdatFile, Cell, Label, LegacyVersion, SwitchEndian, Value};
+use std::io::Cursor;
 
 type FileEndian = SwitchEndian;
 const VERSION: LegacyVersion = LegacyVersion::New3ds;
@@ -125,3 +126,49 @@ fn duplicate_columns() {
         .unwrap();
     assert_eq!(tables[0], back[0]);
 }
+
+#[test]
+fn table_names() {
+    let expected = vec![Label::from("Table1")];
+
+    // Scrambled names must be unscrambled without touching the source
+    let names = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, VERSION)
+        .unwrap()
+        .table_names()
+        .unwrap();
+    assert_eq!(expected, names);
+
+    let names = bdat::legacy::from_reader::<_, FileEndian>(Cursor::new(TEST_FILE_1), VERSION)
+        .unwrap()
+        .table_names()
+        .unwrap();
+    assert_eq!(expected, names);
+
+    let mut data = TEST_FILE_1.to_vec();
+    let names = bdat::from_bytes(&mut data).unwrap().table_names().unwrap();
+    assert_eq!(expected, names);
+}
+
+#[test]
+fn detect_round_trip() {
+    let mut data = TEST_FILE_1.to_vec();
+    let mut file = bdat::from_bytes(&mut data).unwrap();
+    assert_eq!(bdat::BdatVersion::from(VERSION), file.version());
+    let tables = file
+        .get_tables()
+        .unwrap()
+        .into_iter()
+        .map(|t| t.into_legacy())
+        .collect::<Vec<_>>();
+    assert_eq!(4, tables[0].column_count());
+
+    let mut out = bdat::legacy::to_vec::<FileEndian>(&tables, VERSION).unwrap();
+    let new_tables = bdat::from_bytes(&mut out)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .into_iter()
+        .map(|t| t.into_legacy())
+        .collect::<Vec<_>>();
+    assert_eq!(tables, new_tables);
+}
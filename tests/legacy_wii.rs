@@ -126,3 +126,33 @@ fn duplicate_columns() {
         .unwrap();
     assert_eq!(tables[0], back[0]);
 }
+
+#[test]
+fn zero_columns_round_trip() {
+    use bdat::legacy::{LegacyRow, LegacyTableBuilder};
+
+    let table = LegacyTableBuilder::with_name("Padding")
+        .add_row(LegacyRow::new(vec![]))
+        .add_row(LegacyRow::new(vec![]))
+        .build();
+
+    let bytes = bdat::legacy::to_vec::<FileEndian>([&table], VERSION).unwrap();
+    let new_table = bdat::legacy::from_bytes_copy::<FileEndian>(&bytes, VERSION)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    assert_eq!(table, new_table);
+}
+
+#[test]
+fn table_shapes() {
+    let mut file = bdat::legacy::from_bytes_copy::<FileEndian>(TEST_FILE_1, VERSION).unwrap();
+    let expected = file
+        .get_tables()
+        .unwrap()
+        .iter()
+        .map(|t| (t.row_count(), t.column_count()))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, file.table_shapes().unwrap());
+}
@@ -1,4 +1,8 @@
-use bdat::{label_hash, BdatFile, BdatVersion, Label, SwitchEndian};
+use bdat::compat::{CompatColumn, CompatTable};
+use bdat::modern::{ModernColumn, ModernRow, ModernTable, ModernTableBuilder};
+use bdat::{label_hash, BdatError, BdatFile, BdatVersion, Label, SwitchEndian, Value, ValueType};
+use byteorder::ByteOrder;
+use std::borrow::Cow;
 
 type FileEndian = SwitchEndian;
 
@@ -12,6 +16,15 @@ fn version_detect() {
     );
 }
 
+#[test]
+fn detected_version() {
+    let mut data = TEST_FILE_1.to_vec();
+    assert_eq!(
+        BdatVersion::Modern,
+        bdat::from_bytes(&mut data).unwrap().version()
+    );
+}
+
 #[test]
 fn basic_read() {
     let tables = bdat::modern::from_bytes::<FileEndian>(TEST_FILE_1)
@@ -23,6 +36,15 @@ fn basic_read() {
     let table = &tables[0];
     assert_eq!(&label_hash!("Table1"), table.name());
     assert_eq!(4, table.column_count());
+    assert_eq!(
+        vec![
+            ValueType::UnsignedInt,
+            ValueType::Float,
+            ValueType::String,
+            ValueType::HashRef
+        ],
+        table.column_types()
+    );
 
     let data_t1 = [
         (36_u32, 2.0_f32, "Row 1", label_hash!("Row 1")),
@@ -72,3 +94,564 @@ fn table_map() {
     // Lifetime test
     assert_ne!(0, table.column_count());
 }
+
+#[test]
+fn into_table_map() {
+    let tables = bdat::modern::from_bytes::<FileEndian>(TEST_FILE_1)
+        .unwrap()
+        .into_table_map()
+        .unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(
+        &label_hash!("Table1"),
+        tables[&label_hash!("Table1")].name()
+    );
+
+    let table = tables.into_values().next().unwrap();
+    let bytes = bdat::modern::to_vec::<FileEndian>([&table, &table]).unwrap();
+    let err = bdat::modern::from_bytes::<FileEndian>(&bytes)
+        .unwrap()
+        .into_table_map()
+        .unwrap_err();
+    assert!(matches!(err, BdatError::DuplicateTable(name) if name == label_hash!("Table1")));
+}
+
+#[test]
+fn compat_zero_copy() {
+    let mut data = TEST_FILE_1.to_vec();
+    let range = data.as_ptr_range();
+    let tables = bdat::from_bytes(&mut data).unwrap().get_tables().unwrap();
+
+    // String cells point into the original buffer
+    let table = tables[0].as_modern();
+    for row in table.rows() {
+        let Some(Value::String(Cow::Borrowed(s))) = row.values().nth(2) else {
+            panic!("string was copied");
+        };
+        assert!(range.contains(&s.as_ptr()));
+    }
+}
+
+#[test]
+fn table_names() {
+    let names = bdat::modern::from_bytes::<FileEndian>(TEST_FILE_1)
+        .unwrap()
+        .table_names()
+        .unwrap();
+    assert_eq!(vec![label_hash!("Table1")], names);
+
+    let names = bdat::modern::from_reader::<_, FileEndian>(std::io::Cursor::new(TEST_FILE_1))
+        .unwrap()
+        .table_names()
+        .unwrap();
+    assert_eq!(vec![label_hash!("Table1")], names);
+}
+
+#[test]
+fn table_names_skip_rows() {
+    let mut data = TEST_FILE_1.to_vec();
+    let table_offset = u32::from_le_bytes(data[16..20].try_into().unwrap()) as usize;
+    // Move row data out of bounds, names don't need it
+    data[table_offset + 0x20..table_offset + 0x24].copy_from_slice(&0x7fff_ffffu32.to_le_bytes());
+
+    let mut file = bdat::modern::from_bytes::<FileEndian>(&data).unwrap();
+    assert!(file.get_tables().is_err());
+    assert_eq!(vec![label_hash!("Table1")], file.table_names().unwrap());
+
+    let mut file = bdat::modern::from_reader::<_, FileEndian>(std::io::Cursor::new(&data)).unwrap();
+    assert!(file.get_tables().is_err());
+    assert_eq!(vec![label_hash!("Table1")], file.table_names().unwrap());
+}
+
+#[test]
+fn malformed_table_length() {
+    let mut data = TEST_FILE_1.to_vec();
+    let table_offset = u32::from_le_bytes(data[16..20].try_into().unwrap()) as usize;
+    // Claim a huge string table
+    data[table_offset + 0x2c..table_offset + 0x30].copy_from_slice(&0x7fff_ffffu32.to_le_bytes());
+
+    let err = bdat::modern::from_bytes::<FileEndian>(&data)
+        .unwrap()
+        .get_tables()
+        .unwrap_err();
+    assert!(matches!(err, BdatError::MalformedBdat(_)));
+
+    let err = bdat::modern::from_reader::<_, FileEndian>(std::io::Cursor::new(&data))
+        .unwrap()
+        .get_tables()
+        .unwrap_err();
+    assert!(matches!(err, BdatError::MalformedBdat(_)));
+}
+
+#[test]
+fn unexpected_magic() {
+    let mut data = TEST_FILE_1.to_vec();
+    data[..4].copy_from_slice(b"TADB");
+    let err = bdat::modern::from_bytes::<FileEndian>(&data).err().unwrap();
+    assert!(matches!(err, BdatError::UnexpectedMagic { found } if &found == b"TADB"));
+
+    // The magic is checked in file order, so a byte order mismatch is only found afterwards
+    let err = bdat::modern::from_bytes::<bdat::WiiEndian>(TEST_FILE_1)
+        .err()
+        .unwrap();
+    assert!(matches!(err, BdatError::MalformedBdat(_)));
+}
+
+#[test]
+fn unterminated_string_table() {
+    let table = ModernTableBuilder::with_name(Label::Hash(1))
+        .add_column(ModernColumn::new(ValueType::String, Label::Hash(2)))
+        .add_row(ModernRow::new(vec![Value::String("abc".into())]))
+        .build();
+    let mut data = bdat::modern::to_vec::<FileEndian>([&table]).unwrap();
+    let table_offset = FileEndian::read_u32(&data[16..]) as usize;
+    let strings_offset = FileEndian::read_u32(&data[table_offset + 0x28..]) as usize;
+    // Make the string table end right after "abc", dropping its terminator
+    let str_end = data[table_offset + strings_offset..]
+        .windows(4)
+        .position(|w| w == b"abc\0")
+        .unwrap()
+        + 3;
+    FileEndian::write_u32(&mut data[table_offset + 0x2c..], str_end as u32);
+
+    let tables = bdat::modern::from_bytes::<FileEndian>(&data)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    assert_eq!(
+        &Value::String("abc".into()),
+        tables[0].row(1).get(Label::Hash(2))
+    );
+
+    // Offsets past the end of the string table are rejected
+    FileEndian::write_u32(&mut data[table_offset + 0x2c..], 4);
+    let err = bdat::modern::from_bytes::<FileEndian>(&data)
+        .unwrap()
+        .get_tables()
+        .unwrap_err();
+    assert!(matches!(err, BdatError::MalformedBdat(_)));
+}
+
+#[test]
+fn builder_extend_rows() {
+    let row = |hash| ModernRow::new(vec![Value::HashRef(hash), Value::UnsignedInt(hash)]);
+    let table = ModernTableBuilder::with_name(Label::Hash(1))
+        .add_column(ModernColumn::new(ValueType::HashRef, Label::Hash(2)))
+        .add_column(ModernColumn::new(ValueType::UnsignedInt, Label::Hash(3)))
+        .add_row(row(10))
+        .extend_rows((20..23).map(row))
+        .extend_rows(std::iter::empty::<ModernRow>())
+        .build();
+    assert_eq!(4, table.row_count());
+    assert_eq!(1, table.get_row_by_hash(10).unwrap().id());
+    assert_eq!(4, table.get_row_by_hash(22).unwrap().id());
+    assert_eq!(21, table.row(3).get(Label::Hash(3)).get_as::<u32>());
+}
+
+#[test]
+fn row_length_mismatch() {
+    let mut data = TEST_FILE_1.to_vec();
+    let table_offset = FileEndian::read_u32(&data[16..]) as usize;
+    let row_length = FileEndian::read_u32(&data[table_offset + 0x24..]);
+    FileEndian::write_u32(&mut data[table_offset + 0x24..], row_length - 1);
+
+    let err = bdat::modern::from_bytes::<FileEndian>(&data)
+        .unwrap()
+        .get_tables()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        BdatError::RowSizeMismatch { row: 1, expected, actual } if expected == actual + 1
+    ));
+}
+
+#[test]
+fn table_byte_range() {
+    let table = bdat::modern::from_bytes::<FileEndian>(TEST_FILE_1)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    let bytes = bdat::modern::to_vec::<FileEndian>([&table, &table]).unwrap();
+
+    let file = bdat::modern::from_bytes::<FileEndian>(&bytes).unwrap();
+    assert_eq!(None, file.table_byte_range(2));
+    let range = file.table_byte_range(1).unwrap();
+    assert_eq!(file.table_byte_range(0).unwrap().end, range.start);
+    assert_eq!(bytes.len(), range.end);
+
+    // Build a file with only that table: magic, version, table count, file size, offset
+    let mut single = bytes[..8].to_vec();
+    single.extend_from_slice(&1u32.to_le_bytes());
+    single.extend_from_slice(&((20 + range.len()) as u32).to_le_bytes());
+    single.extend_from_slice(&20u32.to_le_bytes());
+    single.extend_from_slice(&bytes[range]);
+    let new_table = bdat::modern::from_bytes::<FileEndian>(&single)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    assert_eq!(table, new_table);
+}
+
+#[test]
+fn table_byte_range_padding() {
+    // Odd-length strings, so the writer has to pad each table
+    let table = |name: u32, text: &'static str| {
+        ModernTableBuilder::with_name(Label::Hash(name))
+            .add_column(ModernColumn::new(ValueType::String, Label::Hash(1)))
+            .add_row(ModernRow::new(vec![Value::String(text.into())]))
+            .build()
+    };
+    let tables = [table(1, "a"), table(2, "bcde"), table(3, "ef")];
+    let bytes = bdat::modern::to_vec::<FileEndian>(&tables).unwrap();
+
+    let file = bdat::modern::from_bytes::<FileEndian>(&bytes).unwrap();
+    let ranges = (0..tables.len())
+        .map(|i| file.table_byte_range(i).unwrap())
+        .collect::<Vec<_>>();
+    for (i, range) in ranges.iter().enumerate() {
+        let offset = FileEndian::read_u32(&bytes[16 + i * 4..]) as usize;
+        assert_eq!(offset, range.start);
+        assert_eq!(0, range.len() % 4);
+        // Offsets of the string table and its length
+        let table = &bytes[range.clone()];
+        let strings_end = FileEndian::read_u32(&table[40..]) + FileEndian::read_u32(&table[44..]);
+        assert!(
+            (strings_end as usize) < range.len(),
+            "table #{i} has no padding"
+        );
+    }
+    assert!(ranges.windows(2).all(|r| r[0].end == r[1].start));
+    assert_eq!(bytes.len(), ranges.last().unwrap().end);
+
+    // Writing the tables again must keep every table at the same offset
+    let read = bdat::modern::from_bytes::<FileEndian>(&bytes)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    assert_eq!(bytes, bdat::modern::to_vec::<FileEndian>(&read).unwrap());
+}
+
+#[test]
+fn base_id_round_trip() {
+    let table = bdat::modern::from_bytes::<FileEndian>(TEST_FILE_1)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    let table = ModernTableBuilder::from(table).set_base_id(1000).build();
+
+    let bytes = bdat::modern::to_vec::<FileEndian>([&table]).unwrap();
+    let new_table = bdat::modern::from_bytes::<FileEndian>(&bytes)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    assert_eq!(1000, new_table.base_id());
+    assert_eq!(1000, new_table.rows().next().unwrap().id());
+    assert_eq!(table, new_table);
+}
+
+#[test]
+fn reserved_header_round_trip() {
+    let mut data = TEST_FILE_1.to_vec();
+    let table_offset = FileEndian::read_u32(&data[16..]) as usize;
+    data[table_offset + 0x14..table_offset + 0x18].copy_from_slice(&[1, 2, 3, 4]);
+
+    let mut file = bdat::modern::from_bytes::<FileEndian>(&data).unwrap();
+    assert_eq!(
+        [1, 2, 3, 4],
+        file.table_rows(0).unwrap().unwrap().reserved()
+    );
+    let tables = file.get_tables().unwrap();
+    assert_eq!([1, 2, 3, 4], tables[0].reserved());
+
+    let bytes = bdat::modern::to_vec::<FileEndian>(&tables).unwrap();
+    let table_offset = FileEndian::read_u32(&bytes[16..]) as usize;
+    assert_eq!(
+        [1, 2, 3, 4],
+        bytes[table_offset + 0x14..table_offset + 0x18]
+    );
+}
+
+#[test]
+fn compat_column_conversion() {
+    let hashed = ModernColumn::new(ValueType::UnsignedInt, Label::Hash(0xDEADBEEF));
+    let named = ModernColumn::new(ValueType::UnsignedInt, Label::String("Name".into()));
+
+    assert_eq!(hashed, CompatColumn::from(hashed.clone()).into_modern());
+    assert!(CompatColumn::from(hashed).try_into_legacy().is_err());
+
+    let legacy = CompatColumn::from(named.clone()).try_into_legacy().unwrap();
+    assert_eq!("Name", legacy.label());
+    assert_eq!(ValueType::UnsignedInt, legacy.value_type());
+    assert_eq!(legacy, CompatColumn::from(legacy.clone()).into_legacy());
+    assert_eq!(named, CompatColumn::from(legacy).try_into_modern().unwrap());
+}
+
+#[test]
+fn multiple_hash_columns() {
+    let mut table = ModernTableBuilder::with_name(Label::Hash(0xCAFEBABE))
+        .add_column(ModernColumn::new(ValueType::HashRef, Label::Hash(1)))
+        .add_column(ModernColumn::new(ValueType::HashRef, Label::Hash(2)))
+        .add_row(ModernRow::new(vec![
+            Value::HashRef(0x10),
+            Value::HashRef(0x20),
+        ]))
+        .add_row(ModernRow::new(vec![
+            Value::HashRef(0x10),
+            Value::HashRef(0x21),
+        ]))
+        // The first column has duplicate values, so it can't be the primary key
+        .build_with_primary_key(Some(1));
+    assert_eq!(Some(1), table.primary_key());
+
+    let bytes = bdat::modern::to_vec::<FileEndian>([&table]).unwrap();
+    table = bdat::modern::from_bytes::<FileEndian>(&bytes)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    assert_eq!(Some(1), table.primary_key());
+    assert_eq!(2, table.row_by_hash(0x21).id());
+    assert!(table.get_row_by_hash(0x10).is_none());
+
+    // Without a primary key, no hash table is written
+    table.set_primary_key(None);
+    let bytes = bdat::modern::to_vec::<FileEndian>([&table]).unwrap();
+    let table = bdat::modern::from_bytes::<FileEndian>(&bytes)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    assert_eq!(None, table.primary_key());
+    assert!(table.get_row_by_hash(0x20).is_none());
+}
+
+#[test]
+fn builder_from_table_keeps_key() {
+    let mut table = ModernTableBuilder::with_name(Label::Hash(0xCAFEBABE))
+        .add_column(ModernColumn::new(ValueType::HashRef, Label::Hash(1)))
+        .add_column(ModernColumn::new(ValueType::HashRef, Label::Hash(2)))
+        .add_row(ModernRow::new(vec![
+            Value::HashRef(0x10),
+            Value::HashRef(0x20),
+        ]))
+        .add_row(ModernRow::new(vec![
+            Value::HashRef(0x10),
+            Value::HashRef(0x21),
+        ]))
+        .build_with_primary_key(Some(1));
+    table.set_reserved([1, 2, 3, 4]);
+
+    let table = ModernTableBuilder::from(table).build();
+    assert_eq!(Some(1), table.primary_key());
+    assert_eq!([1, 2, 3, 4], table.reserved());
+    assert_eq!(2, table.row_by_hash(0x21).id());
+
+    let mut table = table;
+    table.set_primary_key(None);
+    let table = ModernTableBuilder::from(table).build();
+    assert_eq!(None, table.primary_key());
+}
+
+#[test]
+fn reorder_columns_remaps_key() {
+    let table = ModernTableBuilder::with_name(Label::Hash(0xCAFEBABE))
+        .add_column(ModernColumn::new(ValueType::HashRef, Label::Hash(2)))
+        .add_column(ModernColumn::new(ValueType::HashRef, Label::Hash(1)))
+        .add_row(ModernRow::new(vec![
+            Value::HashRef(0x20),
+            Value::HashRef(0x10),
+        ]))
+        .add_row(ModernRow::new(vec![
+            Value::HashRef(0x21),
+            Value::HashRef(0x10),
+        ]))
+        .build_with_primary_key(Some(0));
+
+    let sorted = ModernTableBuilder::from(table.clone())
+        .sort_columns_by_label()
+        .build();
+    assert_eq!(Some(1), sorted.primary_key());
+    assert_eq!(2, sorted.row_by_hash(0x21).id());
+
+    let restored = ModernTableBuilder::from(sorted)
+        .preserve_column_order(&table)
+        .build();
+    assert_eq!(Some(0), restored.primary_key());
+    assert_eq!(2, restored.row_by_hash(0x21).id());
+}
+
+#[test]
+fn lazy_rows() {
+    let mut file = bdat::modern::from_bytes::<FileEndian>(TEST_FILE_1).unwrap();
+    let table = file.get_tables().unwrap().remove(0);
+
+    let rows = file.table_rows(0).unwrap().unwrap();
+    assert_eq!(table.name(), rows.name());
+    assert_eq!(table.base_id(), rows.base_id());
+    assert_eq!(table.row_count(), rows.len());
+    let rows = rows.collect::<Result<Vec<_>, _>>().unwrap();
+    for (expected, row) in table.rows().zip(&rows) {
+        assert!(expected.values().eq(row.values()));
+    }
+
+    assert!(file.table_rows(1).unwrap().is_none());
+}
+
+#[test]
+fn schema_fingerprint() {
+    let build = |columns: Vec<ModernColumn<'static>>| -> CompatTable<'static> {
+        ModernTableBuilder::with_name(Label::Hash(0))
+            .set_columns(columns)
+            .build()
+            .into()
+    };
+
+    let table = build(vec![
+        ModernColumn::new(ValueType::UnsignedInt, Label::from("ID")),
+        ModernColumn::new(ValueType::String, label_hash!("Name")),
+    ]);
+    let same = build(vec![
+        ModernColumn::new(ValueType::UnsignedInt, label_hash!("ID")),
+        ModernColumn::new(ValueType::String, Label::from("Name")),
+    ]);
+    let retyped = build(vec![
+        ModernColumn::new(ValueType::UnsignedShort, label_hash!("ID")),
+        ModernColumn::new(ValueType::String, label_hash!("Name")),
+    ]);
+    let reordered = build(vec![
+        ModernColumn::new(ValueType::String, label_hash!("Name")),
+        ModernColumn::new(ValueType::UnsignedInt, label_hash!("ID")),
+    ]);
+
+    assert_eq!(table.schema_fingerprint(), same.schema_fingerprint());
+    assert_ne!(table.schema_fingerprint(), retyped.schema_fingerprint());
+    assert_ne!(table.schema_fingerprint(), reordered.schema_fingerprint());
+}
+
+#[test]
+fn row_size_mismatch() {
+    let table = ModernTableBuilder::with_name(Label::Hash(0))
+        .add_column(ModernColumn::new(ValueType::UnsignedInt, Label::Hash(1)))
+        .add_row(ModernRow::new(vec![Value::UnsignedInt(1)]))
+        .add_row(ModernRow::new(vec![Value::UnsignedByte(2)]))
+        .build();
+
+    let err = bdat::modern::to_vec::<FileEndian>([&table]).unwrap_err();
+    assert!(matches!(
+        err,
+        BdatError::RowSizeMismatch {
+            row: 2,
+            expected: 4,
+            actual: 1
+        }
+    ));
+}
+
+#[test]
+fn repair_header() {
+    let table = bdat::modern::from_bytes::<FileEndian>(TEST_FILE_1)
+        .unwrap()
+        .get_tables()
+        .unwrap()
+        .remove(0);
+    let bytes = bdat::modern::to_vec::<FileEndian>([&table, &table]).unwrap();
+
+    // Wrong version, file size and offsets
+    let mut corrupt = bytes.clone();
+    corrupt[4..24].fill(0xFF);
+    assert!(bdat::modern::from_bytes::<FileEndian>(&corrupt).is_err());
+    bdat::modern::repair_header::<FileEndian>(&mut corrupt).unwrap();
+    assert_eq!(bytes, corrupt);
+
+    // Header with only one table: the tables need to be moved
+    let mut missing = bytes[..8].to_vec();
+    missing.extend_from_slice(&1u32.to_le_bytes());
+    missing.extend_from_slice(&0u32.to_le_bytes());
+    missing.extend_from_slice(&20u32.to_le_bytes());
+    missing.extend_from_slice(&bytes[24..]);
+    bdat::modern::repair_header::<FileEndian>(&mut missing).unwrap();
+    assert_eq!(bytes, missing);
+    let tables = bdat::modern::from_bytes::<FileEndian>(&missing)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    assert_eq!(vec![table.clone(), table], tables);
+
+    let mut not_bdat = vec![0; 32];
+    assert!(bdat::modern::repair_header::<FileEndian>(&mut not_bdat).is_err());
+}
+
+#[test]
+fn repair_header_zeroed_table() {
+    // Valid table magic and version, but all other fields are zero
+    let mut data = b"BDAT".to_vec();
+    data.resize(16, 0);
+    data.extend_from_slice(b"BDAT");
+    data.extend_from_slice(&0x3004u32.to_le_bytes());
+    data.resize(data.len() + 40, 0);
+    assert!(bdat::modern::repair_header::<FileEndian>(&mut data).is_err());
+}
+
+#[test]
+fn from_bytes_ref() {
+    let tables = bdat::modern::from_bytes::<FileEndian>(TEST_FILE_1)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    let compat_tables = bdat::from_bytes_ref(TEST_FILE_1)
+        .unwrap()
+        .get_tables()
+        .unwrap();
+    assert_eq!(
+        tables,
+        compat_tables
+            .into_iter()
+            .map(CompatTable::into_modern)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn table_shapes() {
+    let mut file = bdat::modern::from_bytes::<FileEndian>(TEST_FILE_1).unwrap();
+    let expected = file
+        .get_tables()
+        .unwrap()
+        .iter()
+        .map(|t| (t.row_count(), t.column_count()))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, file.table_shapes().unwrap());
+}
+
+#[test]
+fn table_shapes_default() {
+    struct Tables(Vec<ModernTable<'static>>);
+
+    impl BdatFile<'static> for Tables {
+        type TableOut = ModernTable<'static>;
+
+        fn get_tables(&mut self) -> bdat::BdatResult<Vec<Self::TableOut>> {
+            Ok(self.0.clone())
+        }
+
+        fn table_count(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    let table = ModernTableBuilder::with_name(Label::Hash(1))
+        .add_column(ModernColumn::new(ValueType::UnsignedInt, Label::Hash(2)))
+        .add_column(ModernColumn::new(ValueType::UnsignedInt, Label::Hash(3)))
+        .add_row(ModernRow::new(vec![
+            Value::UnsignedInt(1),
+            Value::UnsignedInt(2),
+        ]))
+        .build();
+    let mut file = Tables(vec![table]);
+    assert_eq!(vec![(1, 2)], file.table_shapes().unwrap());
+    assert_eq!(vec![Label::Hash(1)], file.table_names().unwrap());
+}
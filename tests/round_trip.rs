@@ -0,0 +1,29 @@
+#![cfg(feature = "testing")]
+
+use bdat::testing::assert_round_trip;
+use bdat::{BdatVersion, LegacyVersion, SwitchEndian, WiiEndian};
+
+static LEGACY_FILE: &[u8] = include_bytes!("res/test_legacy_1.bdat");
+static LEGACY_3DS_FILE: &[u8] = include_bytes!("res/test_legacy_3ds_1.bdat");
+static LEGACY_WII_FILE: &[u8] = include_bytes!("res/test_legacy_wii_1.bdat");
+static LEGACY_X_FILE: &[u8] = include_bytes!("res/test_legacy_x_1.bdat");
+static MODERN_FILE: &[u8] = include_bytes!("res/test_modern_1.bdat");
+
+#[test]
+fn round_trip_legacy() {
+    assert_round_trip::<SwitchEndian>(LEGACY_FILE, LegacyVersion::Switch.into());
+    assert_round_trip::<SwitchEndian>(LEGACY_3DS_FILE, LegacyVersion::New3ds.into());
+    assert_round_trip::<WiiEndian>(LEGACY_WII_FILE, LegacyVersion::Wii.into());
+    assert_round_trip::<WiiEndian>(LEGACY_X_FILE, LegacyVersion::X.into());
+}
+
+#[test]
+fn round_trip_modern() {
+    assert_round_trip::<SwitchEndian>(MODERN_FILE, BdatVersion::Modern);
+}
+
+#[test]
+#[should_panic(expected = "could not read original file")]
+fn round_trip_invalid() {
+    assert_round_trip::<SwitchEndian>(&MODERN_FILE[..16], BdatVersion::Modern);
+}
@@ -2,8 +2,8 @@ use anyhow::{Context, Result};
 use bdat::compat::{CompatColumnRef, CompatTable};
 use bdat::serde::SerializeCell;
 use bdat::{Cell, Value};
-use clap::Args;
-use csv::WriterBuilder;
+use clap::{Args, ValueEnum};
+use csv::{QuoteStyle, Terminator, WriterBuilder};
 use std::io::Write;
 use std::iter::Once;
 
@@ -16,12 +16,32 @@ pub struct CsvOptions {
     /// When converting to CSV, expands legacy-BDAT lists into separate columns
     #[arg(long)]
     expand_lists: bool,
+    /// When converting to CSV, which fields should be quoted
+    #[arg(long, value_enum, default_value_t = CsvQuoteStyle::Necessary)]
+    csv_quote_style: CsvQuoteStyle,
+    /// When converting to CSV, writes a UTF-8 byte order mark at the start of each file
+    #[arg(long)]
+    csv_bom: bool,
+    /// When converting to CSV, ends records with CRLF instead of LF, as required by RFC 4180
+    #[arg(long)]
+    csv_crlf: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CsvQuoteStyle {
+    /// Quote all fields
+    Always,
+    /// Only quote fields that contain the separator, quotes, or line breaks
+    Necessary,
 }
 
 pub struct CsvConverter {
     separator_ch: char,
     expand_lists: bool,
     untyped: bool,
+    quote_style: QuoteStyle,
+    bom: bool,
+    crlf: bool,
 }
 
 /// Utility to `flat_map` multiple iterator types
@@ -37,6 +57,12 @@ impl CsvConverter {
             separator_ch: args.csv_opts.csv_separator.unwrap_or(','),
             expand_lists: args.csv_opts.expand_lists,
             untyped: args.untyped,
+            quote_style: match args.csv_opts.csv_quote_style {
+                CsvQuoteStyle::Always => QuoteStyle::Always,
+                CsvQuoteStyle::Necessary => QuoteStyle::Necessary,
+            },
+            bom: args.csv_opts.csv_bom,
+            crlf: args.csv_opts.csv_crlf,
         }
     }
 
@@ -104,8 +130,19 @@ impl CsvConverter {
 
 impl BdatSerialize for CsvConverter {
     fn write_table(&self, table: CompatTable, writer: &mut dyn Write) -> Result<()> {
+        if self.bom {
+            writer
+                .write_all("\u{FEFF}".as_bytes())
+                .context("Failed to write BOM")?;
+        }
         let mut writer = WriterBuilder::new()
             .delimiter(self.separator_ch as u8)
+            .quote_style(self.quote_style)
+            .terminator(if self.crlf {
+                Terminator::CRLF
+            } else {
+                Terminator::Any(b'\n')
+            })
             .from_writer(writer);
 
         let header = table
@@ -128,8 +165,8 @@ impl BdatSerialize for CsvConverter {
         Ok(())
     }
 
-    fn get_file_name(&self, table_name: &str) -> String {
-        format!("{table_name}.csv")
+    fn get_table_extension(&self) -> &'static str {
+        "csv"
     }
 }
 
@@ -144,3 +181,83 @@ impl<E, T: Iterator<Item = E>, T2: Iterator<Item = E>> Iterator for ColumnIter<E
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bdat::legacy::{LegacyColumnBuilder, LegacyRow, LegacyTableBuilder};
+    use bdat::{Cell, Value, ValueType};
+    use csv::QuoteStyle;
+
+    use super::{BdatSerialize, CsvConverter};
+
+    fn write(converter: &CsvConverter, values: &[&str]) -> String {
+        let table = LegacyTableBuilder::with_name("Test")
+            .add_column(LegacyColumnBuilder::new(ValueType::String, "Text".into()))
+            .add_column(
+                LegacyColumnBuilder::new(ValueType::UnsignedByte, "List".into())
+                    .set_count(2)
+                    .build(),
+            )
+            .extend_rows(values.iter().map(|&s| {
+                LegacyRow::new(vec![
+                    Cell::Single(Value::String(s.into())),
+                    Cell::List(vec![Value::UnsignedByte(1), Value::UnsignedByte(2)]),
+                ])
+            }))
+            .build();
+        let mut out = Vec::new();
+        converter.write_table(table.into(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn converter() -> CsvConverter {
+        CsvConverter {
+            separator_ch: ',',
+            expand_lists: false,
+            untyped: true,
+            quote_style: QuoteStyle::Necessary,
+            bom: false,
+            crlf: false,
+        }
+    }
+
+    #[test]
+    fn escaping() {
+        let values = ["plain", "a,b", "say \"hi\"", "line\nbreak", "cr\r\nlf"];
+        let out = write(&converter(), &values);
+        assert_eq!(
+            "Text,List\nplain,\"[1,2]\"\n\"a,b\",\"[1,2]\"\n\"say \"\"hi\"\"\",\"[1,2]\"\n\
+            \"line\nbreak\",\"[1,2]\"\n\"cr\r\nlf\",\"[1,2]\"\n",
+            out
+        );
+
+        let read = csv::Reader::from_reader(out.as_bytes())
+            .records()
+            .map(|r| r.unwrap()[0].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(values.as_slice(), read);
+    }
+
+    #[test]
+    fn strict_options() {
+        let out = write(
+            &CsvConverter {
+                quote_style: QuoteStyle::Always,
+                crlf: true,
+                ..converter()
+            },
+            &["a"],
+        );
+        assert_eq!("\"Text\",\"List\"\r\n\"a\",\"[1,2]\"\r\n", out);
+        assert!(!out.starts_with('\u{FEFF}'));
+
+        let out = write(
+            &CsvConverter {
+                bom: true,
+                ..converter()
+            },
+            &["a"],
+        );
+        assert_eq!("\u{FEFF}Text,List\na,\"[1,2]\"\n", out);
+    }
+}
@@ -19,6 +19,7 @@ use serde_json::Map;
 
 use crate::error::{FormatError, MAX_DUPLICATE_COLUMNS};
 use crate::util::fixed_vec::FixedVec;
+use crate::util::hash::HashNameTable;
 
 use super::{schema::FileSchema, BdatDeserialize, BdatSerialize, ConvertArgs};
 
@@ -28,6 +29,11 @@ pub struct JsonOptions {
     /// to improve readability.
     #[arg(long)]
     pretty: bool,
+    /// (Extract only) If this is set, hashed labels are kept as-is, and columns whose name is
+    /// found in the hash list also get a "_label_name" field with the unhashed name. That field
+    /// is only informative, and it is ignored when packing.
+    #[arg(long)]
+    pub annotate: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -40,6 +46,7 @@ struct JsonTable<'b> {
 struct TableRow {
     #[serde(rename = "$id")]
     id: RowId,
+    /// Keeps column order (serde_json's `preserve_order` feature), so output is stable
     #[serde(flatten)]
     cells: Map<String, serde_json::Value>,
 }
@@ -53,32 +60,53 @@ struct ColumnSchema<'b> {
     flags: Vec<LegacyFlag<'b>>,
     #[serde(default, skip_serializing_if = "col_skip_count")]
     count: usize,
+    /// Whether the column is the primary key of a modern table. If no column has this set,
+    /// the first hash column is used when packing.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    primary_key: bool,
+    /// Unhashed column name, only written when annotating
+    #[serde(
+        rename = "_label_name",
+        skip_serializing_if = "Option::is_none",
+        skip_deserializing
+    )]
+    label_name: Option<String>,
 }
 
 fn col_skip_count(c: &usize) -> bool {
     *c <= 1
 }
 
-pub struct JsonConverter {
+pub struct JsonConverter<'h> {
     untyped: bool,
     pretty: bool,
+    annotations: Option<&'h HashNameTable>,
 }
 
 // For duplicate column mitigation
 type DuplicateColumnKey<'c> = (FixedVec<usize, MAX_DUPLICATE_COLUMNS>, CompatColumn<'c>);
 
-impl JsonConverter {
+impl<'h> JsonConverter<'h> {
     pub fn new(args: &ConvertArgs) -> Self {
         Self {
             untyped: args.untyped,
             pretty: args.json_opts.pretty,
+            annotations: None,
         }
     }
 
+    /// Annotates hashed column labels with their unhashed names, if found in the
+    /// given table.
+    pub fn with_annotations(mut self, hash_table: &'h HashNameTable) -> Self {
+        self.annotations = Some(hash_table);
+        self
+    }
+
     fn read_table_modern<'b>(&self, name: Label<'b>, table: JsonTable) -> Result<ModernTable<'b>> {
         let schema = table
             .schema
             .ok_or_else(|| FormatError::MissingTypeInfo.with_context(name.clone()))?;
+        let primary_key = schema.iter().position(|c| c.primary_key);
 
         let (columns, column_map, _): (Vec<ModernColumn>, HashMap<String, (usize, ValueType)>, _) =
             schema.into_iter().try_fold(
@@ -121,11 +149,14 @@ impl JsonConverter {
             })
             .collect::<Result<Vec<_>>>()?;
 
-        Ok(ModernTableBuilder::with_name(name)
+        let builder = ModernTableBuilder::with_name(name)
             .set_columns(columns)
             .set_rows(rows)
-            .set_base_id(base_id)
-            .build())
+            .set_base_id(base_id);
+        Ok(match primary_key {
+            Some(column) => builder.build_with_primary_key(Some(column)),
+            None => builder.build(),
+        })
     }
 
     fn read_table_legacy<'b>(
@@ -213,16 +244,26 @@ impl JsonConverter {
     }
 }
 
-impl BdatSerialize for JsonConverter {
+impl<'h> BdatSerialize for JsonConverter<'h> {
     fn write_table(&self, table: CompatTable, writer: &mut dyn Write) -> Result<()> {
+        let primary_key = match &table {
+            CompatTable::Modern(m) => m.primary_key(),
+            CompatTable::Legacy(_) => None,
+        };
         let schema = (!self.untyped).then(|| {
             table
                 .columns()
-                .map(|c| ColumnSchema {
+                .enumerate()
+                .map(|(i, c)| ColumnSchema {
                     name: c.label().to_string(),
                     ty: c.value_type(),
                     flags: c.flags().to_vec(),
                     count: c.count(),
+                    primary_key: primary_key == Some(i),
+                    label_name: match (c.label(), self.annotations) {
+                        (Label::Hash(hash), Some(names)) => names.unhash(hash).map(Into::into),
+                        _ => None,
+                    },
                 })
                 .collect::<Vec<_>>()
         });
@@ -261,12 +302,12 @@ impl BdatSerialize for JsonConverter {
         Ok(())
     }
 
-    fn get_file_name(&self, table_name: &str) -> String {
-        format!("{table_name}.json")
+    fn get_table_extension(&self) -> &'static str {
+        "json"
     }
 }
 
-impl BdatDeserialize for JsonConverter {
+impl<'h> BdatDeserialize for JsonConverter<'h> {
     fn read_table(
         &self,
         name: Label<'static>,
@@ -287,3 +328,81 @@ impl BdatDeserialize for JsonConverter {
         "json"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bdat::legacy::{LegacyColumnBuilder, LegacyFlag, LegacyRow, LegacyTableBuilder};
+    use bdat::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+    use bdat::{Cell, Label, Value, ValueType};
+
+    use super::{BdatSerialize, JsonConverter, JsonTable};
+
+    #[test]
+    fn primary_key_round_trip() {
+        let table = ModernTableBuilder::with_name(Label::Hash(1))
+            .add_column(ModernColumn::new(ValueType::HashRef, Label::Hash(2)))
+            .add_column(ModernColumn::new(ValueType::HashRef, Label::Hash(3)))
+            .add_row(ModernRow::new(vec![Value::HashRef(10), Value::HashRef(20)]))
+            .build_with_primary_key(Some(1));
+        let converter = JsonConverter {
+            untyped: false,
+            pretty: false,
+            annotations: None,
+        };
+        let mut out = Vec::new();
+        converter.write_table(table.into(), &mut out).unwrap();
+
+        let json: JsonTable = serde_json::from_slice(&out).unwrap();
+        let flags = json.schema.as_ref().unwrap().iter().map(|c| c.primary_key);
+        assert_eq!(vec![false, true], flags.collect::<Vec<_>>());
+        let table = converter.read_table_modern(Label::Hash(1), json).unwrap();
+        assert_eq!(Some(1), table.primary_key());
+        assert_eq!(
+            20,
+            table.row_by_hash(20).get(Label::Hash(3)).get_as::<u32>()
+        );
+    }
+
+    #[test]
+    fn stable_key_order() {
+        let table = LegacyTableBuilder::with_name("Test")
+            .add_column(LegacyColumnBuilder::new(
+                ValueType::UnsignedInt,
+                "Zeta".into(),
+            ))
+            .add_column(
+                LegacyColumnBuilder::new(ValueType::UnsignedByte, "Alpha".into())
+                    .set_flags(vec![
+                        LegacyFlag::new_bit("FlagZ", 0),
+                        LegacyFlag::new_bit("FlagA", 1),
+                    ])
+                    .build(),
+            )
+            .add_column(LegacyColumnBuilder::new(ValueType::Float, "Mid".into()))
+            .add_row(LegacyRow::new(vec![
+                Cell::Single(Value::UnsignedInt(1)),
+                Cell::Flags(vec![1, 0]),
+                Cell::Single(Value::Float(0.5.into())),
+            ]))
+            .build();
+        let converter = JsonConverter {
+            untyped: true,
+            pretty: false,
+            annotations: None,
+        };
+        let write = || {
+            let mut out = Vec::new();
+            converter
+                .write_table(table.clone().into(), &mut out)
+                .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let json = write();
+        assert_eq!(json, write());
+        assert_eq!(
+            r#"{"schema":null,"rows":[{"$id":1,"Zeta":1,"Alpha":{"FlagZ":1,"FlagA":0},"Mid":0.5}]}"#,
+            json
+        );
+    }
+}
@@ -1,14 +1,19 @@
 use std::{
-    ffi::OsStr,
     fs::File,
     io::{BufReader, BufWriter, Read, Write},
     path::Path,
+    sync::Mutex,
 };
 
 use anyhow::{Context, Result};
-use bdat::{compat::CompatTable, Label};
+use bdat::{
+    compat::CompatTable,
+    legacy::{LegacyRow, LegacyTableBuilder, LegacyWriteOptions},
+    modern::{ModernRow, ModernTableBuilder},
+    Label,
+};
 use clap::Args;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use indicatif::ProgressStyle;
 use rayon::prelude::*;
 
 use crate::{
@@ -23,10 +28,12 @@ use crate::{
 };
 
 use self::schema::{AsFileName, FileSchema};
+use self::template::NameTemplate;
 
 mod csv;
 mod json;
 mod schema;
+mod template;
 
 #[derive(Args)]
 pub struct ConvertArgs {
@@ -47,6 +54,26 @@ pub struct ConvertArgs {
     /// Only convert these tables. If absent, converts all tables from all files.
     #[arg(short, long)]
     tables: Vec<String>,
+    /// (Extract only) Only include these columns in the output. If absent, includes all
+    /// columns. Note: the extracted output cannot be repacked if this is set, as tables
+    /// would be incomplete
+    #[arg(short, long)]
+    columns: Vec<String>,
+    /// (Pack only) If this is set, tables are converted and serialized, but no files are written.
+    /// A summary with the table count and size of each file is printed instead.
+    #[arg(long)]
+    dry_run: bool,
+    /// (Pack only) Write tables in the order recorded in the schema, instead of sorting them by
+    /// name. Legacy games look up tables by name and expect them to be sorted, so only use this
+    /// to reproduce files that were not sorted to begin with. Has no effect on modern files.
+    #[arg(long)]
+    preserve_order: bool,
+    /// Template for the file names of converted tables. Supported placeholders are {table}
+    /// (the table name), {table_lower} (the table name in lowercase) and {ext} (the
+    /// extension for the file type). When packing, the same template used for extracting
+    /// must be passed.
+    #[arg(long, default_value = "{table}.{ext}", value_parser = NameTemplate::parse)]
+    name_template: NameTemplate,
 
     #[clap(flatten)]
     jobs: RayonPoolJobs,
@@ -64,8 +91,13 @@ pub trait BdatSerialize {
     /// Writes a converted BDAT table to a [`Write`] implementation.
     fn write_table(&self, table: CompatTable, writer: &mut dyn Write) -> Result<()>;
 
+    /// Returns the file extension used in converted table files
+    fn get_table_extension(&self) -> &'static str;
+
     /// Formats the file name for a converted BDAT table.
-    fn get_file_name(&self, table_name: &str) -> String;
+    fn get_file_name(&self, template: &NameTemplate, table_name: &str) -> String {
+        template.format(table_name, self.get_table_extension())
+    }
 }
 
 pub trait BdatDeserialize {
@@ -100,18 +132,24 @@ pub fn run_serialization(args: ConvertArgs, hash_table: HashNameTable) -> Result
     let out_dir = Path::new(&out_dir);
     std::fs::create_dir_all(out_dir).context("Could not create output directory")?;
 
-    let serializer: Box<dyn BdatSerialize + Send + Sync> = match args
+    // Annotations are only supported by the JSON format
+    let annotate = args.json_opts.annotate && args.file_type.as_deref() == Some("json");
+    let serializer: Box<dyn BdatSerialize + Send + Sync + '_> = match args
         .file_type
         .as_ref()
         .ok_or(Error::MissingRequiredArgument("file-type"))?
         .as_str()
     {
         "csv" => Box::new(csv::CsvConverter::new(&args)),
+        "json" if annotate => {
+            Box::new(json::JsonConverter::new(&args).with_annotations(&hash_table))
+        }
         "json" => Box::new(json::JsonConverter::new(&args)),
         t => return Err(Error::UnknownFileType(t.to_string()).into()),
     };
 
     let table_filter: Filter = args.tables.into_iter().map(FilterArg).collect();
+    let column_filter: Filter = args.columns.into_iter().map(FilterArg).collect();
 
     let files = args
         .input
@@ -120,58 +158,77 @@ pub fn run_serialization(args: ConvertArgs, hash_table: HashNameTable) -> Result
         .collect::<walkdir::Result<Vec<_>>>()?;
     let base_path = crate::util::get_common_denominator(&files);
 
-    let multi_bar = MultiProgress::new();
-    let file_bar = multi_bar
-        .add(ProgressBar::new(files.len() as u64).with_style(build_progress_style("Files", true)));
-    let table_bar_style = build_progress_style("Tables", false);
+    let progress_bar = ProgressBarState::new("Files", "Tables", files.len(), args.input.quiet);
+    // Held while a schema file is being read and written back, so files that share
+    // the same schema can be merged safely.
+    let schema_lock = Mutex::new(());
 
     let res = files
         .into_par_iter()
         .panic_fuse()
         .map(|path| {
-            let mut file = std::fs::read(&path)?;
+            let mut file = crate::util::compress::read_file(&path)?;
             let game = args.input.game_from_bytes(&file)?;
             let tables = game.from_bytes(&mut file).with_context(|| {
                 format!("Could not parse BDAT tables ({})", path.to_string_lossy())
             })?;
 
-            let file_name = path
-                .file_stem()
-                .and_then(OsStr::to_str)
+            let file_name = crate::util::compress::file_stem(&path)
                 .map(ToString::to_string)
                 .unwrap();
 
-            file_bar.inc(0);
-            let table_bar = multi_bar
-                .add(ProgressBar::new(tables.len() as u64).with_style(table_bar_style.clone()));
+            progress_bar.master_bar.inc(0);
+            let table_bar = progress_bar.add_child(tables.len());
 
-            let out_dir = out_dir.join(
-                path.strip_prefix(&base_path)
-                    .unwrap()
-                    .parent()
-                    .unwrap_or_else(|| Path::new("")),
-            );
+            let relative_path = path.strip_prefix(&base_path).unwrap();
+            let out_dir = out_dir.join(relative_path.parent().unwrap_or_else(|| Path::new("")));
             let tables_dir = out_dir.join(&file_name);
             std::fs::create_dir_all(&tables_dir)?;
 
-            let mut schema = (!args.no_schema).then(|| FileSchema::new(file_name, game.into()));
-
-            for mut table in tables {
-                hash_table.convert_all(&mut table);
+            let mut schema = (!args.no_schema).then(|| {
+                // With a single input file, the base path is the file itself
+                let source = match relative_path.as_os_str().is_empty() {
+                    true => Path::new(path.file_name().unwrap()),
+                    false => relative_path,
+                };
+                let mut schema = FileSchema::new(file_name, game.into(), source);
+                schema.partial = !column_filter.is_empty();
+                schema
+            });
+
+            let mut tables = tables;
+            for table in &mut tables {
+                // When annotating, hashes are kept and unhashed names are added separately
+                if !annotate {
+                    hash_table.convert_all(table);
+                }
 
                 if let Some(schema) = &mut schema {
-                    schema.feed_table(&table);
+                    schema.feed_table(table);
                 }
+            }
 
-                let name = table.name();
+            // Write the schema first, so conflicting tables are not overwritten
+            if let Some(schema) = schema {
+                schema.write(out_dir, &schema_lock)?;
+            }
+
+            for table in tables {
+                let name = table.name().into_owned();
                 if !table_filter.contains(&name) {
                     continue;
                 }
+                let table = match column_filter.is_empty() {
+                    true => table,
+                    false => filter_columns(table, &column_filter),
+                };
 
                 // {:+} displays hashed names without brackets (<>)
-                let out_file =
-                    File::create(tables_dir.join(serializer.get_file_name(&name.as_file_name())))
-                        .context("Could not create output file")?;
+                let out_file = File::create(
+                    tables_dir
+                        .join(serializer.get_file_name(&args.name_template, &name.as_file_name())),
+                )
+                .context("Could not create output file")?;
                 let mut writer = BufWriter::new(out_file);
                 serializer
                     .write_table(table, &mut writer)
@@ -181,12 +238,8 @@ pub fn run_serialization(args: ConvertArgs, hash_table: HashNameTable) -> Result
                 table_bar.inc(1);
             }
 
-            if let Some(schema) = schema {
-                schema.write(out_dir)?;
-            }
-
-            file_bar.inc(1);
-            multi_bar.remove(&table_bar);
+            progress_bar.master_bar.inc(1);
+            progress_bar.remove_child(&table_bar);
 
             Ok(())
         })
@@ -196,7 +249,7 @@ pub fn run_serialization(args: ConvertArgs, hash_table: HashNameTable) -> Result
         r?;
     }
 
-    file_bar.finish();
+    progress_bar.finish();
 
     Ok(())
 }
@@ -212,12 +265,18 @@ fn run_deserialization(args: ConvertArgs) -> Result<()> {
     }
     let base_path = crate::util::get_common_denominator(&schema_files);
 
-    let out_dir = args
-        .out_dir
-        .as_ref()
-        .ok_or(Error::MissingRequiredArgument("out-dir"))?;
-    let out_dir = Path::new(&out_dir);
-    std::fs::create_dir_all(out_dir).context("Could not create output directory")?;
+    let out_dir = match args.dry_run {
+        true => None,
+        false => {
+            let out_dir = args
+                .out_dir
+                .as_ref()
+                .ok_or(Error::MissingRequiredArgument("out-dir"))?;
+            let out_dir = Path::new(out_dir);
+            std::fs::create_dir_all(out_dir).context("Could not create output directory")?;
+            Some(out_dir)
+        }
+    };
 
     let deserializer: Box<dyn BdatDeserialize + Send + Sync> = match args
         .file_type
@@ -229,7 +288,8 @@ fn run_deserialization(args: ConvertArgs) -> Result<()> {
         t => return Err(Error::UnknownFileType(t.to_string()).into()),
     };
 
-    let progress_bar = ProgressBarState::new("Files", "Tables", schema_files.len());
+    let progress_bar =
+        ProgressBarState::new("Files", "Tables", schema_files.len(), args.input.quiet);
 
     progress_bar.master_bar.inc(0);
     let res = schema_files
@@ -237,6 +297,9 @@ fn run_deserialization(args: ConvertArgs) -> Result<()> {
         .panic_fuse()
         .map(|schema_path| {
             let schema_file = FileSchema::read(File::open(&schema_path)?)?;
+            if schema_file.partial {
+                return Err(Error::from(SchemaError::Partial(schema_file.file_name)).into());
+            }
 
             // The relative path to the tables (we mimic the original file structure in the output)
             let relative_path = schema_path
@@ -251,6 +314,7 @@ fn run_deserialization(args: ConvertArgs) -> Result<()> {
             let tables = schema_file
                 .find_table_files(
                     &schema_path.parent().unwrap().join(&schema_file.file_name),
+                    &args.name_template,
                     deserializer.get_table_extension(),
                 )
                 .into_par_iter()
@@ -277,14 +341,33 @@ fn run_deserialization(args: ConvertArgs) -> Result<()> {
 
             progress_bar.remove_child(&table_bar);
 
-            let out_dir = out_dir.join(relative_path);
-            std::fs::create_dir_all(&out_dir)?;
-            let out_file = File::create(out_dir.join(format!("{}.bdat", schema_file.file_name)))?;
             let game = args
                 .input
                 .game
                 .unwrap_or_else(|| BdatGame::version_default(schema_file.version));
-            game.to_writer(out_file, tables)?;
+            let file_name = format!("{}.bdat", schema_file.file_name);
+            // Tables are in schema order, which is the order they were extracted in
+            let write_opts = LegacyWriteOptions::new().preserve_order(args.preserve_order);
+
+            match out_dir {
+                Some(out_dir) => {
+                    let out_dir = out_dir.join(relative_path);
+                    std::fs::create_dir_all(&out_dir)?;
+                    let out_file = File::create(out_dir.join(file_name))?;
+                    game.to_writer(out_file, tables, write_opts)?;
+                }
+                None => {
+                    let table_count = tables.len();
+                    let bytes = game.to_vec(tables, write_opts).with_context(|| {
+                        format!("Could not serialize tables ({})", schema_path.display())
+                    })?;
+                    progress_bar.print_output(format!(
+                        "{}: {table_count} tables, {} bytes",
+                        relative_path.join(file_name).display(),
+                        bytes.len()
+                    ));
+                }
+            }
             progress_bar.master_bar.inc(1);
             Ok(())
         })
@@ -298,6 +381,52 @@ fn run_deserialization(args: ConvertArgs) -> Result<()> {
     Ok(())
 }
 
+/// Removes the columns that don't match the filter, along with their cells.
+fn filter_columns<'b>(table: CompatTable<'b>, filter: &Filter) -> CompatTable<'b> {
+    let keep = table
+        .columns()
+        .map(|c| filter.contains(&c.label()))
+        .collect::<Vec<_>>();
+    fn retain<T>(items: impl IntoIterator<Item = T>, keep: &[bool]) -> Vec<T> {
+        items
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(item, &keep)| keep.then_some(item))
+            .collect()
+    }
+
+    match table {
+        CompatTable::Modern(table) => {
+            // Primary key index in the filtered columns
+            let primary_key = table
+                .primary_key()
+                .filter(|&i| keep[i])
+                .map(|i| keep[..i].iter().filter(|&&k| k).count());
+            let builder = ModernTableBuilder::with_name(table.name().clone().into_owned())
+                .set_base_id(table.base_id())
+                .set_columns(retain(table.columns().cloned(), &keep));
+            let rows = table
+                .into_rows()
+                .map(|row| ModernRow::new(retain(row.into_values(), &keep)))
+                .collect();
+            builder
+                .set_rows(rows)
+                .build_with_primary_key(primary_key)
+                .into()
+        }
+        CompatTable::Legacy(table) => {
+            let builder = LegacyTableBuilder::with_name(table.name().to_string())
+                .set_base_id(table.base_id())
+                .set_columns(retain(table.columns().cloned(), &keep));
+            let rows = table
+                .into_rows()
+                .map(|row| LegacyRow::new(retain(row.into_cells(), &keep)))
+                .collect();
+            builder.set_rows(rows).build().into()
+        }
+    }
+}
+
 pub fn build_progress_style(label: &str, with_time: bool) -> ProgressStyle {
     ProgressStyle::with_template(&match with_time {
         true => format!("{{spinner:.cyan}} [{{elapsed_precise:.cyan}}] {label}{{msg}}: {{human_pos}}/{{human_len}} ({{percent}}%) [{{bar:.cyan/blue}}] ETA: {{eta}}"),
@@ -305,3 +434,46 @@ pub fn build_progress_style(label: &str, with_time: bool) -> ProgressStyle {
     })
     .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use bdat::modern::{ModernColumn, ModernRow, ModernTableBuilder};
+    use bdat::{compat::CompatTable, Label, Value, ValueType};
+
+    use crate::filter::{Filter, FilterArg};
+
+    #[test]
+    fn filter_columns() {
+        let table: CompatTable = ModernTableBuilder::with_name(Label::Hash(1))
+            .add_column(ModernColumn::new(ValueType::UnsignedInt, "Hp".into()))
+            .add_column(ModernColumn::new(ValueType::HashRef, "Id".into()))
+            .add_column(ModernColumn::new(ValueType::String, "Name".into()))
+            .add_row(ModernRow::new(vec![
+                Value::UnsignedInt(100),
+                Value::HashRef(0xDEADBEEF),
+                Value::String("Noah".into()),
+            ]))
+            .build()
+            .into();
+        let filter: Filter = ["Name", "Id"]
+            .map(|s| FilterArg(s.to_string()))
+            .into_iter()
+            .collect();
+
+        let CompatTable::Modern(table) = super::filter_columns(table, &filter) else {
+            panic!("expected modern table");
+        };
+        assert_eq!(
+            vec![Label::from("Id"), Label::from("Name")],
+            table
+                .columns()
+                .map(|c| c.label().clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(0), table.primary_key());
+        assert_eq!(
+            vec![&Value::HashRef(0xDEADBEEF), &Value::String("Noah".into())],
+            table.row(1).values().collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,10 +1,12 @@
 use std::{
     borrow::Cow,
-    fs::OpenOptions,
-    io::{BufWriter, Read},
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, Read},
     path::{Path, PathBuf},
+    sync::Mutex,
 };
 
+use super::template::NameTemplate;
 use crate::error::{Error, SchemaError};
 use bdat::{compat::CompatTable, BdatVersion, Label, Utf};
 use serde::{Deserialize, Serialize};
@@ -22,6 +24,23 @@ pub struct FileSchema {
     pub version: BdatVersion,
     #[serde(default)]
     pub format_version: usize,
+    /// The BDAT files the tables were extracted from. Empty for schemas written by older
+    /// versions.
+    #[serde(default)]
+    sources: Vec<SchemaSource>,
+    /// Whether tables were extracted with only some of their columns. Partial schemas
+    /// can't be used to pack tables.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+    tables: Vec<String>,
+}
+
+/// A BDAT file that contributed tables to a schema.
+#[derive(Serialize, Deserialize, Clone)]
+struct SchemaSource {
+    /// Path of the file, relative to the input directory
+    path: String,
+    /// Names of the tables extracted from this file
     tables: Vec<String>,
 }
 
@@ -30,11 +49,23 @@ pub trait AsFileName {
 }
 
 impl FileSchema {
-    pub fn new(file_name: String, version: BdatVersion) -> Self {
+    /// Creates a new schema. The source path should be relative to the input directory,
+    /// so the same input is recognized no matter how its path was given.
+    pub fn new(file_name: String, version: BdatVersion, source: &Path) -> Self {
+        let path = source
+            .iter()
+            .map(|c| c.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
         Self {
             file_name,
             version,
             format_version: FORMAT_VERSION,
+            sources: vec![SchemaSource {
+                path,
+                tables: Vec::new(),
+            }],
+            partial: false,
             tables: Vec::new(),
         }
     }
@@ -54,12 +85,21 @@ impl FileSchema {
 
     /// Registers a table in the file schema
     pub fn feed_table(&mut self, table: &CompatTable) {
-        self.tables.push(table.name().to_string());
+        let name = table.name().to_string();
+        if let Some(source) = self.sources.first_mut() {
+            source.tables.push(name.clone());
+        }
+        self.tables.push(name);
     }
 
     /// Attempts to find all deserialized table files, from the paths defined by the
     /// file schema.
-    pub fn find_table_files(&self, base_dir: &Path, extension: &str) -> Vec<(Label, PathBuf)> {
+    pub fn find_table_files(
+        &self,
+        base_dir: &Path,
+        template: &NameTemplate,
+        extension: &str,
+    ) -> Vec<(Label, PathBuf)> {
         let mut files = Vec::with_capacity(self.tables.len());
 
         for label in self
@@ -68,7 +108,7 @@ impl FileSchema {
             .chain(std::iter::once(&self.file_name.clone()))
         {
             let label = Label::parse(label.clone(), false);
-            let path = base_dir.join(format!("{}.{extension}", label.as_file_name()));
+            let path = base_dir.join(template.format(&label.as_file_name(), extension));
             if path.is_file() {
                 files.push((label, path));
             }
@@ -83,20 +123,73 @@ impl FileSchema {
     }
 
     /// Writes the file schema to a file.
-    pub fn write(&self, base_dir: impl AsRef<Path>) -> anyhow::Result<()> {
+    ///
+    /// If a schema for a different source file already exists at the target path, the two
+    /// schemas are merged, provided they share no tables. Otherwise,
+    /// [`SchemaError::Conflict`] is returned. If the existing schema also lists this
+    /// schema's source, only the tables from that source are replaced. Schemas written by
+    /// older versions don't list their sources, and are overwritten.
+    ///
+    /// The lock must be shared by all schema writes in the same output directory.
+    pub fn write(&self, base_dir: impl AsRef<Path>, lock: &Mutex<()>) -> anyhow::Result<()> {
+        let path = base_dir
+            .as_ref()
+            .join(format!("{}.bschema", self.file_name));
+        let _lock = lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let merged = match path.is_file() {
+            true => {
+                let existing = Self::read(BufReader::new(File::open(&path)?))?;
+                self.merge_into(existing).map_err(Error::from)?
+            }
+            false => None,
+        };
+
         let file = OpenOptions::new()
             .create(true)
             .write(true)
             .truncate(true)
-            .open(
-                base_dir
-                    .as_ref()
-                    .join(format!("{}.bschema", self.file_name)),
-            )?;
+            .open(path)?;
         let writer = BufWriter::new(file);
-        serde_json::to_writer(writer, self).unwrap();
+        serde_json::to_writer(writer, merged.as_ref().unwrap_or(self)).unwrap();
         Ok(())
     }
+
+    /// Merges this schema into an existing one for the same file. Returns `None` if the
+    /// existing schema should be overwritten instead.
+    fn merge_into(&self, mut existing: Self) -> Result<Option<Self>, SchemaError> {
+        // Re-extracting a source replaces its tables, keeping those from other sources
+        for source in &self.sources {
+            if let Some(pos) = existing.sources.iter().position(|s| s.path == source.path) {
+                let old = existing.sources.remove(pos);
+                existing.tables.retain(|t| !old.tables.contains(t));
+            }
+        }
+        if existing.sources.is_empty() {
+            return Ok(None);
+        }
+        if existing.version != self.version
+            || existing.tables.iter().any(|t| self.tables.contains(t))
+        {
+            return Err(SchemaError::Conflict(Box::new((
+                self.file_name.clone(),
+                existing.source_paths(),
+                self.source_paths(),
+            ))));
+        }
+        existing.sources.extend_from_slice(&self.sources);
+        existing.partial |= self.partial;
+        existing.tables.extend_from_slice(&self.tables);
+        Ok(Some(existing))
+    }
+
+    fn source_paths(&self) -> String {
+        self.sources
+            .iter()
+            .map(|s| s.path.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
 
 impl<'b> AsFileName for Label<'b> {
@@ -108,3 +201,70 @@ impl<'b> AsFileName for Label<'b> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use bdat::{BdatVersion, LegacyVersion};
+
+    use super::FileSchema;
+
+    fn schema(source: &str, tables: &[&str]) -> FileSchema {
+        let mut schema = FileSchema::new(
+            "file".to_string(),
+            BdatVersion::Legacy(LegacyVersion::Switch),
+            Path::new(source),
+        );
+        schema.tables = tables.iter().map(ToString::to_string).collect();
+        schema.sources[0].tables = schema.tables.clone();
+        schema
+    }
+
+    #[test]
+    fn merge_schemas() {
+        let merged = schema("b/file.bdat", &["B"])
+            .merge_into(schema("a/file.bdat", &["A"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(merged.source_paths(), "a/file.bdat, b/file.bdat");
+        assert_eq!(merged.tables, ["A", "B"]);
+
+        // Same source, overwrite
+        assert!(schema("a/file.bdat", &["A"])
+            .merge_into(schema("a/file.bdat", &["A", "B"]))
+            .unwrap()
+            .is_none());
+
+        // Same source as part of a merged schema, only replace its tables
+        let merged = schema("a/file.bdat", &["A2"])
+            .merge_into(merged)
+            .unwrap()
+            .unwrap();
+        assert_eq!(merged.source_paths(), "b/file.bdat, a/file.bdat");
+        assert_eq!(merged.tables, ["B", "A2"]);
+
+        // Schema from an older version, without sources
+        let mut old = schema("a/file.bdat", &["A"]);
+        old.sources.clear();
+        assert!(schema("b/file.bdat", &["A"])
+            .merge_into(old)
+            .unwrap()
+            .is_none());
+
+        // Overlapping tables
+        assert!(schema("b/file.bdat", &["A"])
+            .merge_into(schema("a/file.bdat", &["A"]))
+            .is_err());
+    }
+
+    #[test]
+    fn source_path() {
+        let schema = FileSchema::new(
+            "file".to_string(),
+            BdatVersion::Modern,
+            &Path::new("a").join("b").join("file.bdat"),
+        );
+        assert_eq!(schema.source_paths(), "a/b/file.bdat");
+    }
+}
@@ -0,0 +1,106 @@
+use crate::error::Error;
+
+/// Placeholders that can be used in a [`NameTemplate`]
+const PLACEHOLDERS: &[&str] = &["table", "table_lower", "ext"];
+
+/// A template for the file names of converted tables, e.g. `{table}.{ext}`.
+///
+/// Supported placeholders:
+/// * `{table}`: the table name
+/// * `{table_lower}`: the table name, in lowercase
+/// * `{ext}`: the default extension for the file type
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameTemplate(String);
+
+impl NameTemplate {
+    /// Parses a template, checking that it only uses known placeholders and that it
+    /// includes the table name.
+    pub fn parse(template: &str) -> Result<Self, Error> {
+        let invalid =
+            |reason: &str| Error::InvalidNameTemplate(template.to_string(), reason.into());
+        if template.contains(['/', '\\']) {
+            return Err(invalid("path separators are not allowed"));
+        }
+        let mut has_table = false;
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| invalid("unclosed '{'"))?;
+            let name = &rest[start + 1..start + end];
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(invalid(&format!("unknown placeholder {{{name}}}")));
+            }
+            has_table |= name.starts_with("table");
+            rest = &rest[start + end + 1..];
+        }
+        if rest.contains('}') {
+            return Err(invalid("unmatched '}'"));
+        }
+        if !has_table {
+            return Err(invalid("{table} or {table_lower} is required"));
+        }
+        Ok(Self(template.to_string()))
+    }
+
+    /// Formats the file name for a table.
+    pub fn format(&self, table_name: &str, extension: &str) -> String {
+        let mut out = String::with_capacity(self.0.len() + table_name.len());
+        let mut rest = self.0.as_str();
+        // Placeholders were validated in parse
+        while let Some(start) = rest.find('{') {
+            let end = start + rest[start..].find('}').unwrap();
+            out.push_str(&rest[..start]);
+            match &rest[start + 1..end] {
+                "table" => out.push_str(table_name),
+                "table_lower" => out.push_str(&table_name.to_lowercase()),
+                _ => out.push_str(extension),
+            }
+            rest = &rest[end + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+impl Default for NameTemplate {
+    fn default() -> Self {
+        Self("{table}.{ext}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NameTemplate;
+
+    #[test]
+    fn format() {
+        assert_eq!(
+            "Table1.json",
+            NameTemplate::default().format("Table1", "json")
+        );
+        let template = NameTemplate::parse("{table_lower}_{table}.txt").unwrap();
+        assert_eq!("table1_Table1.txt", template.format("Table1", "csv"));
+        // Placeholders in the table name are not expanded
+        assert_eq!(
+            "{ext}.csv",
+            NameTemplate::parse("{table}.{ext}")
+                .unwrap()
+                .format("{ext}", "csv")
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for template in [
+            "{ext}",
+            "{table}.{xt}",
+            "{table",
+            "table}",
+            "{table}}",
+            "a/{table}",
+        ] {
+            assert!(NameTemplate::parse(template).is_err(), "{template}");
+        }
+    }
+}
@@ -1,14 +1,13 @@
 use std::{
     borrow::Cow,
     collections::BTreeMap,
-    fs::File,
-    io::BufReader,
+    io::Cursor,
     path::{Path, PathBuf},
 };
 
 use anyhow::Result;
 use clap::Args;
-use indicatif::ProgressBar;
+use indicatif::{ProgressBar, ProgressDrawTarget};
 use itertools::Itertools;
 use rayon::{iter::Either, prelude::*};
 
@@ -30,6 +29,9 @@ pub struct DiffArgs {
     /// Don't print file names.
     #[arg(long)]
     no_file_names: bool,
+    /// Print a summary of how many tables and rows were changed.
+    #[arg(long)]
+    stats: bool,
 
     #[clap(flatten)]
     input: InputData,
@@ -47,10 +49,24 @@ struct PathDiff<'p> {
     new: &'p Path,
 }
 
-struct RowDiff<'t, 'tb> {
-    row_id: RowId,
+/// Column information shared by all row diffs of a table pair.
+struct TableDiff<'t, 'tb> {
     old: &'t CompatTable<'tb>,
     new: &'t CompatTable<'tb>,
+    /// Whether both tables have the same columns, in which case rows can be compared by
+    /// their content hash first.
+    same_schema: bool,
+    /// Columns present in both tables
+    common_cols: Vec<Label<'t>>,
+    /// Columns only present in the new table
+    added_cols: Vec<Label<'t>>,
+    /// Columns only present in the old table
+    removed_cols: Vec<Label<'t>>,
+}
+
+struct RowDiff<'d, 't, 'tb> {
+    row_id: RowId,
+    table: &'d TableDiff<'t, 'tb>,
 }
 
 struct RowChanges<'a, 'tb> {
@@ -60,6 +76,15 @@ struct RowChanges<'a, 'tb> {
     changes: Vec<ColumnChange<'a, 'tb>>,
 }
 
+/// Counts of changed items, printed with `--stats`.
+#[derive(Default, Debug, PartialEq)]
+struct DiffStats {
+    tables_added: usize,
+    tables_removed: usize,
+    tables_changed: usize,
+    rows_changed: usize,
+}
+
 struct ColumnChange<'a, 'tb> {
     label: Label<'a>,
     added: bool,
@@ -70,6 +95,9 @@ pub fn run_diff(args: DiffArgs) -> Result<()> {
     let progress = ProgressBar::new(3)
         .with_style(crate::convert::build_progress_style("Diff", true))
         .with_message(" (Reading files)");
+    if args.input.quiet {
+        progress.set_draw_target(ProgressDrawTarget::hidden());
+    }
     let new_files = args
         .input
         .list_files(BdatFileFilter, !args.no_file_names)?
@@ -96,7 +124,7 @@ pub fn run_diff(args: DiffArgs) -> Result<()> {
     let (old_tables, new_tables) = files_to_read
         .par_iter()
         .flat_map(|(file, new)| {
-            let reader = BufReader::new(File::open(file)?);
+            let reader = Cursor::new(crate::util::compress::read_file(file)?);
             let mut tables = bdat::from_reader(reader).and_then(|mut f| {
                 Ok(f.get_tables()?
                     .into_iter()
@@ -144,8 +172,11 @@ pub fn run_diff(args: DiffArgs) -> Result<()> {
     progress.inc(1);
     progress.set_message(" (Processing result)");
 
+    let mut stats = DiffStats::default();
+
     println!("------------\nAdded Tables\n------------");
     added.for_each(|table| {
+        stats.tables_added += 1;
         if args.no_file_names {
             println!("+ Table \"{}\"", table.table.name());
         } else {
@@ -163,6 +194,7 @@ pub fn run_diff(args: DiffArgs) -> Result<()> {
 
     println!("\n--------------\nRemoved Tables\n--------------");
     removed.for_each(|table| {
+        stats.tables_removed += 1;
         if args.no_file_names {
             println!("- Table \"{}\"", table.table.name());
         } else {
@@ -185,15 +217,15 @@ pub fn run_diff(args: DiffArgs) -> Result<()> {
             None => continue,
         };
 
+        let table_diff = TableDiff::new(&table.table, &new_table.table);
         let row_changes = new_table
             .table
             .rows()
-            .flat_map(|new_row| {
-                let id = new_row.id();
-                RowDiff::new(&table.table, &new_table.table, id).diff()
-            })
+            .flat_map(|new_row| RowDiff::new(&table_diff, new_row.id()).diff())
             .collect_vec();
         if !row_changes.is_empty() {
+            stats.tables_changed += 1;
+            stats.rows_changed += row_changes.len();
             let path_diff = table.get_path_diff(new_table);
             let path_diff = path_diff.to_distinguishable();
             if args.no_file_names {
@@ -211,46 +243,76 @@ pub fn run_diff(args: DiffArgs) -> Result<()> {
         }
     }
 
+    if args.stats {
+        println!("\n{stats}");
+    }
+
     Ok(())
 }
 
-impl<'t, 'tb> RowDiff<'t, 'tb> {
-    fn new(old: &'t CompatTable<'tb>, new: &'t CompatTable<'tb>, row_id: RowId) -> Self {
-        Self { row_id, old, new }
+impl<'t, 'tb> TableDiff<'t, 'tb> {
+    fn new(old: &'t CompatTable<'tb>, new: &'t CompatTable<'tb>) -> Self {
+        let old_cols: MurmurHashSet<_> = old.columns().map(|col| col.label()).collect();
+        let new_cols: MurmurHashSet<_> = new.columns().map(|col| col.label()).collect();
+        Self {
+            old,
+            new,
+            same_schema: old.schema_fingerprint() == new.schema_fingerprint(),
+            common_cols: old_cols.intersection(&new_cols).cloned().collect(),
+            added_cols: new_cols.difference(&old_cols).cloned().collect(),
+            removed_cols: old_cols.difference(&new_cols).cloned().collect(),
+        }
+    }
+}
+
+impl<'d, 't, 'tb> RowDiff<'d, 't, 'tb> {
+    fn new(table: &'d TableDiff<'t, 'tb>, row_id: RowId) -> Self {
+        Self { row_id, table }
     }
 
     fn diff(self) -> Option<RowChanges<'t, 'tb>> {
-        let (old, new) = (self.old.get_row(self.row_id), self.new.get_row(self.row_id));
+        let table = self.table;
+        let (old, new) = (
+            table.old.get_row(self.row_id),
+            table.new.get_row(self.row_id),
+        );
+
+        if table.same_schema {
+            if let (Some(old_row), Some(new_row)) = (&old, &new) {
+                if let (CompatRef::Modern(old_row), CompatRef::Modern(new_row)) =
+                    (**old_row, **new_row)
+                {
+                    if old_row.content_hash() == new_row.content_hash() {
+                        return None;
+                    }
+                }
+            }
+        }
 
         let changed_cols: Vec<_> =
             match (old, new) {
-                (None, Some(new_row)) => self
+                (None, Some(new_row)) => table
                     .new
                     .columns()
                     .map(|col| (col.label(), true, new_row.get(col.label())).into())
                     .collect(),
-                (Some(old_row), None) => self
+                (Some(old_row), None) => table
                     .old
                     .columns()
                     .map(|col| (col.label(), false, old_row.get(col.label())).into())
                     .collect(),
                 (Some(old_row), Some(new_row)) => {
-                    let (old_table, new_table) = (self.old, self.new);
-                    let old_cols: MurmurHashSet<_> =
-                        old_table.columns().map(|col| col.label()).collect();
-                    let new_cols: MurmurHashSet<_> =
-                        new_table.columns().map(|col| col.label()).collect();
-
-                    let changed_cols = old_cols.intersection(&new_cols).filter_map(|col| {
+                    let changed_cols = table.common_cols.iter().filter_map(|col| {
                         let old_value = old_row.get_if_present(col)?;
                         let new_value = new_row.get_if_present(col)?;
                         (old_value != new_value).then_some((col, old_value, new_value))
                     });
 
-                    new_cols
-                        .difference(&old_cols)
+                    table
+                        .added_cols
+                        .iter()
                         .map(|label| (label.clone(), true, new_row.get(label.as_ref())).into())
-                        .chain(old_cols.difference(&new_cols).map(|label| {
+                        .chain(table.removed_cols.iter().map(|label| {
                             (label.clone(), false, old_row.get(label.as_ref())).into()
                         }))
                         .chain(changed_cols.flat_map(|(label, old_val, new_val)| {
@@ -377,3 +439,32 @@ impl<'a, 'tb> From<(Label<'a>, bool, Cell<'tb>)> for ColumnChange<'a, 'tb> {
         }
     }
 }
+
+impl std::fmt::Display for DiffStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} tables added, {} removed, {} changed, {} rows changed",
+            self.tables_added, self.tables_removed, self.tables_changed, self.rows_changed
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiffStats;
+
+    #[test]
+    fn stats_summary() {
+        let stats = DiffStats {
+            tables_added: 1,
+            tables_removed: 2,
+            tables_changed: 3,
+            rows_changed: 40,
+        };
+        assert_eq!(
+            stats.to_string(),
+            "1 tables added, 2 removed, 3 changed, 40 rows changed"
+        );
+    }
+}
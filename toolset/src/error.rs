@@ -13,6 +13,8 @@ pub enum Error {
     MissingRequiredArgument(&'static str),
     #[error("Unsupported file type '{0}'")]
     UnknownFileType(String),
+    #[error("Invalid name template '{0}': {1}")]
+    InvalidNameTemplate(String, String),
     #[error("Not a legacy BDAT file")]
     NotLegacy,
     #[error("Not a modern BDAT file")]
@@ -32,6 +34,16 @@ pub enum SchemaError {
         Please update or run 'extract' again without '--no-schema'", _0.0, _0.1, _0.2
     )]
     UnsupportedSchema(Box<(String, usize, &'static [usize])>),
+    #[error(
+        "Schema for file '{}' already exists with tables from {}, which conflict with \
+        tables from {}. Please extract to a different directory", _0.0, _0.1, _0.2
+    )]
+    Conflict(Box<(String, String, String)>),
+    #[error(
+        "Schema for file '{0}' was extracted with '--columns', so its tables are incomplete. \
+        Please run 'extract' again without '--columns'"
+    )]
+    Partial(String),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -21,6 +21,11 @@ pub struct BdatFileFilter;
 pub struct SchemaFileFilter;
 
 impl Filter {
+    /// Returns whether the filter accepts all labels.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
     pub fn contains(&self, label: &Label) -> bool {
         if self.hashes.is_empty() {
             return true;
@@ -45,6 +50,10 @@ impl FileFilter for BdatFileFilter {
             // version detection
             return true;
         }
+        if crate::util::compress::is_compressed(extension) {
+            return crate::util::compress::read_file(path)
+                .is_ok_and(|data| bdat::detect_bytes_version(&data).is_ok());
+        }
         // Accept non-".bdat" files that actually appear to be BDAT files
         File::open(path)
             .map_err(|_| ())
@@ -0,0 +1,56 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use bdat::compat::{CompatRef, CompatTable};
+use bdat::Label;
+use clap::Args;
+
+use crate::{filter::BdatFileFilter, InputData};
+
+#[derive(Args)]
+pub struct HashDumpArgs {
+    /// Only print hashes that are not in the names file (see --hashes).
+    #[arg(long)]
+    unresolved: bool,
+
+    #[clap(flatten)]
+    input: InputData,
+}
+
+pub fn run(args: HashDumpArgs) -> Result<()> {
+    let hash_table = args.input.load_hashes()?;
+    let mut hashes = BTreeSet::new();
+
+    for file in args.input.list_files(BdatFileFilter, false)? {
+        let path = file?;
+        let mut file = crate::util::compress::read_file(&path)?;
+        let tables = args
+            .input
+            .game_from_bytes(&file)?
+            .from_bytes(&mut file)
+            .with_context(|| format!("Could not parse BDAT tables ({})", path.to_string_lossy()))?;
+        for table in &tables {
+            collect_hashes(table, &mut hashes);
+        }
+    }
+
+    for hash in hashes {
+        match hash_table.unhash(hash) {
+            Some(_) if args.unresolved => {}
+            Some(name) => println!("{} = {name}", Label::Hash(hash)),
+            None => println!("{}", Label::Hash(hash)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects hashed table names, column labels and row ID hashes.
+fn collect_hashes(table: &CompatTable, hashes: &mut BTreeSet<u32>) {
+    hashes.extend(table.name().as_hash());
+    hashes.extend(table.columns().filter_map(|c| c.label().as_hash()));
+    hashes.extend(table.rows().filter_map(|row| match *row {
+        CompatRef::Modern(row) => row.id_hash(),
+        CompatRef::Legacy(_) => None,
+    }));
+}
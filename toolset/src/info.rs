@@ -4,9 +4,9 @@ use crate::{
     InputData,
 };
 use anyhow::{Context, Result};
-use bdat::Label;
+use bdat::{compat::CompatTable, Cell, Label, Value};
 use clap::Args;
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashSet, fmt::Display};
 
 #[derive(Args)]
 pub struct InfoArgs {
@@ -16,6 +16,11 @@ pub struct InfoArgs {
     /// Only print these columns. If absent, prints all columns.
     #[arg(short, long)]
     columns: Vec<String>,
+    /// Also print statistics for each column: min/max for numeric columns, the number of
+    /// distinct values and the number of empty strings. This is slower, as it goes through
+    /// every row.
+    #[arg(long)]
+    column_stats: bool,
 
     #[clap(flatten)]
     input: InputData,
@@ -28,7 +33,7 @@ pub fn get_info(args: InfoArgs) -> Result<()> {
 
     for file in args.input.list_files(BdatFileFilter, false)? {
         let path = file?;
-        let mut file = std::fs::read(&path)?;
+        let mut file = crate::util::compress::read_file(&path)?;
         let tables = args
             .input
             .game_from_bytes(&file)?
@@ -47,13 +52,23 @@ pub fn get_info(args: InfoArgs) -> Result<()> {
                 table.column_count(),
                 table.row_count()
             );
+            if let CompatTable::Modern(modern) = &table {
+                if modern.reserved() != [0; 4] {
+                    println!(
+                        "  [Warn] Unknown header value at 0x14 is not zero: {:02X?}",
+                        modern.reserved()
+                    );
+                }
+            }
 
             if table.column_count() != 0 {
                 println!("  Columns:");
+                let stats = args.column_stats.then(|| column_stats(&table));
                 let mut offset = 0;
-                for col in table
+                for (i, col) in table
                     .into_columns()
-                    .filter(|c| column_filter.contains(&c.label()))
+                    .enumerate()
+                    .filter(|(_, c)| column_filter.contains(&c.label()))
                 {
                     let mut extra = Cow::Borrowed("");
                     if col.count() > 1 {
@@ -77,6 +92,10 @@ pub fn get_info(args: InfoArgs) -> Result<()> {
                         );
                     }
 
+                    if let Some(stats) = &stats {
+                        println!("      Stats: {}", stats[i]);
+                    }
+
                     offset += col.data_size();
                 }
             }
@@ -86,6 +105,85 @@ pub fn get_info(args: InfoArgs) -> Result<()> {
     Ok(())
 }
 
+/// Statistics for the values in a column
+#[derive(Default)]
+struct ColumnStats {
+    /// Numeric value and its formatted representation
+    min: Option<(f64, String)>,
+    max: Option<(f64, String)>,
+    distinct: HashSet<String>,
+    empty_strings: usize,
+}
+
+impl ColumnStats {
+    fn add(&mut self, value: &Value) {
+        let formatted = value.to_string();
+        if let Some(n) = numeric_value(value) {
+            if self.min.as_ref().is_none_or(|(min, _)| n < *min) {
+                self.min = Some((n, formatted.clone()));
+            }
+            if self.max.as_ref().is_none_or(|(max, _)| n > *max) {
+                self.max = Some((n, formatted.clone()));
+            }
+        }
+        if let Value::String(s) | Value::DebugString(s) = value {
+            if s.is_empty() {
+                self.empty_strings += 1;
+            }
+        }
+        self.distinct.insert(formatted);
+    }
+}
+
+impl Display for ColumnStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let (Some((_, min)), Some((_, max))) = (&self.min, &self.max) {
+            write!(f, "min {min}, max {max}, ")?;
+        }
+        write!(
+            f,
+            "{} distinct values, {} empty strings",
+            self.distinct.len(),
+            self.empty_strings
+        )
+    }
+}
+
+/// Calculates statistics for each column in the table, in column order.
+fn column_stats(table: &CompatTable) -> Vec<ColumnStats> {
+    let mut stats = std::iter::repeat_with(ColumnStats::default)
+        .take(table.column_count())
+        .collect::<Vec<_>>();
+    for row in table.rows() {
+        for (stats, cell) in stats.iter_mut().zip(row.cells()) {
+            match cell {
+                Cell::Single(v) => stats.add(&v),
+                Cell::List(values) => values.iter().for_each(|v| stats.add(v)),
+                // Flag values are only counted as a whole
+                flags @ Cell::Flags(_) => {
+                    stats.distinct.insert(flags.to_string());
+                }
+            }
+        }
+    }
+    stats
+}
+
+/// Returns the value as a number, if it is a numeric type. Hashes and message IDs are not
+/// considered numeric.
+fn numeric_value(value: &Value) -> Option<f64> {
+    Some(match value {
+        Value::UnsignedByte(n) | Value::Percent(n) => *n as f64,
+        Value::UnsignedShort(n) => *n as f64,
+        Value::UnsignedInt(n) => *n as f64,
+        Value::SignedByte(n) => *n as f64,
+        Value::SignedShort(n) => *n as f64,
+        Value::SignedInt(n) => *n as f64,
+        Value::Float(f) => f32::from(*f) as f64,
+        _ => return None,
+    })
+}
+
 fn format_unhashed_label(label: &Label, hash_table: &HashNameTable) -> String {
     let previous_hash = match label {
         Label::Hash(h) => Some(*h),
@@ -97,3 +195,31 @@ fn format_unhashed_label(label: &Label, hash_table: &HashNameTable) -> String {
         (l, _) => l.to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bdat::{
+        compat::CompatTable,
+        modern::{ModernColumn, ModernRow, ModernTableBuilder},
+        Label, Value, ValueType,
+    };
+
+    #[test]
+    fn column_stats() {
+        let table: CompatTable =
+            ModernTableBuilder::with_name(Label::Hash(1))
+                .add_column(ModernColumn::new(ValueType::SignedShort, Label::Hash(2)))
+                .add_column(ModernColumn::new(ValueType::String, Label::Hash(3)))
+                .extend_rows([(-5, ""), (10, "a"), (3, "")].map(|(n, s)| {
+                    ModernRow::new(vec![Value::SignedShort(n), Value::String(s.into())])
+                }))
+                .build()
+                .into();
+        let stats = super::column_stats(&table);
+        assert_eq!(
+            "min -5, max 10, 3 distinct values, 0 empty strings",
+            stats[0].to_string()
+        );
+        assert_eq!("2 distinct values, 2 empty strings", stats[1].to_string());
+    }
+}
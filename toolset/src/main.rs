@@ -11,6 +11,7 @@ use convert::ConvertArgs;
 use diff::DiffArgs;
 use filter::FileFilter;
 use hash::HashArgs;
+use hashdump::HashDumpArgs;
 use info::InfoArgs;
 use itertools::Itertools;
 use util::hash::HashNameTable;
@@ -21,6 +22,7 @@ mod diff;
 pub mod error;
 pub mod filter;
 pub mod hash;
+mod hashdump;
 mod info;
 mod scramble;
 pub mod util;
@@ -55,12 +57,17 @@ enum Commands {
     /// Calculates hashes from file or command input. Hashes are returned one per line, in the order
     /// of the respective input strings.
     Hash(HashArgs),
+    /// Prints all unique hashes (table names, column names and row IDs) found in BDAT files,
+    /// one per line. If a names file is provided (see --hashes), known names are also printed.
+    #[command(name = "hashdump")]
+    HashDump(HashDumpArgs),
 }
 
 #[derive(Args, Default)]
 pub struct InputData {
     /// A file containing unhashed names, one in each line. If provided, all matched hashes will
-    /// be replaced with the unhashed names.
+    /// be replaced with the unhashed names. Lines can also be `<hash> = name` pairs (as printed
+    /// by hashdump), in which case names that don't match their hash are ignored.
     #[arg(long, global = true)]
     hashes: Option<String>,
 
@@ -69,6 +76,10 @@ pub struct InputData {
     #[arg(long, short, value_enum, global = true)]
     game: Option<BdatGame>,
 
+    /// Don't show progress bars. Useful when redirecting output or running in scripts.
+    #[arg(long, visible_alias = "no-progress", global = true)]
+    quiet: bool,
+
     /// The input files. For "bdat-toolset diff", these are the "new" BDAT files.
     #[arg(global = true)]
     files: Vec<String>,
@@ -85,6 +96,7 @@ fn main() -> anyhow::Result<()> {
         Some(Commands::Scramble(args)) => scramble::scramble(args),
         Some(Commands::Unscramble(args)) => scramble::unscramble(args),
         Some(Commands::Hash(args)) => hash::run(args),
+        Some(Commands::HashDump(args)) => hashdump::run(args),
         _ => Ok(()),
     }
 }
@@ -131,7 +143,17 @@ impl InputData {
         match &self.hashes {
             Some(path) => {
                 let file = File::open(path).context("Could not open hashes file")?;
-                Ok(HashNameTable::load_from_names(file)?)
+                let table = HashNameTable::load_from_names(file)?;
+                for (hash, name) in table.mismatches() {
+                    eprintln!("[Warn] Name \"{name}\" does not match hash <{hash:08X}>, ignoring");
+                }
+                for (hash, names) in table.collisions() {
+                    eprintln!(
+                        "[Warn] Names {names:?} share hash <{hash:08X}>, using \"{}\"",
+                        names.last().unwrap()
+                    );
+                }
+                Ok(table)
             }
             None => Ok(HashNameTable::empty()),
         }
@@ -65,7 +65,7 @@ fn run(
         None => Ok(file.with_extension(extension)),
     };
 
-    let progress = ProgressBarState::new("Files", "Tables", files.len());
+    let progress = ProgressBarState::new("Files", "Tables", files.len(), args.input.quiet);
     progress.master_bar.inc(0);
 
     let res = files
@@ -169,7 +169,7 @@ fn scramble_file(path_in: PathBuf, path_out: PathBuf, progress: &ProgressBarStat
         match wii_endian {
             true => header.scramble_data::<WiiEndian>(table),
             false => header.scramble_data::<SwitchEndian>(table),
-        }
+        }?;
         table_bar.inc(1);
         Ok::<_, anyhow::Error>(())
     })?;
@@ -0,0 +1,99 @@
+//! Transparent decompression for BDAT inputs.
+//!
+//! Files with a `.gz` (feature `flate2`) or `.zst` (feature `zstd`) extension are
+//! decompressed into memory. Other files are read as-is.
+
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{BufReader, Read, Result},
+    path::Path,
+};
+
+/// Returns whether files with the given extension are decompressed by [`read_file`].
+pub fn is_compressed(extension: Option<&str>) -> bool {
+    match extension {
+        #[cfg(feature = "flate2")]
+        Some("gz") => true,
+        #[cfg(feature = "zstd")]
+        Some("zst") => true,
+        _ => false,
+    }
+}
+
+/// Reads the entire file, decompressing it if needed.
+pub fn read_file(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    if !is_compressed(path.extension().and_then(OsStr::to_str)) {
+        return std::fs::read(path);
+    }
+    decompress(path.extension(), BufReader::new(File::open(path)?))
+}
+
+/// Returns the file name without its extension, also removing the compression extension
+/// if the file is compressed. (e.g. `file.bdat.gz` becomes `file`)
+pub fn file_stem(path: &Path) -> Option<&str> {
+    let stem = Path::new(path.file_stem()?);
+    if is_compressed(path.extension().and_then(OsStr::to_str)) {
+        return stem.file_stem()?.to_str();
+    }
+    stem.to_str()
+}
+
+#[allow(unused_mut)]
+fn decompress(extension: Option<&OsStr>, mut reader: impl Read) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    match extension.and_then(OsStr::to_str) {
+        #[cfg(feature = "flate2")]
+        Some("gz") => {
+            flate2::read::GzDecoder::new(reader).read_to_end(&mut out)?;
+        }
+        #[cfg(feature = "zstd")]
+        Some("zst") => {
+            zstd::Decoder::new(reader)?.read_to_end(&mut out)?;
+        }
+        _ => {
+            reader.read_to_end(&mut out)?;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    #[test]
+    fn file_stem() {
+        assert_eq!(Some("a"), super::file_stem(Path::new("dir/a.bdat")));
+        assert_eq!(Some("a"), super::file_stem(Path::new("a")));
+        #[cfg(feature = "flate2")]
+        assert_eq!(Some("a"), super::file_stem(Path::new("dir/a.bdat.gz")));
+        #[cfg(not(feature = "zstd"))]
+        assert_eq!(Some("a.bdat"), super::file_stem(Path::new("a.bdat.zst")));
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn gzip() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(b"BDAT").unwrap();
+        let data = encoder.finish().unwrap();
+        assert_eq!(
+            b"BDAT".as_slice(),
+            super::decompress(Some("gz".as_ref()), data.as_slice()).unwrap()
+        );
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd() {
+        let data = zstd::encode_all(b"BDAT".as_slice(), 0).unwrap();
+        assert_eq!(
+            b"BDAT".as_slice(),
+            super::decompress(Some("zst".as_ref()), data.as_slice()).unwrap()
+        );
+    }
+}
@@ -7,7 +7,7 @@ use std::{
 
 use bdat::{
     compat::CompatTable,
-    hash::{murmur3_with_seed, IdentityHasher, PreHashedMap},
+    hash::{murmur3_str, murmur3_with_seed, IdentityHasher, PreHashedMap},
     modern::ModernColumn,
     Label,
 };
@@ -36,9 +36,16 @@ impl Hasher for MurmurHasher {
     }
 }
 
+/// Version of the name cache format, stored in the upper bits of the names file hash so
+/// caches written in an older format are rebuilt.
+const CACHE_VERSION: u64 = 1;
+
 pub struct HashNameTable {
     file_name_hash: u64,
     inner: PreHashedMap<u32, String>,
+    mismatches: Vec<(u32, String)>,
+    /// Names that were replaced by a different name with the same hash
+    collisions: Vec<(u32, String)>,
 }
 
 impl HashNameTable {
@@ -47,6 +54,8 @@ impl HashNameTable {
         Self {
             inner: map,
             file_name_hash: 0,
+            mismatches: Vec::new(),
+            collisions: Vec::new(),
         }
     }
 
@@ -67,12 +76,28 @@ impl HashNameTable {
             let mut string = vec![0u8; len as usize];
             reader.read_exact(&mut string)?;
 
-            res.inner.insert(hash, String::from_utf8(string).unwrap());
+            // Only verified names are cached
+            res.insert_unchecked(hash, String::from_utf8(string).unwrap());
+        }
+
+        reader.read_exact(&mut buf)?;
+        for _ in 0..usize::from_le_bytes(buf) {
+            reader.read_exact(&mut buf[0..6])?;
+            let hash = u32::from_le_bytes(buf[0..4].try_into().unwrap());
+            let len = u16::from_le_bytes(buf[4..6].try_into().unwrap());
+
+            let mut string = vec![0u8; len as usize];
+            reader.read_exact(&mut string)?;
+            res.mismatches
+                .push((hash, String::from_utf8(string).unwrap()));
         }
 
         Ok(res)
     }
 
+    /// Loads names from a names file. Each line is either a name, or a `<hash> = name` pair
+    /// (the format printed by `hashdump`). Names from pairs are checked against their hash,
+    /// see [`HashNameTable::mismatches`].
     pub fn load_from_names(reader: impl Read) -> std::io::Result<Self> {
         let reader = BufReader::new(reader);
         let (lines, bytes) =
@@ -84,7 +109,7 @@ impl HashNameTable {
                     lines.push(line);
                     Ok::<_, std::io::Error>((lines, bytes))
                 })?;
-        let hash = bdat::hash::murmur3(&bytes) as u64;
+        let hash = bdat::hash::murmur3(&bytes) as u64 | CACHE_VERSION << 32;
 
         let mut cached = OpenOptions::new()
             .create(true)
@@ -99,8 +124,11 @@ impl HashNameTable {
 
         let mut res = Self::empty();
         res.file_name_hash = hash;
-        for line in lines {
-            res.inner.insert(bdat::hash::murmur3_str(&line), line);
+        for line in &lines {
+            match parse_name_line(line) {
+                (Some(hash), name) => res.insert(hash, name.to_string()),
+                (None, name) => res.insert_unchecked(murmur3_str(name), name.to_string()),
+            }
         }
 
         cached.rewind()?;
@@ -109,6 +137,12 @@ impl HashNameTable {
         Ok(res)
     }
 
+    /// Replaces hashed labels in the table (name and columns) with their unhashed
+    /// names, if known.
+    ///
+    /// Only names that were verified to hash to the original value are used, so
+    /// converted labels always hash back to the same value on repack. See
+    /// [`HashNameTable::mismatches`] for names that failed verification.
     pub fn convert_all<'b>(&'b self, table: &mut CompatTable<'b>) {
         if table.is_legacy() || self.inner.is_empty() {
             return;
@@ -133,6 +167,64 @@ impl HashNameTable {
         self.inner.get(&hash).map(|s| s.as_str())
     }
 
+    /// Returns the `(hash, name)` pairs from the names file whose name doesn't hash to the
+    /// associated hash. These names are never used to convert labels.
+    pub fn mismatches(&self) -> &[(u32, String)] {
+        &self.mismatches
+    }
+
+    /// Returns a map of all known names to their hashes, including names that
+    /// collide with others.
+    pub fn reverse(&self) -> HashMap<String, u32> {
+        self.inner
+            .iter()
+            .chain(self.collisions.iter().map(|(h, n)| (h, n)))
+            .map(|(&hash, name)| (name.clone(), hash))
+            .collect()
+    }
+
+    /// Returns the hashes that multiple distinct names hash to, along with
+    /// those names, sorted by hash.
+    ///
+    /// For each hash, the last name is the one used to convert labels.
+    pub fn collisions(&self) -> Vec<(u32, Vec<String>)> {
+        let mut res: Vec<(u32, Vec<String>)> = Vec::new();
+        for (hash, name) in &self.collisions {
+            match res.iter_mut().find(|(h, _)| h == hash) {
+                Some((_, names)) if !names.contains(name) => names.push(name.clone()),
+                Some(_) => {}
+                None => res.push((*hash, vec![name.clone()])),
+            }
+        }
+        for (hash, names) in &mut res {
+            // The same name may have been inserted again after a colliding one
+            let current = &self.inner[hash];
+            names.retain(|n| n != current);
+            names.push(current.clone());
+        }
+        res.sort_unstable_by_key(|(hash, _)| *hash);
+        res
+    }
+
+    /// Registers a name for the given hash, after checking that the name
+    /// actually hashes to that value.
+    fn insert(&mut self, hash: u32, name: String) {
+        if murmur3_str(&name) != hash {
+            self.mismatches.push((hash, name));
+            return;
+        }
+        self.insert_unchecked(hash, name);
+    }
+
+    /// Registers a name for the given hash, which must be the name's hash.
+    fn insert_unchecked(&mut self, hash: u32, name: String) {
+        if let Some(old) = self.inner.insert(hash, name) {
+            if old != self.inner[&hash] {
+                self.collisions.push((hash, old));
+            }
+        }
+    }
+
     pub fn convert_label<'b>(&'b self, label: &mut Label<'b>) {
         if let Label::Hash(hash) = label {
             *label = self.get_label(*hash);
@@ -158,14 +250,118 @@ impl HashNameTable {
     /// with [`HashNameTable::read`].
     pub fn write(&self, writer: &mut impl Write) -> std::io::Result<()> {
         writer.write_all(&self.file_name_hash.to_le_bytes())?;
-        writer.write_all(&self.inner.len().to_le_bytes())?;
-        for (k, v) in &self.inner {
-            writer.write_all(&k.to_le_bytes())?;
-
-            let bytes = v.as_bytes();
-            writer.write_all(&(bytes.len() as u16).to_le_bytes())?;
-            writer.write_all(bytes)?;
+        writer.write_all(&(self.collisions.len() + self.inner.len()).to_le_bytes())?;
+        // Replaced names come first, so reading them back yields the same table
+        for (k, v) in self
+            .collisions
+            .iter()
+            .map(|(k, v)| (k, v))
+            .chain(&self.inner)
+        {
+            Self::write_entry(writer, *k, v)?;
+        }
+        writer.write_all(&self.mismatches.len().to_le_bytes())?;
+        for (k, v) in &self.mismatches {
+            Self::write_entry(writer, *k, v)?;
         }
         Ok(())
     }
+
+    fn write_entry(writer: &mut impl Write, hash: u32, name: &str) -> std::io::Result<()> {
+        writer.write_all(&hash.to_le_bytes())?;
+        let bytes = name.as_bytes();
+        writer.write_all(&(bytes.len() as u16).to_le_bytes())?;
+        writer.write_all(bytes)
+    }
+}
+
+/// Parses a line from a names file, returning the explicit hash if the line is a
+/// `<hash> = name` pair.
+fn parse_name_line(line: &str) -> (Option<u32>, &str) {
+    line.strip_prefix('<')
+        .and_then(|l| l.split_once("> = "))
+        .and_then(|(hash, name)| Some((u32::from_str_radix(hash, 16).ok()?, name)))
+        .map_or((None, line), |(hash, name)| (Some(hash), name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_name_line, HashNameTable};
+    use bdat::hash::murmur3_str;
+
+    #[test]
+    fn mismatched_names() {
+        let mut table = HashNameTable::empty();
+        table.insert(murmur3_str("Good"), "Good".to_string());
+        table.insert(0xDEADBEEF, "Bad".to_string());
+
+        assert_eq!(Some("Good"), table.unhash(murmur3_str("Good")));
+        assert_eq!(None, table.unhash(0xDEADBEEF));
+        assert_eq!(&[(0xDEADBEEF, "Bad".to_string())], table.mismatches());
+
+        // Mismatches are kept in the cache
+        let mut cached = Vec::new();
+        table.write(&mut cached).unwrap();
+        let table = HashNameTable::read(&cached[8..], 0).unwrap();
+        assert_eq!(&[(0xDEADBEEF, "Bad".to_string())], table.mismatches());
+    }
+
+    #[test]
+    fn name_lines() {
+        assert_eq!((None, "Name"), parse_name_line("Name"));
+        assert_eq!(
+            (Some(0xDEADBEEF), "Name"),
+            parse_name_line("<DEADBEEF> = Name")
+        );
+        assert_eq!((None, "<Name>"), parse_name_line("<Name>"));
+    }
+
+    #[test]
+    fn collisions() {
+        // These two names have the same hash
+        let (a, b) = ("name_116952", "name_145325");
+        let hash = murmur3_str(a);
+        assert_eq!(hash, murmur3_str(b));
+
+        let mut table = HashNameTable::empty();
+        table.insert(hash, a.to_string());
+        table.insert(hash, b.to_string());
+        table.insert(hash, b.to_string());
+        table.insert(murmur3_str("Other"), "Other".to_string());
+
+        assert_eq!(
+            vec![(hash, vec![a.to_string(), b.to_string()])],
+            table.collisions()
+        );
+        assert_eq!(Some(b), table.unhash(hash));
+
+        let reverse = table.reverse();
+        assert_eq!(3, reverse.len());
+        assert_eq!(Some(&hash), reverse.get(a));
+        assert_eq!(Some(&hash), reverse.get(b));
+
+        let mut cached = Vec::new();
+        table.write(&mut cached).unwrap();
+        let table = HashNameTable::read(&cached[8..], 0).unwrap();
+        assert_eq!(
+            vec![(hash, vec![a.to_string(), b.to_string()])],
+            table.collisions()
+        );
+        assert_eq!(Some(b), table.unhash(hash));
+    }
+
+    #[test]
+    fn collisions_reinserted() {
+        let (a, b) = ("name_116952", "name_145325");
+        let hash = murmur3_str(a);
+
+        let mut table = HashNameTable::empty();
+        for name in [a, b, a, b, a] {
+            table.insert(hash, name.to_string());
+        }
+        assert_eq!(
+            vec![(hash, vec![b.to_string(), a.to_string()])],
+            table.collisions()
+        );
+    }
 }
@@ -1,13 +1,15 @@
 use anyhow::{Context, Result};
 use bdat::{
-    compat::CompatTable, BdatFile, BdatResult, BdatVersion, LegacyVersion, SwitchEndian, WiiEndian,
+    compat::CompatTable, legacy::LegacyWriteOptions, BdatFile, BdatResult, BdatVersion,
+    LegacyVersion, SwitchEndian, WiiEndian,
 };
 use clap::{Args, ValueEnum};
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use itertools::Itertools;
 use std::io::{Seek, Write};
 use std::path::{Path, PathBuf};
 
+pub mod compress;
 pub mod fixed_vec;
 pub mod hash;
 
@@ -78,10 +80,12 @@ impl BdatGame {
         })
     }
 
+    /// Writes tables in this game's format. The options are only used for legacy formats.
     pub fn to_writer<'b, W: Write + Seek>(
         self,
         writer: W,
         tables: impl IntoIterator<Item = CompatTable<'b>>,
+        opts: LegacyWriteOptions,
     ) -> BdatResult<()> {
         if self == Self::Modern {
             let tables = tables
@@ -95,23 +99,39 @@ impl BdatGame {
             .map(CompatTable::into_legacy)
             .collect_vec();
         match self {
-            Self::Wii => {
-                bdat::legacy::to_writer::<_, WiiEndian>(writer, tables, LegacyVersion::Wii)
-            }
-            Self::LegacySwitch => {
-                bdat::legacy::to_writer::<_, SwitchEndian>(writer, tables, LegacyVersion::Switch)
-            }
-            Self::Xcx => bdat::legacy::to_writer::<_, WiiEndian>(writer, tables, LegacyVersion::X),
-            Self::New3ds => {
-                bdat::legacy::to_writer::<_, SwitchEndian>(writer, tables, LegacyVersion::New3ds)
-            }
+            Self::Wii => bdat::legacy::to_writer_options::<_, WiiEndian>(
+                writer,
+                tables,
+                LegacyVersion::Wii,
+                opts,
+            ),
+            Self::LegacySwitch => bdat::legacy::to_writer_options::<_, SwitchEndian>(
+                writer,
+                tables,
+                LegacyVersion::Switch,
+                opts,
+            ),
+            Self::Xcx => bdat::legacy::to_writer_options::<_, WiiEndian>(
+                writer,
+                tables,
+                LegacyVersion::X,
+                opts,
+            ),
+            Self::New3ds => bdat::legacy::to_writer_options::<_, SwitchEndian>(
+                writer,
+                tables,
+                LegacyVersion::New3ds,
+                opts,
+            ),
             Self::Modern => unreachable!(),
         }
     }
 
-    pub fn to_vec<'b, W: Write + Seek>(
+    /// Writes tables in this game's format. The options are only used for legacy formats.
+    pub fn to_vec<'b>(
         self,
         tables: impl IntoIterator<Item = CompatTable<'b>>,
+        opts: LegacyWriteOptions,
     ) -> BdatResult<Vec<u8>> {
         if self == Self::Modern {
             let tables = tables
@@ -125,19 +145,29 @@ impl BdatGame {
             .map(CompatTable::into_legacy)
             .collect_vec();
         match self {
-            Self::Wii => bdat::legacy::to_vec::<WiiEndian>(tables, LegacyVersion::Wii),
+            Self::Wii => {
+                bdat::legacy::to_vec_options::<WiiEndian>(tables, LegacyVersion::Wii, opts)
+            }
             Self::LegacySwitch => {
-                bdat::legacy::to_vec::<SwitchEndian>(tables, LegacyVersion::Switch)
+                bdat::legacy::to_vec_options::<SwitchEndian>(tables, LegacyVersion::Switch, opts)
+            }
+            Self::Xcx => bdat::legacy::to_vec_options::<WiiEndian>(tables, LegacyVersion::X, opts),
+            Self::New3ds => {
+                bdat::legacy::to_vec_options::<SwitchEndian>(tables, LegacyVersion::New3ds, opts)
             }
-            Self::Xcx => bdat::legacy::to_vec::<WiiEndian>(tables, LegacyVersion::X),
-            _ => unreachable!(),
+            Self::Modern => unreachable!(),
         }
     }
 }
 
 impl ProgressBarState {
-    pub fn new(master_name: &str, child_name: &str, total: usize) -> Self {
-        let multi_bar = MultiProgress::new();
+    /// Creates a new master progress bar. If `hidden` is set, neither the master bar nor
+    /// its children are drawn.
+    pub fn new(master_name: &str, child_name: &str, total: usize, hidden: bool) -> Self {
+        let multi_bar = match hidden {
+            true => MultiProgress::with_draw_target(ProgressDrawTarget::hidden()),
+            false => MultiProgress::new(),
+        };
         let master_bar = multi_bar.add(
             ProgressBar::new(total as u64)
                 .with_style(Self::build_progress_style(master_name, true)),
@@ -168,6 +198,14 @@ impl ProgressBarState {
         self.multi_bar.println(msg)
     }
 
+    /// Prints a line to the standard output. Unlike [`println`], the line is also printed
+    /// when progress bars are hidden (e.g. when not running in a terminal).
+    ///
+    /// [`println`]: Self::println
+    pub fn print_output<I: AsRef<str>>(&self, msg: I) {
+        self.multi_bar.suspend(|| println!("{}", msg.as_ref()))
+    }
+
     fn build_progress_style(label: &str, with_time: bool) -> ProgressStyle {
         ProgressStyle::with_template(&match with_time {
             true => format!("{{spinner:.cyan}} [{{elapsed_precise:.cyan}}] {label}{{msg}}: {{human_pos}}/{{human_len}} ({{percent}}%) [{{bar:.cyan/blue}}] ETA: {{eta}}"),